@@ -0,0 +1,65 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use rs_document::Document;
+use std::collections::HashMap;
+
+const LOREM: &str = include_str!("../python/tests/lorem.txt");
+
+/// Benchmarks a cleaner on a fresh copy of the lorem ipsum document each iteration.
+fn bench_cleaner(c: &mut Criterion, name: &str, cleaner: fn(&mut Document)) {
+    let document = Document::new(LOREM, HashMap::new());
+    c.bench_function(name, |b| {
+        b.iter_batched_ref(
+            || document.clone(),
+            |document| cleaner(black_box(document)),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn cleaners(c: &mut Criterion) {
+    bench_cleaner(c, "clean_bullets", Document::clean_bullets);
+    bench_cleaner(c, "clean_ligatures", Document::clean_ligatures);
+    bench_cleaner(
+        c,
+        "clean_extra_whitespace",
+        Document::clean_extra_whitespace,
+    );
+    bench_cleaner(c, "clean_non_ascii_chars", Document::clean_non_ascii_chars);
+    bench_cleaner(c, "new_line_grouper", Document::new_line_grouper);
+    bench_cleaner(c, "clean", Document::clean);
+
+    let paragraphs = LOREM
+        .split(". ")
+        .map(|sentence| format!("\u{2022} {}\nwrapped onto a second line", sentence))
+        .collect::<Vec<String>>()
+        .join("\n\n");
+    let document = Document::new(paragraphs, HashMap::new());
+    c.bench_function("group_broken_paragraphs", |b| {
+        b.iter_batched_ref(
+            || document.clone(),
+            |document| document.group_broken_paragraphs(),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn clean_and_split(c: &mut Criterion) {
+    let documents: Vec<Document> = (0..100)
+        .map(|_| Document::new(LOREM, HashMap::new()))
+        .collect();
+    c.bench_function("clean_and_split_100_docs", |b| {
+        b.iter(|| {
+            documents
+                .iter()
+                .flat_map(|document| {
+                    let mut document = document.clone();
+                    document.clean();
+                    document.recursive_character_splitter(black_box(2000), false, "id", None)
+                })
+                .count()
+        })
+    });
+}
+
+criterion_group!(benches, cleaners, clean_and_split);
+criterion_main!(benches);
@@ -0,0 +1,352 @@
+//! The Node.js bindings, a thin layer over the core `Document` API.
+//!
+//! Names follow JavaScript conventions: napi-rs exposes `page_content` as
+//! `pageContent`, `recursive_character_splitter` as `recursiveCharacterSplitter`, and
+//! so on.
+
+use napi::{Error, Status};
+use napi_derive::napi;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// A document with page content and string metadata, like LangChain's Document.
+#[napi(js_name = "Document")]
+pub struct JsDocument {
+    inner: rs_document::Document,
+}
+
+impl From<rs_document::Document> for JsDocument {
+    fn from(inner: rs_document::Document) -> Self {
+        JsDocument { inner }
+    }
+}
+
+/// Options shared by the splitters.
+#[napi(object)]
+#[derive(Default)]
+pub struct SplitOptions {
+    /// Add `chunk_index`, `total_chunks` and `parent_id` metadata to every chunk.
+    pub provenance: Option<bool>,
+    /// The metadata key holding a document's id. Defaults to `"id"`.
+    pub id_key: Option<String>,
+    /// A template rendered from each chunk's metadata and prepended to the chunk.
+    pub context_template: Option<String>,
+    /// Pack whole sentences into each chunk and overlap neighbouring chunks by this many
+    /// sentences, instead of by a third of the chunk size. Only used by
+    /// `recursiveCharacterSplitter` and `cleanAndSplitDocs`.
+    pub overlap_sentences: Option<u32>,
+    /// How many units each chunk repeats from the end of the one before it. Only used by
+    /// `splitOnNumCharacters`.
+    pub overlap: Option<u32>,
+    /// What `splitOnNumCharacters` counts: `"graphemes"` (the default), `"characters"` or
+    /// `"bytes"`.
+    pub unit: Option<String>,
+    /// Leave out chunks shorter than this, in the unit of the chunk size.
+    pub min_chunk_size: Option<u32>,
+    /// Leave out chunks that are only whitespace.
+    pub drop_empty: Option<bool>,
+}
+
+impl SplitOptions {
+    fn provenance(&self) -> bool {
+        self.provenance.unwrap_or(false)
+    }
+
+    fn id_key(&self) -> &str {
+        self.id_key.as_deref().unwrap_or("id")
+    }
+
+    /// Splits with `recursive_character_splitter`, or with `sentence_overlap_splitter`
+    /// when `overlap_sentences` is set.
+    fn recursive_split(
+        &self,
+        document: &rs_document::Document,
+        chunk_size: u32,
+    ) -> Vec<rs_document::Document> {
+        let chunks = match self.overlap_sentences {
+            Some(overlap_sentences) => document.sentence_overlap_splitter(
+                chunk_size as usize,
+                overlap_sentences as usize,
+                false,
+                self.id_key(),
+                None,
+            ),
+            None => document.recursive_character_splitter(
+                chunk_size as usize,
+                false,
+                self.id_key(),
+                None,
+            ),
+        };
+        self.finish(document, chunks, &str::len)
+    }
+
+    /// Leaves out small and empty chunks of `document`, measured by `length`, and then
+    /// adds provenance and context headers to the rest. The chunks must have been split
+    /// without either.
+    fn finish(
+        &self,
+        document: &rs_document::Document,
+        mut chunks: Vec<rs_document::Document>,
+        length: &dyn Fn(&str) -> usize,
+    ) -> Vec<rs_document::Document> {
+        rs_document::Document::filter_chunks(
+            &mut chunks,
+            self.min_chunk_size.unwrap_or(0) as usize,
+            self.drop_empty.unwrap_or(false),
+            length,
+        );
+        document.finish_chunks(
+            &mut chunks,
+            self.provenance(),
+            self.id_key(),
+            self.context_template.as_deref(),
+        );
+        chunks
+    }
+}
+
+/// Anything with a `pageContent` and optional `metadata`, such as a `Document` or a
+/// LangChain.js document.
+#[napi(object)]
+pub struct DocumentLike {
+    pub page_content: String,
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+fn to_documents(chunks: Vec<rs_document::Document>) -> Vec<JsDocument> {
+    chunks.into_iter().map(JsDocument::from).collect()
+}
+
+#[napi]
+impl JsDocument {
+    /// Returns a new document with page content and metadata.
+    #[napi(constructor)]
+    pub fn new(page_content: String, metadata: Option<HashMap<String, String>>) -> Self {
+        rs_document::Document::new(page_content, metadata.unwrap_or_default()).into()
+    }
+
+    /// The text of the document.
+    #[napi(getter)]
+    pub fn page_content(&self) -> String {
+        self.inner.page_content.clone()
+    }
+
+    #[napi(setter)]
+    pub fn set_page_content(&mut self, page_content: String) {
+        self.inner.page_content = page_content;
+    }
+
+    /// Metadata for the document. Currently only supports strings as keys *and* values.
+    #[napi(getter)]
+    pub fn metadata(&self) -> HashMap<String, String> {
+        self.inner.metadata.clone()
+    }
+
+    #[napi(setter)]
+    pub fn set_metadata(&mut self, metadata: HashMap<String, String>) {
+        self.inner.metadata = metadata;
+    }
+
+    /// Remove Non Ascii characters from document's page_content.
+    #[napi]
+    pub fn clean_non_ascii_chars(&mut self) {
+        self.inner.clean_non_ascii_chars()
+    }
+
+    /// Remove bullets from page_content using a regular expression pattern.
+    #[napi]
+    pub fn clean_bullets(&mut self) {
+        self.inner.clean_bullets()
+    }
+
+    /// Replace common ligatures like æ in page_content.
+    #[napi]
+    pub fn clean_ligatures(&mut self) {
+        self.inner.clean_ligatures()
+    }
+
+    /// Remove extraneous whitespace from page_content
+    #[napi]
+    pub fn clean_extra_whitespace(&mut self) {
+        self.inner.clean_extra_whitespace()
+    }
+
+    /// Groups paragraphs in page_content that have line breaks.
+    #[napi]
+    pub fn group_broken_paragraphs(&mut self) {
+        self.inner.group_broken_paragraphs()
+    }
+
+    /// Concatenates page_content that has one-line paragraph break pattern.
+    #[napi]
+    pub fn new_line_grouper(&mut self) {
+        self.inner.new_line_grouper()
+    }
+
+    /// Groups page_content with `newLineGrouper` or `groupBrokenParagraphs`,
+    /// depending on how many of its lines are blank.
+    #[napi]
+    pub fn auto_paragraph_grouper(&mut self) {
+        self.inner.auto_paragraph_grouper()
+    }
+
+    /// Reflows text laid out in two or three columns side by side into reading order,
+    /// one column after the other.
+    #[napi]
+    pub fn reflow_columns(&mut self) {
+        self.inner.reflow_columns()
+    }
+
+    /// Strips footnote markers and footnotes, or moves the footnotes inline or into
+    /// metadata. `mode` is `"strip"` (the default), `"inline"` or `"metadata"`.
+    #[napi]
+    pub fn clean_footnotes(&mut self, mode: Option<String>) -> napi::Result<()> {
+        let mode: rs_document::FootnoteMode =
+            mode.as_deref()
+                .unwrap_or("strip")
+                .parse()
+                .map_err(|error: rs_document::Error| {
+                    Error::new(Status::InvalidArg, error.to_string())
+                })?;
+        self.inner.clean_footnotes(mode);
+        Ok(())
+    }
+
+    /// A helper function that calls all of the cleaning functions at once
+    #[napi]
+    pub fn clean(&mut self) {
+        self.inner.clean()
+    }
+
+    /// An opinionated splitter based on LangChain's RecursiveCharacterTextSplitter.
+    ///
+    /// This splitter will always default to 1/3 of the chunk_size as the chunk_overlap,
+    /// unless `overlapSentences` is set.
+    #[napi]
+    pub fn recursive_character_splitter(
+        &self,
+        chunk_size: u32,
+        options: Option<SplitOptions>,
+    ) -> Vec<JsDocument> {
+        let options = options.unwrap_or_default();
+        to_documents(options.recursive_split(&self.inner, chunk_size))
+    }
+
+    /// A variant of `recursiveCharacterSplitter` for markdown that never splits inside a
+    /// fenced code block or a table, unless the block alone exceeds chunk_size.
+    ///
+    /// Tables that are too large are split into groups of rows with the header repeated,
+    /// and chunks that contain a table get `has_table` metadata.
+    #[napi]
+    pub fn markdown_splitter(
+        &self,
+        chunk_size: u32,
+        options: Option<SplitOptions>,
+    ) -> Vec<JsDocument> {
+        let options = options.unwrap_or_default();
+        let chunks =
+            self.inner
+                .markdown_splitter(chunk_size as usize, false, options.id_key(), None);
+        to_documents(options.finish(&self.inner, chunks, &str::len))
+    }
+
+    /// A basic splitter to split on a number of characters, without breaking up grapheme
+    /// clusters.
+    #[napi]
+    pub fn split_on_num_characters(
+        &self,
+        num_characters: u32,
+        options: Option<SplitOptions>,
+    ) -> napi::Result<Vec<JsDocument>> {
+        let options = options.unwrap_or_default();
+        let unit: rs_document::SizeUnit = options
+            .unit
+            .as_deref()
+            .unwrap_or("graphemes")
+            .parse()
+            .map_err(|error: rs_document::Error| {
+                Error::new(Status::InvalidArg, error.to_string())
+            })?;
+        let mut chunks = self
+            .inner
+            .split_on_num_characters(
+                num_characters,
+                options.overlap.unwrap_or(0),
+                unit,
+                false,
+                options.id_key(),
+            )
+            .map_err(|error| Error::new(Status::InvalidArg, error.to_string()))?;
+        rs_document::Document::filter_chunks(
+            &mut chunks,
+            options.min_chunk_size.unwrap_or(0) as usize,
+            options.drop_empty.unwrap_or(false),
+            &|text| unit.measure(text),
+        );
+        self.inner
+            .finish_chunks(&mut chunks, options.provenance(), options.id_key(), None);
+        Ok(to_documents(chunks))
+    }
+
+    /// Prepends a context header, rendered from the document's metadata, to page_content.
+    #[napi]
+    pub fn add_context_header(&mut self, template: String) {
+        self.inner.add_context_header(&template)
+    }
+
+    /// Splits the document into large parent chunks and small child chunks, for the
+    /// parent document retriever pattern.
+    ///
+    /// Returns a `[parents, children]` pair.
+    #[napi(ts_return_type = "[Document[], Document[]]")]
+    pub fn hierarchical_split(
+        &self,
+        parent_size: u32,
+        child_size: u32,
+        id_key: Option<String>,
+    ) -> napi::Result<Vec<Vec<JsDocument>>> {
+        let (parents, children) = self
+            .inner
+            .hierarchical_split(
+                parent_size as usize,
+                child_size as usize,
+                id_key.as_deref().unwrap_or("id"),
+            )
+            .map_err(|error| Error::new(Status::InvalidArg, error.to_string()))?;
+        Ok(vec![to_documents(parents), to_documents(children)])
+    }
+
+    /// Create a human readable string of the document.
+    #[napi(js_name = "toString")]
+    pub fn to_js_string(&self) -> String {
+        format!(
+            "Document(page_content=\"{}\", metadata={:?})",
+            self.inner.page_content, self.inner.metadata
+        )
+    }
+}
+
+/// Cleans and splits an array of documents in parallel.
+///
+/// Accepts `Document` instances or any objects with `pageContent` and `metadata`.
+#[napi]
+pub fn clean_and_split_docs(
+    docs: Vec<DocumentLike>,
+    chunk_size: u32,
+    options: Option<SplitOptions>,
+) -> Vec<JsDocument> {
+    let options = options.unwrap_or_default();
+    let chunks: Vec<rs_document::Document> = docs
+        .into_par_iter()
+        .flat_map(|document| {
+            let mut document = rs_document::Document::new(
+                document.page_content,
+                document.metadata.unwrap_or_default(),
+            );
+            document.clean();
+            options.recursive_split(&document, chunk_size)
+        })
+        .collect();
+    to_documents(chunks)
+}
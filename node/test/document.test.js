@@ -0,0 +1,129 @@
+const test = require("node:test");
+const assert = require("node:assert");
+const { Document, cleanAndSplitDocs } = require("../index.js");
+
+test("attributes", () => {
+  const doc = new Document("A".repeat(20), { Hello: "World" });
+  assert.strictEqual(doc.pageContent, "A".repeat(20));
+  assert.deepStrictEqual(doc.metadata, { Hello: "World" });
+  doc.pageContent = "changed";
+  assert.strictEqual(doc.pageContent, "changed");
+});
+
+test("toString", () => {
+  const doc = new Document("hello", { Hello: "World" });
+  assert.strictEqual(
+    String(doc),
+    'Document(page_content="hello", metadata={"Hello": "World"})',
+  );
+});
+
+test("cleaners", () => {
+  const doc = new Document("ITEM 1.     BUSINESS ");
+  doc.cleanExtraWhitespace();
+  assert.strictEqual(doc.pageContent, "ITEM 1. BUSINESS");
+});
+
+test("splitting", () => {
+  const doc = new Document("A".repeat(20), { Hello: "World" });
+  const split = doc.splitOnNumCharacters(5);
+  assert.strictEqual(split.length, 4);
+  assert.strictEqual(split[0].pageContent, "AAAAA");
+  assert.deepStrictEqual(split[0].metadata, { Hello: "World" });
+});
+
+test("character splitting with overlap keeps graphemes whole", () => {
+  const doc = new Document("e\u0301abcdefg", {});
+  const split = doc.splitOnNumCharacters(4, { overlap: 1 });
+  assert.deepStrictEqual(
+    split.map((chunk) => chunk.pageContent),
+    ["e\u0301abc", "cdef", "fg"],
+  );
+  assert.throws(() => doc.splitOnNumCharacters(4, { unit: "words" }), /Unknown unit/);
+});
+
+test("splitters drop small and empty chunks", () => {
+  const doc = new Document("A".repeat(20) + " ".repeat(10) + "BBB", {});
+  const split = doc.splitOnNumCharacters(10, {
+    provenance: true,
+    dropEmpty: true,
+    minChunkSize: 4,
+  });
+  assert.deepStrictEqual(
+    split.map((chunk) => chunk.metadata.total_chunks),
+    ["2", "2"],
+  );
+});
+
+test("footnotes move into metadata", () => {
+  const doc = new Document("Shown in prior work.[1]\n\n[1] Smith 2020.", {});
+  doc.cleanFootnotes("metadata");
+  assert.strictEqual(doc.pageContent, "Shown in prior work.");
+  assert.deepStrictEqual(doc.metadata, { footnote_1: "Smith 2020." });
+  assert.throws(() => doc.cleanFootnotes("move"), /Unknown footnote mode/);
+});
+
+test("columns are reflowed into reading order", () => {
+  const doc = new Document(
+    [
+      "The fox ran down the lane     and met a bear at the",
+      "at the end of a long day,     end of it. They walked",
+      "without stopping at all,      home together.",
+    ].join("\n"),
+    {},
+  );
+  doc.reflowColumns();
+  assert.deepStrictEqual(doc.pageContent.split("\n"), [
+    "The fox ran down the lane",
+    "at the end of a long day,",
+    "without stopping at all,",
+    "and met a bear at the",
+    "end of it. They walked",
+    "home together.",
+  ]);
+});
+
+test("recursive splitting with provenance", () => {
+  const doc = new Document("A".repeat(20), { id: "doc-1" });
+  const split = doc.recursiveCharacterSplitter(9, { provenance: true });
+  assert.ok(split.every((chunk) => chunk.pageContent.length <= 9));
+  assert.strictEqual(split[0].metadata.parent_id, "doc-1");
+  assert.strictEqual(split[0].metadata.chunk_index, "0");
+});
+
+test("recursive splitting with sentence overlap", () => {
+  const doc = new Document("First one here. Second one here. Third one here.");
+  const split = doc.recursiveCharacterSplitter(32, { overlapSentences: 1 });
+  assert.deepStrictEqual(
+    split.map((chunk) => chunk.pageContent),
+    ["First one here. Second one here.", "Second one here. Third one here."],
+  );
+});
+
+test("markdown splitting keeps code blocks whole", () => {
+  const code = "```js\nconst a = 1;\nconst b = 2;\n```";
+  const doc = new Document(`Some intro text here.\n\n${code}\n\nThe end.`);
+  const split = doc.markdownSplitter(45);
+  assert.ok(split.some((chunk) => chunk.pageContent.includes(code)));
+  assert.ok(split.every((chunk) => chunk.pageContent.split("```").length % 2 === 1));
+});
+
+test("hierarchical split", () => {
+  const doc = new Document("word ".repeat(200));
+  const [parents, children] = doc.hierarchicalSplit(300, 100);
+  assert.ok(parents.length < children.length);
+  assert.throws(() => doc.hierarchicalSplit(100, 300));
+});
+
+test("cleanAndSplitDocs", () => {
+  const docs = [
+    new Document("Some   text", { source: "a" }),
+    { pageContent: "More text", metadata: { source: "b" } },
+  ];
+  const chunks = cleanAndSplitDocs(docs, 1000);
+  assert.deepStrictEqual(
+    chunks.map((chunk) => chunk.pageContent),
+    ["Some text", "More text"],
+  );
+  assert.strictEqual(chunks[1].metadata.source, "b");
+});
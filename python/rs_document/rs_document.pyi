@@ -1,11 +1,258 @@
+import asyncio
+from os import PathLike
+from typing import IO, Any, Iterable, Iterator, Literal, Protocol, TypedDict, Union, overload
+
+import langchain_core.documents
+import pyarrow
 from typing_extensions import Self
 
-def clean_and_split_docs(docs: list[Document], chunk_size: int) -> list[Document]: ...
+class _HasPageContent(Protocol):
+    page_content: str
+    metadata: dict[str, Any]
+
+class ReadabilityScores(TypedDict):
+    flesch_kincaid_grade: float
+    flesch_reading_ease: float
+    smog_index: float
+    average_sentence_length: float
+    sentences: int
+    words: int
+
+class LengthStats(TypedDict):
+    total: int
+    mean: float
+    min: int
+    max: int
+    p50: int
+    p90: int
+    p95: int
+    p99: int
+
+class HistogramBucket(TypedDict):
+    min: int
+    max: int
+    count: int
+
+class ChunkStats(TypedDict):
+    count: int
+    sizes: LengthStats
+    histogram: list[HistogramBucket]
+
+class CorpusStats(TypedDict):
+    documents: int
+    characters: LengthStats
+    words: LengthStats
+    tokens: LengthStats
+    chunks: ChunkStats | None
+    metadata_coverage: dict[str, float]
+
+class ProcessResult(TypedDict):
+    chunks: list[Document]
+    added: list[str]
+    removed: list[str]
+    processed: int
+    reused: int
+
+_DecodeErrors = Literal["detect", "replace", "error"]
+DocumentLike = Union[Document, dict[str, Any], _HasPageContent]
+StrPath = Union[str, PathLike[str]]
+
+@overload
+def clean_and_split_docs(
+    docs: Iterable[DocumentLike],
+    chunk_size: int,
+    provenance: bool = False,
+    id_key: str = "id",
+    context_template: str | None = None,
+    output_type: Literal["rs_document"] = "rs_document",
+    overlap_sentences: int | None = None,
+    unit: Literal["characters", "tokens"] = "characters",
+    encoding: str = "cl100k_base",
+    min_chunk_size: int = 0,
+    drop_empty: bool = False,
+) -> list[Document]: ...
+@overload
+def clean_and_split_docs(
+    docs: Iterable[DocumentLike],
+    chunk_size: int,
+    provenance: bool = False,
+    id_key: str = "id",
+    context_template: str | None = None,
+    *,
+    output_type: Literal["langchain"],
+    overlap_sentences: int | None = None,
+    unit: Literal["characters", "tokens"] = "characters",
+    encoding: str = "cl100k_base",
+    min_chunk_size: int = 0,
+    drop_empty: bool = False,
+) -> list[langchain_core.documents.Document]: ...
+@overload
+def clean_and_split_docs_async(
+    docs: Iterable[DocumentLike],
+    chunk_size: int,
+    provenance: bool = False,
+    id_key: str = "id",
+    context_template: str | None = None,
+    output_type: Literal["rs_document"] = "rs_document",
+    overlap_sentences: int | None = None,
+    unit: Literal["characters", "tokens"] = "characters",
+    encoding: str = "cl100k_base",
+    min_chunk_size: int = 0,
+    drop_empty: bool = False,
+) -> asyncio.Future[list[Document]]: ...
+@overload
+def clean_and_split_docs_async(
+    docs: Iterable[DocumentLike],
+    chunk_size: int,
+    provenance: bool = False,
+    id_key: str = "id",
+    context_template: str | None = None,
+    *,
+    output_type: Literal["langchain"],
+    overlap_sentences: int | None = None,
+    unit: Literal["characters", "tokens"] = "characters",
+    encoding: str = "cl100k_base",
+    min_chunk_size: int = 0,
+    drop_empty: bool = False,
+) -> asyncio.Future[list[langchain_core.documents.Document]]: ...
+def coalesce_chunks(
+    docs: Iterable[DocumentLike], min_size: int, max_size: int
+) -> list[Document]: ...
+def docs_to_msgpack(docs: Iterable[DocumentLike]) -> bytes: ...
+def docs_from_msgpack(data: bytes) -> list[Document]: ...
+def load_csv(
+    path: StrPath,
+    content_columns: list[str] | None = None,
+    metadata_columns: list[str] | None = None,
+    delimiter: str = ",",
+    encoding: str | None = None,
+    errors: _DecodeErrors = "detect",
+) -> list[Document]: ...
+def corpus_stats(
+    docs: Iterable[DocumentLike],
+    chunk_size: int | None = None,
+    unit: Literal["characters", "tokens"] = "characters",
+    encoding: str = "cl100k_base",
+    overlap_sentences: int | None = None,
+    bins: int = 10,
+) -> CorpusStats: ...
+def count_tokens_batch(
+    docs: Iterable[DocumentLike], encoding: str = "cl100k_base"
+) -> list[int]: ...
+def dedupe_docs(
+    docs: Iterable[DocumentLike], key: Literal["content", "content+metadata"] = "content"
+) -> tuple[list[Document], list[int]]: ...
+def load_text_files(
+    paths: list[StrPath],
+    encoding: str | None = None,
+    errors: _DecodeErrors = "detect",
+) -> list[Document]: ...
+def load_text_files_async(
+    paths: list[StrPath],
+    encoding: str | None = None,
+    errors: _DecodeErrors = "detect",
+) -> asyncio.Future[list[Document]]: ...
+def detect_languages(
+    docs: Iterable[DocumentLike],
+    metadata_key: str = "language",
+    min_confidence: float = 0.0,
+) -> list[Document]: ...
+def extract_entities_batch(
+    docs: Iterable[DocumentLike], patterns: dict[str, str]
+) -> list[Document]: ...
+def extract_keywords_batch(
+    docs: Iterable[DocumentLike],
+    top_k: int = 10,
+    method: Literal["rake", "tfidf"] = "rake",
+    metadata_key: str = "keywords",
+) -> list[Document]: ...
+def filter_by_quality(
+    docs: Iterable[DocumentLike], min_score: float = 0.5, metadata_key: str | None = None
+) -> tuple[list[Document], list[int]]: ...
+def load_jsonl(
+    path: StrPath,
+    content_key: str = "text",
+    metadata_keys: list[str] | None = None,
+    encoding: str | None = None,
+    errors: _DecodeErrors = "detect",
+) -> JsonlIterator: ...
+def load_markdown(path: StrPath) -> Document: ...
+def load_parquet(
+    path: StrPath, content_column: str = "text", metadata_columns: list[str] | None = None
+) -> list[Document]: ...
+def load_sqlite(path: StrPath, query: str, content_column: str) -> list[Document]: ...
+def load_url(url: str, timeout: float = 30.0) -> Document: ...
+def load_urls(
+    urls: list[str], timeout: float = 30.0, max_workers: int = 16
+) -> list[Document]: ...
+def load_url_async(url: str, timeout: float = 30.0) -> asyncio.Future[Document]: ...
+def load_urls_async(
+    urls: list[str], timeout: float = 30.0, max_workers: int = 16
+) -> asyncio.Future[list[Document]]: ...
+def near_dedupe_docs(
+    docs: Iterable[DocumentLike],
+    threshold: float = 0.9,
+    num_perm: int = 128,
+    shingle_size: int = 3,
+) -> tuple[list[Document], list[int]]: ...
+def ngram_counts(
+    docs: Iterable[DocumentLike],
+    n: int | tuple[int, int] = 1,
+    top_k: int = 100,
+    per_document: bool = False,
+) -> list[tuple[str, int]]: ...
+def partition_by_language(
+    docs: Iterable[DocumentLike],
+    metadata_key: str = "language",
+    min_confidence: float = 0.0,
+) -> dict[str, list[Document]]: ...
+def readability_batch(docs: Iterable[DocumentLike]) -> list[ReadabilityScores]: ...
+def set_log_level(
+    level: Literal["off", "error", "warning", "info", "debug", "trace"] | None,
+) -> None: ...
+def split_stream(
+    source: StrPath | IO[bytes] | IO[str],
+    chunk_size: int,
+    metadata: dict[str, str] | None = None,
+    encoding: str | None = None,
+    errors: _DecodeErrors = "detect",
+) -> ChunkIterator: ...
+def write_parquet(docs: Iterable[DocumentLike], path: StrPath) -> int: ...
+def to_arrow(docs: Iterable[DocumentLike]) -> pyarrow.Table: ...
+def docs_to_arrow_table(docs: Iterable[DocumentLike]) -> ArrowTable: ...
+def to_vector_payloads(
+    docs: Iterable[DocumentLike],
+    id_strategy: Literal["hash", "metadata", "index"] = "hash",
+    id_key: str = "id",
+) -> list[tuple[str | int, str, dict[str, str | list[str]]]]: ...
+def transform_metadata(
+    docs: Iterable[DocumentLike],
+    rename: dict[str, str] | None = None,
+    add: dict[str, str] | None = None,
+    drop: list[str] | None = None,
+    compute: dict[str, str] | None = None,
+) -> list[Document]: ...
+def write_jsonl(
+    docs: Iterable[DocumentLike],
+    path: StrPath,
+    append: bool = False,
+    gzip: bool | None = None,
+) -> int: ...
 
 class Document:
     page_content: str
     metadata: dict[str, str]
     def __init__(self: Self, page_content: str, metadata: dict[str, str]) -> None: ...
+    @staticmethod
+    def from_file(
+        path: StrPath, encoding: str | None = None, errors: _DecodeErrors = "detect"
+    ) -> Document: ...
+    @staticmethod
+    def from_html(html_or_path: StrPath) -> Document: ...
+    def to_msgpack(self: Self) -> bytes: ...
+    @staticmethod
+    def from_msgpack(data: bytes) -> Document: ...
+    def to_langchain(self: Self) -> langchain_core.documents.Document: ...
     def clean_non_ascii_chars(self: Self) -> None: ...
     def clean_bullets(self: Self) -> None: ...
     def clean_ligatures(self: Self) -> None: ...
@@ -13,6 +260,152 @@ class Document:
     def group_broken_paragraphs(self: Self) -> None: ...
     def new_line_grouper(self: Self) -> None: ...
     def auto_paragraph_grouper(self: Self) -> None: ...
+    def reflow_columns(self: Self) -> None: ...
+    def clean_footnotes(
+        self: Self, mode: Literal["strip", "inline", "metadata"] = "strip"
+    ) -> None: ...
     def clean(self: Self) -> None: ...
-    def recursive_character_splitter(self: Self, chunk_size: int) -> list[Document]: ...
-    def split_on_num_characters(self: Self, num_characters: int) -> list[Document]: ...
+    def recursive_character_splitter(
+        self: Self,
+        chunk_size: int,
+        provenance: bool = False,
+        id_key: str = "id",
+        context_template: str | None = None,
+        overlap_sentences: int | None = None,
+        unit: Literal["characters", "tokens"] = "characters",
+        encoding: str = "cl100k_base",
+        min_chunk_size: int = 0,
+        drop_empty: bool = False,
+    ) -> list[Document]: ...
+    def markdown_splitter(
+        self: Self,
+        chunk_size: int,
+        provenance: bool = False,
+        id_key: str = "id",
+        context_template: str | None = None,
+        min_chunk_size: int = 0,
+        drop_empty: bool = False,
+    ) -> list[Document]: ...
+    def split_on_num_characters(
+        self: Self,
+        num_characters: int,
+        provenance: bool = False,
+        id_key: str = "id",
+        overlap: int = 0,
+        unit: Literal["graphemes", "characters", "bytes"] = "graphemes",
+        min_chunk_size: int = 0,
+        drop_empty: bool = False,
+    ) -> list[Document]: ...
+    def sentence_window_splitter(
+        self: Self,
+        sentences_per_chunk: int = 1,
+        window_size: int = 3,
+        provenance: bool = False,
+        id_key: str = "id",
+        context_template: str | None = None,
+    ) -> list[Document]: ...
+    def add_context_header(self: Self, template: str) -> None: ...
+    def hierarchical_split(
+        self: Self, parent_size: int, child_size: int, id_key: str = "id"
+    ) -> tuple[list[Document], list[Document]]: ...
+    def extract_keywords(
+        self: Self, top_k: int = 10, method: Literal["rake", "tfidf"] = "rake"
+    ) -> list[tuple[str, float]]: ...
+    def readability(self: Self) -> ReadabilityScores: ...
+    def sentences(self: Self) -> list[str]: ...
+    def sentence_spans(self: Self) -> list[tuple[int, int]]: ...
+    def extract_entities(self: Self, patterns: dict[str, str]) -> None: ...
+    def count_tokens(self: Self, encoding: str = "cl100k_base") -> int: ...
+    def quality_score(self: Self) -> float: ...
+
+class SplitterChain:
+    def __init__(self: Self) -> None: ...
+    def markdown_headers(self: Self, max_level: int = 6) -> Self: ...
+    def recursive(self: Self, chunk_size: int) -> Self: ...
+    def markdown(self: Self, chunk_size: int) -> Self: ...
+    def sentences(self: Self, chunk_size: int, overlap_sentences: int = 1) -> Self: ...
+    def characters(
+        self: Self,
+        num_characters: int,
+        overlap: int = 0,
+        unit: Literal["graphemes", "characters", "bytes"] = "graphemes",
+    ) -> Self: ...
+    def merge_small(self: Self, min_size: int, max_size: int) -> Self: ...
+    def filter(self: Self, min_chunk_size: int = 0, drop_empty: bool = True) -> Self: ...
+    def split(
+        self: Self,
+        document: Document,
+        provenance: bool = False,
+        id_key: str = "id",
+        context_template: str | None = None,
+    ) -> list[Document]: ...
+    def split_docs(
+        self: Self,
+        docs: Iterable[DocumentLike],
+        provenance: bool = False,
+        id_key: str = "id",
+        context_template: str | None = None,
+    ) -> list[Document]: ...
+
+_CleanerName = Literal[
+    "clean",
+    "reflow_columns",
+    "clean_extra_whitespace",
+    "clean_ligatures",
+    "clean_bullets",
+    "clean_non_ascii_chars",
+    "group_broken_paragraphs",
+    "new_line_grouper",
+    "auto_paragraph_grouper",
+    "clean_footnotes",
+]
+
+class Pipeline:
+    def __init__(
+        self: Self,
+        cleaners: list[_CleanerName | dict[str, dict[str, str]]] | None = None,
+        splitter: SplitterChain | None = None,
+        provenance: bool = False,
+        id_key: str = "id",
+        context_template: str | None = None,
+    ) -> None: ...
+    @staticmethod
+    def from_config(path: StrPath) -> Pipeline: ...
+    def run(self: Self, document: Document) -> list[Document]: ...
+    def run_docs(self: Self, docs: Iterable[DocumentLike]) -> list[Document]: ...
+
+class IncrementalProcessor:
+    def __init__(
+        self: Self,
+        path: StrPath,
+        chunk_size: int,
+        provenance: bool = False,
+        id_key: str = "id",
+        context_template: str | None = None,
+        overlap_sentences: int | None = None,
+        unit: Literal["characters", "tokens"] = "characters",
+        encoding: str = "cl100k_base",
+        min_chunk_size: int = 0,
+        drop_empty: bool = False,
+    ) -> None: ...
+    def process(
+        self: Self, docs: Iterable[DocumentLike], prune: bool = True
+    ) -> ProcessResult: ...
+    def clear(self: Self) -> None: ...
+
+class ArrowTable:
+    @property
+    def num_rows(self: Self) -> int: ...
+    @property
+    def column_names(self: Self) -> list[str]: ...
+    def __len__(self: Self) -> int: ...
+    def __arrow_c_schema__(self: Self) -> object: ...
+    def __arrow_c_stream__(self: Self, requested_schema: object | None = None) -> object: ...
+
+class JsonlIterator(Iterator[Document]):
+    def __iter__(self: Self) -> Self: ...
+    def __next__(self: Self) -> Document: ...
+
+class ChunkIterator(Iterator[Document]):
+    def __iter__(self: Self) -> Self: ...
+    def __next__(self: Self) -> Document: ...
@@ -1,5 +1,5 @@
 import pytest
-from rs_document import Document, clean_and_split_docs
+from rs_document import Document, clean_and_split_docs, split_stream
 from rs_document.post_processors import UNSTRUCTURED_POST_PROCESSORS
 
 
@@ -23,6 +23,33 @@ def test_splitting(document_fixture: Document) -> None:
     assert split[0].page_content == "AAAAA"
 
 
+def test_splitting_overlap(document_fixture: Document) -> None:
+    split = document_fixture.split_on_num_characters(8, overlap=3)
+    assert [len(doc.page_content) for doc in split] == [8, 8, 8, 5]
+
+
+def test_splitting_keeps_graphemes_whole() -> None:
+    family = "\U0001f468\u200d\U0001f469\u200d\U0001f467"
+    accented = "e\u0301"
+    doc = Document(page_content=family + accented + "ab", metadata={})
+    split = doc.split_on_num_characters(2)
+    assert [chunk.page_content for chunk in split] == [family + accented, "ab"]
+
+    split = doc.split_on_num_characters(3, unit="characters")
+    assert [chunk.page_content for chunk in split] == [family, accented + "a", "b"]
+
+    split = doc.split_on_num_characters(4, unit="bytes")
+    assert "".join(chunk.page_content for chunk in split) == doc.page_content
+    assert split[0].page_content == family
+
+
+def test_splitting_rejects_bad_overlap(document_fixture: Document) -> None:
+    with pytest.raises(ValueError, match="overlap"):
+        document_fixture.split_on_num_characters(5, overlap=5)
+    with pytest.raises(ValueError, match="Unknown unit"):
+        document_fixture.split_on_num_characters(5, unit="words")
+
+
 def test_repr(document_fixture: Document) -> None:
     assert (
         repr(document_fixture)
@@ -111,3 +138,400 @@ def test_performance_improvement_over_python(number_files) -> None:
     python_time = python_later - python_now
 
     assert (python_time / rust_time) >= EXPECTED_PERFORMANCE_IMPROVEMENT
+
+
+def test_batch_releases_gil() -> None:
+    import sys
+    import threading
+    import time
+
+    with open("python/tests/lorem.txt") as textfile:
+        content = textfile.read()
+    docs = [Document(page_content=content, metadata={}) for _ in range(5_000)]
+
+    count = 0
+    started = threading.Event()
+    done = threading.Event()
+
+    def counter() -> None:
+        nonlocal count
+        started.set()
+        while not done.is_set():
+            count += 1
+            # Sleeping lets go of the GIL, and waking up needs it back.
+            time.sleep(0.0001)
+
+    # With a switch interval this long, the main thread only lets go of the GIL when it
+    # blocks or when clean_and_split_docs releases it, so the counter can only move
+    # while the batch runs without it.
+    interval = sys.getswitchinterval()
+    sys.setswitchinterval(1000)
+    try:
+        thread = threading.Thread(target=counter)
+        thread.start()
+        started.wait()
+        before = count
+        clean_and_split_docs(docs, 2000)
+        after = count
+        done.set()
+        thread.join()
+    finally:
+        sys.setswitchinterval(interval)
+    assert after > before
+
+
+def test_splitting_provenance(document_fixture: Document) -> None:
+    split = document_fixture.split_on_num_characters(5, provenance=True)
+    assert [doc.metadata["chunk_index"] for doc in split] == ["0", "1", "2", "3"]
+    assert all(doc.metadata["total_chunks"] == "4" for doc in split)
+    assert len({doc.metadata["parent_id"] for doc in split}) == 1
+    assert split[0].metadata["Hello"] == "World"
+
+
+def test_splitting_provenance_uses_id_key() -> None:
+    doc = Document(page_content="A" * 20, metadata={"source": "a.txt"})
+    split = doc.split_on_num_characters(10, provenance=True, id_key="source")
+    assert all(chunk.metadata["parent_id"] == "a.txt" for chunk in split)
+
+
+def test_clean_and_split_provenance_hashes_original_text() -> None:
+    doc = Document(page_content="Some   text  with\n\n extra   spaces", metadata={})
+    original = doc.split_on_num_characters(100, provenance=True)[0]
+    split = clean_and_split_docs([doc], 10, provenance=True)
+    assert len(split) > 1
+    assert all(
+        chunk.metadata["parent_id"] == original.metadata["parent_id"] for chunk in split
+    )
+
+
+def test_splitting_min_chunk_size_and_drop_empty() -> None:
+    doc = Document(page_content="A" * 20 + " " * 10 + "BBB", metadata={})
+    split = doc.split_on_num_characters(10, provenance=True)
+    assert [chunk.page_content for chunk in split][-2:] == [" " * 10, "BBB"]
+
+    split = doc.split_on_num_characters(10, provenance=True, drop_empty=True)
+    assert [chunk.page_content for chunk in split] == ["A" * 10, "A" * 10, "BBB"]
+    assert [chunk.metadata["total_chunks"] for chunk in split] == ["3"] * 3
+
+    split = doc.split_on_num_characters(
+        10, provenance=True, min_chunk_size=4, drop_empty=True
+    )
+    assert [chunk.metadata["chunk_index"] for chunk in split] == ["0", "1"]
+
+
+def test_recursive_splitting_min_chunk_size() -> None:
+    doc = Document(page_content="A paragraph of text.\n\nTail", metadata={})
+    assert len(doc.recursive_character_splitter(24)) == 2
+    split = doc.recursive_character_splitter(24, min_chunk_size=15, provenance=True)
+    assert [chunk.page_content for chunk in split] == ["A paragraph of text."]
+    assert split[0].metadata["total_chunks"] == "1"
+    chunks = clean_and_split_docs([doc], 24, min_chunk_size=15)
+    assert [chunk.page_content for chunk in chunks] == ["A paragraph of text."]
+
+
+def test_hierarchical_split() -> None:
+    with open("python/tests/lorem.txt") as textfile:
+        content = textfile.read()
+    doc = Document(page_content=content, metadata={"id": "lorem"})
+    parents, children = doc.hierarchical_split(2000, 300)
+
+    parent_ids = {parent.metadata["id"] for parent in parents}
+    assert len(parent_ids) == len(parents)
+    assert all(parent.metadata["parent_id"] == "lorem" for parent in parents)
+    assert len(children) > len(parents)
+    assert all(child.metadata["parent_id"] in parent_ids for child in children)
+    assert all(len(child.page_content) <= 300 for child in children)
+
+
+def test_short_document_splits_into_one_chunk() -> None:
+    doc = Document(page_content="hello", metadata={})
+    assert [chunk.page_content for chunk in doc.recursive_character_splitter(100)] == [
+        "hello"
+    ]
+
+
+def test_splitting_keeps_separators() -> None:
+    doc = Document(page_content="Hello world. Second paragraph here.", metadata={})
+    chunks = [chunk.page_content for chunk in doc.recursive_character_splitter(20)]
+    assert chunks[0] == "Hello world. Second"
+    assert all(len(chunk) <= 20 for chunk in chunks)
+
+
+def test_splitting_never_drops_text() -> None:
+    doc = Document(page_content="ab cd ef gh", metadata={})
+    chunks = [chunk.page_content for chunk in doc.recursive_character_splitter(3)]
+    assert chunks == ["ab", "b c", "cd", "d e", "ef", "f g", "gh"]
+
+
+def test_splitting_chunk_boundaries() -> None:
+    with open("python/tests/lorem.txt") as textfile:
+        text = textfile.read().strip()
+    doc = Document(page_content=text, metadata={})
+    chunks = [chunk.page_content for chunk in doc.recursive_character_splitter(300)]
+    assert all(len(chunk) <= 300 for chunk in chunks)
+
+    # Every chunk is a slice of the text, starting inside the chunk before it so that
+    # neighbours overlap and no text between them is skipped.
+    ranges = []
+    cursor = 0
+    for chunk in chunks:
+        start = text.index(chunk, cursor)
+        ranges.append((start, start + len(chunk)))
+        cursor = start + 1
+    assert ranges[0][0] == 0
+    assert ranges[-1][1] == len(text)
+    for (start, end), (next_start, next_end) in zip(ranges, ranges[1:]):
+        assert start < next_start < end < next_end
+
+
+def test_splitting_overlap_sentences() -> None:
+    doc = Document(
+        "The first sentence is here. The second one follows. "
+        "A third sentence appears. Then a fourth.",
+        {},
+    )
+    chunks = [
+        chunk.page_content
+        for chunk in doc.recursive_character_splitter(60, overlap_sentences=1)
+    ]
+    assert chunks == [
+        "The first sentence is here. The second one follows.",
+        "The second one follows. A third sentence appears.",
+        "A third sentence appears. Then a fourth.",
+    ]
+    no_overlap = doc.recursive_character_splitter(60, overlap_sentences=0)
+    assert [chunk.page_content for chunk in no_overlap] == [
+        "The first sentence is here. The second one follows.",
+        "A third sentence appears. Then a fourth.",
+    ]
+
+
+def test_splitting_overlap_sentences_splits_long_sentences() -> None:
+    doc = Document("word " * 30, {})
+    chunks = doc.recursive_character_splitter(40, overlap_sentences=1, provenance=True)
+    assert all(len(chunk.page_content) <= 40 for chunk in chunks)
+    assert chunks[-1].metadata["total_chunks"] == str(len(chunks))
+
+
+def test_sentence_window_splitter() -> None:
+    doc = Document("One is first. Two follows.\n\nThree starts. Four ends.", {"a": "b"})
+    chunks = doc.sentence_window_splitter(window_size=1, provenance=True)
+    assert [chunk.page_content for chunk in chunks] == [
+        "One is first.",
+        "Two follows.",
+        "Three starts.",
+        "Four ends.",
+    ]
+    assert chunks[0].metadata["window_before"] == ""
+    assert chunks[0].metadata["window_after"] == "Two follows."
+    assert chunks[2].metadata["window_before"] == "Two follows."
+    assert chunks[2].metadata["window_after"] == "Four ends."
+    assert chunks[3].metadata["window_after"] == ""
+    assert chunks[3].metadata["a"] == "b"
+    assert chunks[3].metadata["total_chunks"] == "4"
+
+
+def test_sentence_window_splitter_groups() -> None:
+    doc = Document("A one. B two. C three. D four. E five.", {})
+    chunks = doc.sentence_window_splitter(sentences_per_chunk=2, window_size=2)
+    assert [chunk.page_content for chunk in chunks] == [
+        "A one. B two.",
+        "C three. D four.",
+        "E five.",
+    ]
+    assert chunks[1].metadata["window_before"] == "A one. B two."
+    assert chunks[1].metadata["window_after"] == "E five."
+    assert chunks[2].metadata["window_before"] == "C three. D four."
+    assert Document("", {}).sentence_window_splitter() == []
+    with pytest.raises(ValueError):
+        doc.sentence_window_splitter(sentences_per_chunk=0)
+
+
+def test_clean_and_split_docs_overlap_sentences() -> None:
+    docs = [Document("One  sentence here. Another sentence there.", {})]
+    chunks = clean_and_split_docs(docs, 30, overlap_sentences=1)
+    assert [chunk.page_content for chunk in chunks] == [
+        "One sentence here.",
+        "Another sentence there.",
+    ]
+
+
+def test_splitting_unit_tokens() -> None:
+    doc = Document("日本語のテキストです。" * 50, {})
+    chunks = doc.recursive_character_splitter(30, unit="tokens")
+    assert len(chunks) > 1
+    assert all(chunk.count_tokens() <= 30 for chunk in chunks)
+    english = Document("The splitter counts tokens. It packs sentences. " * 20, {})
+    chunks = english.recursive_character_splitter(
+        50, unit="tokens", encoding="o200k_base", overlap_sentences=1
+    )
+    assert all(chunk.count_tokens("o200k_base") <= 50 for chunk in chunks)
+    # The same chunk_size in characters makes far more, smaller chunks.
+    assert len(english.recursive_character_splitter(50, overlap_sentences=1)) > len(
+        chunks
+    )
+
+
+def test_splitting_unit_characters_counts_characters() -> None:
+    doc = Document("Grüße aus Köln, schöne Straße. " * 20, {})
+    chunks = doc.recursive_character_splitter(60, min_chunk_size=20)
+    assert all(20 <= len(chunk.page_content) <= 60 for chunk in chunks)
+    # Counting UTF-8 bytes instead would leave these chunks a lot shorter.
+    assert any(len(chunk.page_content.encode()) > 60 for chunk in chunks)
+
+
+def test_markdown_splitter_counts_characters() -> None:
+    table = "| Straße | Größe |\n|---|---|\n" + "| Königsallee | groß |\n" * 6
+    doc = Document("Grüße aus Köln, schöne Straße. " * 10 + "\n\n" + table, {})
+    chunks = doc.markdown_splitter(80, min_chunk_size=10)
+    assert all(10 <= len(chunk.page_content) <= 80 for chunk in chunks)
+    assert any(len(chunk.page_content.encode()) > 80 for chunk in chunks)
+    assert any(chunk.metadata.get("has_table") == "true" for chunk in chunks)
+
+
+def test_hierarchical_split_counts_characters() -> None:
+    doc = Document("Grüße aus Köln, schöne Straße. " * 40, {"id": "köln"})
+    parents, children = doc.hierarchical_split(200, 60)
+    assert all(len(parent.page_content) <= 200 for parent in parents)
+    assert all(len(child.page_content) <= 60 for child in children)
+    assert any(len(child.page_content.encode()) > 60 for child in children)
+
+
+def test_splitting_unknown_unit() -> None:
+    with pytest.raises(ValueError):
+        Document("text", {}).recursive_character_splitter(10, unit="words")
+    with pytest.raises(ValueError):
+        clean_and_split_docs([Document("text", {})], 10, unit="words")
+
+
+def test_markdown_splitter_keeps_code_blocks_and_tables_whole() -> None:
+    code = "```python\ndef add(a, b):\n    return a + b\n```"
+    table = "| a | b |\n|---|---|\n| 1 | 2 |\n| 3 | 4 |"
+    text = f"Intro paragraph, several words.\n\n{code}\n\nMiddle.\n\n{table}\n\nEnd."
+    chunks = [chunk.page_content for chunk in Document(text, {}).markdown_splitter(60)]
+    assert any(code in chunk for chunk in chunks)
+    assert any(table in chunk for chunk in chunks)
+    assert all(chunk.count("```") % 2 == 0 for chunk in chunks)
+    assert all(len(chunk) <= 60 for chunk in chunks)
+    # The plain splitter starts and ends chunks inside the code block.
+    plain = Document(text, {}).recursive_character_splitter(60)
+    assert any(chunk.page_content.count("```") % 2 for chunk in plain)
+
+
+def test_markdown_splitter_splits_large_blocks_between_lines() -> None:
+    lines = [f"line {i} of code" for i in range(12)]
+    text = "```\n" + "\n".join(lines) + "\n```"
+    chunks = Document(text, {}).markdown_splitter(60)
+    assert len(chunks) > 1
+    for chunk in chunks:
+        assert all(
+            line in ["```", *lines] for line in chunk.page_content.split("\n")
+        )
+
+
+def test_markdown_splitter_unclosed_fence_runs_to_the_end() -> None:
+    text = "Text before.\n\n```\ncode\n\nmore code"
+    chunks = Document(text, {}).markdown_splitter(30)
+    assert chunks[-1].page_content.endswith("```\ncode\n\nmore code")
+
+
+def test_markdown_splitter_repeats_table_header() -> None:
+    header = "| name | qty |\n|------|-----|"
+    rows = [f"| item{i} | {i} |" for i in range(10)]
+    text = "Intro text.\n\n" + header + "\n" + "\n".join(rows) + "\n\nAfter text."
+    chunks = Document(text, {}).markdown_splitter(60)
+    table_chunks = [chunk for chunk in chunks if "has_table" in chunk.metadata]
+    assert len(table_chunks) > 1
+    assert all(chunk.page_content.startswith(header) for chunk in table_chunks)
+    assert all(len(chunk.page_content) <= 60 for chunk in chunks)
+    lines = [chunk.page_content.split("\n") for chunk in table_chunks]
+    assert [row for chunk_lines in lines for row in chunk_lines[2:]] == rows
+    assert chunks[0].page_content == "Intro text."
+    assert chunks[0].metadata == {}
+
+
+def test_markdown_splitter_aligned_columns() -> None:
+    table = (
+        "Name      Qty   Price\n"
+        "Apple       3    1.20\n"
+        "Banana     12    0.50\n"
+        "Cherry    100   10.00"
+    )
+    chunks = Document(table, {}).markdown_splitter(50)
+    assert [chunk.page_content for chunk in chunks] == [
+        "Name      Qty   Price\nApple       3    1.20",
+        "Name      Qty   Price\nBanana     12    0.50",
+        "Name      Qty   Price\nCherry    100   10.00",
+    ]
+    assert all(chunk.metadata["has_table"] == "true" for chunk in chunks)
+
+
+def test_markdown_splitter_prose_has_no_table() -> None:
+    text = "One sentence.  Two spaces.\nAnother line.  More text\nA third  line."
+    chunks = Document(text, {}).markdown_splitter(500)
+    assert chunks[0].metadata == {}
+
+
+def test_context_header() -> None:
+    doc = Document(page_content="Run the installer.", metadata={"title": "Guide"})
+    doc.add_context_header("{title} > {section}\n\n")
+    assert doc.page_content == "Guide > \n\nRun the installer."
+    start = int(doc.metadata["content_start"])
+    end = int(doc.metadata["content_end"])
+    assert doc.page_content[start:end] == "Run the installer."
+
+
+def test_splitter_context_template() -> None:
+    doc = Document(page_content="A" * 20, metadata={"title": "Guide"})
+    split = doc.recursive_character_splitter(
+        9, provenance=True, context_template="{title} ({chunk_index}): "
+    )
+    assert split[0].page_content.startswith("Guide (0): ")
+    assert split[1].page_content.startswith("Guide (1): ")
+
+
+def test_split_stream_path(tmp_path) -> None:
+    path = tmp_path / "big.txt"
+    path.write_text("First paragraph.\n\nSecond paragraph.")
+    chunks = list(split_stream(path, 1000))
+    assert [chunk.page_content for chunk in chunks] == [
+        "First paragraph.\n\nSecond paragraph."
+    ]
+    assert chunks[0].metadata == {"source": str(path)}
+
+
+def test_split_stream_detects_encoding(tmp_path) -> None:
+    import io
+
+    text = "Ça coûte 5 € à Zürich, n’est-ce pas ?"
+    path = tmp_path / "legacy.txt"
+    path.write_bytes(text.encode("cp1252"))
+    assert [chunk.page_content for chunk in split_stream(path, 1000)] == [text]
+    data = io.BytesIO(text.encode("utf-16"))
+    assert [chunk.page_content for chunk in split_stream(data, 1000)] == [text]
+    with pytest.raises(ValueError, match="not valid UTF-8"):
+        list(split_stream(path, 1000, errors="error"))
+
+
+def test_split_stream_matches_splitter_within_a_segment() -> None:
+    with open("python/tests/lorem.txt") as textfile:
+        content = textfile.read()
+    doc = Document(page_content=content, metadata={"Hello": "World"})
+    expected = doc.recursive_character_splitter(1000)
+    with open("python/tests/lorem.txt", "rb") as textfile:
+        chunks = list(split_stream(textfile, 1000, {"Hello": "World"}))
+    assert [chunk.page_content for chunk in chunks] == [
+        chunk.page_content for chunk in expected
+    ]
+    assert chunks[0].metadata == {"Hello": "World"}
+
+
+def test_split_stream_large_file() -> None:
+    import io
+
+    text = "é word " * 200_000
+    chunks = list(split_stream(io.StringIO(text), 500))
+    # chunk_size counts characters, not UTF-8 bytes.
+    assert all(len(chunk.page_content) <= 500 for chunk in chunks)
+    assert any(len(chunk.page_content.encode()) > 500 for chunk in chunks)
+    assert chunks[-1].page_content.endswith("é word")
@@ -0,0 +1,332 @@
+import pytest
+from rs_document import (
+    Document,
+    clean_and_split_docs,
+    corpus_stats,
+    count_tokens_batch,
+    detect_languages,
+    extract_entities_batch,
+    extract_keywords_batch,
+    filter_by_quality,
+    ngram_counts,
+    partition_by_language,
+    readability_batch,
+)
+
+
+def test_extract_keywords_rake() -> None:
+    doc = Document("Rust splitters for retrieval augmented generation.", {})
+    assert doc.extract_keywords() == [
+        ("retrieval augmented generation", 9.0),
+        ("rust splitters", 4.0),
+    ]
+    assert doc.extract_keywords(top_k=1) == [("retrieval augmented generation", 9.0)]
+
+
+def test_extract_keywords_tfidf() -> None:
+    doc = Document("The cat chased the other cat around the garden.", {})
+    keywords = doc.extract_keywords(top_k=2, method="tfidf")
+    assert [word for word, _ in keywords] == ["cat", "around"]
+
+
+def test_extract_keywords_unknown_method() -> None:
+    with pytest.raises(ValueError):
+        Document("text", {}).extract_keywords(method="bm25")
+
+
+def test_extract_keywords_batch_uses_corpus_idf() -> None:
+    docs = [
+        Document("the cat sat on the mat with the cat", {"id": "1"}),
+        Document("the dog sat on the log", {"id": "2"}),
+        Document("a cat and a dog", {"id": "3"}),
+    ]
+    tagged = extract_keywords_batch(docs, top_k=2, method="tfidf")
+    assert [doc.metadata["id"] for doc in tagged] == ["1", "2", "3"]
+    assert tagged[0].metadata["keywords"] == "cat, mat"
+    assert tagged[1].metadata["keywords"] == "log, dog"
+
+
+def test_extract_keywords_batch_rake() -> None:
+    docs = [{"page_content": "Vector stores need keyword metadata.", "metadata": {}}]
+    tagged = extract_keywords_batch(docs, metadata_key="kw")
+    assert tagged[0].metadata["kw"] == "vector stores need, keyword metadata"
+
+
+@pytest.fixture()
+def ngram_docs() -> list[Document]:
+    return [
+        Document("The cat sat. The cat ran! Home page | About us", {}),
+        Document("the cat. About us", {}),
+    ]
+
+
+def test_ngram_counts_unigrams(ngram_docs: list[Document]) -> None:
+    assert ngram_counts(ngram_docs, top_k=4) == [
+        ("cat", 3),
+        ("the", 3),
+        ("about", 2),
+        ("us", 2),
+    ]
+
+
+def test_ngram_counts_do_not_span_punctuation(ngram_docs: list[Document]) -> None:
+    assert ngram_counts(ngram_docs, n=2) == [
+        ("the cat", 3),
+        ("about us", 2),
+        ("cat ran", 1),
+        ("cat sat", 1),
+        ("home page", 1),
+    ]
+
+
+def test_ngram_counts_per_document_range(ngram_docs: list[Document]) -> None:
+    counts = ngram_counts(ngram_docs, n=(1, 2), top_k=4, per_document=True)
+    assert counts == [("about", 2), ("about us", 2), ("cat", 2), ("the", 2)]
+
+
+@pytest.mark.parametrize("n", [0, (3, 1)])
+def test_ngram_counts_invalid_n(n) -> None:
+    with pytest.raises(ValueError):
+        ngram_counts([Document("text", {})], n=n)
+
+
+def test_readability() -> None:
+    scores = Document("The cat sat on the mat. It was happy!", {}).readability()
+    assert scores["sentences"] == 2
+    assert scores["words"] == 9
+    assert scores["average_sentence_length"] == 4.5
+    assert scores["flesch_kincaid_grade"] == pytest.approx(-0.72, abs=0.01)
+    assert scores["flesch_reading_ease"] == pytest.approx(108.27, abs=0.01)
+    assert scores["smog_index"] == pytest.approx(3.1291)
+
+
+def test_readability_sentence_boundaries() -> None:
+    doc = Document("Dr. Smith met J. R. Tolkien at 3 p.m. on Tuesday.\n\nHeading", {})
+    assert doc.readability()["sentences"] == 2
+
+
+def test_readability_harder_text_scores_higher() -> None:
+    easy = Document("The dog ran. The dog sat. The dog ate.", {})
+    hard = Document(
+        "Incomprehensibility characterizes bureaucratic documentation "
+        "throughout governmental institutions.",
+        {},
+    )
+    easy_scores, hard_scores = readability_batch([easy, hard])
+    assert easy_scores["flesch_kincaid_grade"] < hard_scores["flesch_kincaid_grade"]
+    assert easy_scores["flesch_reading_ease"] > hard_scores["flesch_reading_ease"]
+
+
+def test_readability_empty() -> None:
+    assert Document("", {}).readability()["flesch_kincaid_grade"] == 0.0
+
+
+@pytest.fixture()
+def multilingual_docs() -> list[Document]:
+    return [
+        Document(
+            "This is a longer English paragraph about documents, chunking and retrieval "
+            "for language models. It should be detected without any trouble at all.",
+            {"id": "en"},
+        ),
+        Document(
+            "Der schnelle braune Fuchs springt über den faulen Hund und läuft weiter.",
+            {"id": "de"},
+        ),
+        Document(
+            "El rápido zorro marrón salta sobre el perro perezoso y sigue corriendo.",
+            {"id": "es"},
+        ),
+        Document("ok", {"id": "short"}),
+    ]
+
+
+def test_detect_languages(multilingual_docs: list[Document]) -> None:
+    tagged = detect_languages(multilingual_docs)
+    assert [doc.metadata["language"] for doc in tagged] == ["eng", "deu", "spa", "und"]
+
+
+def test_detect_languages_metadata_key(multilingual_docs: list[Document]) -> None:
+    tagged = detect_languages(multilingual_docs[:1], metadata_key="lang")
+    assert tagged[0].metadata == {"id": "en", "lang": "eng"}
+
+
+def test_partition_by_language(multilingual_docs: list[Document]) -> None:
+    docs = multilingual_docs + [Document("hi", {"id": "tagged", "language": "eng"})]
+    partitions = partition_by_language(docs)
+    assert {
+        language: [doc.metadata["id"] for doc in group]
+        for language, group in partitions.items()
+    } == {"deu": ["de"], "eng": ["en", "tagged"], "spa": ["es"], "und": ["short"]}
+
+
+def test_sentences() -> None:
+    doc = Document('Dr. Smith arrived. "Hello!" she said.\n\nNext steps', {})
+    assert doc.sentences() == ["Dr. Smith arrived.", '"Hello!" she said.', "Next steps"]
+
+
+def test_sentences_abbreviations_and_numbers() -> None:
+    doc = Document("We met at 3.15 p.m. on Friday, e.g. after J. R. Smith left. Then ate.", {})
+    assert doc.sentences() == [
+        "We met at 3.15 p.m. on Friday, e.g. after J. R. Smith left.",
+        "Then ate.",
+    ]
+
+
+def test_sentences_full_width() -> None:
+    doc = Document("中文句子。第二句！「引用。」结束", {})
+    assert doc.sentences() == ["中文句子。", "第二句！", "「引用。」", "结束"]
+
+
+def test_sentence_spans() -> None:
+    doc = Document("  Café au lait. Über alles! ", {})
+    spans = doc.sentence_spans()
+    assert spans == [(2, 15), (16, 27)]
+    assert [doc.page_content[start:end] for start, end in spans] == doc.sentences()
+
+
+ENTITY_PATTERNS = {
+    "tickets": r"[A-Z]+-\d+",
+    "emails": r"[\w.+-]+@[\w-]+\.[\w.]+",
+    "years": r"(\d{4})-\d{2}-\d{2}",
+}
+
+
+def test_extract_entities() -> None:
+    doc = Document(
+        "Fixed in OPS-12, see OPS-7 and OPS-12. Mail ops@example.com by 2024-01-05.",
+        {"source": "notes"},
+    )
+    doc.extract_entities(ENTITY_PATTERNS)
+    assert doc.metadata == {
+        "source": "notes",
+        "tickets": '["OPS-12","OPS-7"]',
+        "emails": '["ops@example.com"]',
+        "years": '["2024"]',
+    }
+
+
+def test_extract_entities_without_matches() -> None:
+    doc = Document("Nothing to see here.", {})
+    doc.extract_entities(ENTITY_PATTERNS)
+    assert doc.metadata == {}
+
+
+def test_extract_entities_invalid_pattern() -> None:
+    with pytest.raises(ValueError, match="broken"):
+        Document("text", {}).extract_entities({"broken": "("})
+
+
+def test_extract_entities_batch() -> None:
+    docs = [Document("OPS-1 and OPS-2", {}), {"page_content": "No tickets."}]
+    enriched = extract_entities_batch(docs, {"tickets": r"OPS-\d+"})
+    assert [doc.metadata for doc in enriched] == [{"tickets": '["OPS-1","OPS-2"]'}, {}]
+
+
+def test_count_tokens() -> None:
+    doc = Document("Hello world, this is a test.", {})
+    assert doc.count_tokens() == 8
+    assert doc.count_tokens("o200k_base") == 8
+    assert doc.count_tokens("gpt-4o") == doc.count_tokens("o200k_base")
+
+
+def test_count_tokens_unknown_encoding() -> None:
+    with pytest.raises(ValueError):
+        Document("text", {}).count_tokens("not-an-encoding")
+
+
+def test_count_tokens_batch() -> None:
+    docs = [Document("Hello world", {}), {"page_content": ""}]
+    assert count_tokens_batch(docs) == [2, 0]
+
+
+def test_quality_score_prose() -> None:
+    doc = Document(
+        "The quick brown fox jumps over the lazy dog. It was a sunny day, and the "
+        "children played in the park.",
+        {},
+    )
+    assert doc.quality_score() == pytest.approx(1.0)
+
+
+def test_quality_score_garbage() -> None:
+    assert Document("~~|| ^^ %% $$ ## @@ ** && ;;", {}).quality_score() < 0.2
+    assert Document("a" * 40, {}).quality_score() < 0.2
+    assert Document("", {}).quality_score() == 0.0
+
+
+def test_quality_score_fixed_width_dump() -> None:
+    line = "QUJDREVGR0hJSktMTU5PUFFSU1RVVldYWVo0123456789abcdefXYZw"
+    dump = "\n".join(line for _ in range(10))
+    assert Document(dump, {}).quality_score() < 0.5
+
+
+def test_quality_score_cjk() -> None:
+    doc = Document("今天天气很好，我们去公园散步。孩子们在草地上玩耍。", {})
+    assert doc.quality_score() > 0.9
+
+
+def test_filter_by_quality() -> None:
+    docs = [
+        Document("A perfectly ordinary sentence about the weather.", {}),
+        {"page_content": "\ufffd\ufffd\x00\x01 ## @@ \ufffd"},
+        Document("Another readable sentence.", {"source": "b"}),
+    ]
+    kept, dropped = filter_by_quality(docs, min_score=0.5, metadata_key="quality")
+    assert [doc.page_content for doc in kept] == [
+        "A perfectly ordinary sentence about the weather.",
+        "Another readable sentence.",
+    ]
+    assert dropped == [1]
+    assert float(kept[1].metadata["quality"]) >= 0.5
+    assert kept[1].metadata["source"] == "b"
+
+
+def test_corpus_stats_lengths_and_coverage() -> None:
+    docs = [
+        Document("one two three four", {"source": "a", "title": "t"}),
+        Document("five six", {"source": "b"}),
+        {"page_content": "seven", "metadata": {}},
+        Document("", {"source": "c"}),
+    ]
+    stats = corpus_stats(docs)
+    assert stats["documents"] == 4
+    assert stats["words"] == {
+        "total": 7,
+        "mean": 1.75,
+        "min": 0,
+        "max": 4,
+        "p50": 1,
+        "p90": 4,
+        "p95": 4,
+        "p99": 4,
+    }
+    assert stats["characters"]["max"] == 18
+    assert stats["tokens"]["total"] == count_tokens_batch(docs)[0] + 3
+    assert stats["metadata_coverage"] == {"source": 0.75, "title": 0.25}
+    assert stats["chunks"] is None
+
+
+def test_corpus_stats_trial_split_histogram() -> None:
+    docs = [Document("word " * 100, {}), Document("tiny", {})]
+    stats = corpus_stats(docs, chunk_size=100, bins=4)
+    chunks = clean_and_split_docs(docs, 100)
+    assert stats["chunks"] is not None
+    assert stats["chunks"]["count"] == len(chunks)
+    assert stats["chunks"]["sizes"]["max"] <= 100
+    histogram = stats["chunks"]["histogram"]
+    assert [(bucket["min"], bucket["max"]) for bucket in histogram] == [
+        (0, 25),
+        (26, 50),
+        (51, 75),
+        (76, 100),
+    ]
+    assert sum(bucket["count"] for bucket in histogram) == len(chunks)
+    assert histogram[0]["count"] == 1
+
+
+def test_corpus_stats_rejects_bad_arguments() -> None:
+    with pytest.raises(ValueError):
+        corpus_stats([], chunk_size=10, bins=0)
+    with pytest.raises(ValueError):
+        corpus_stats([], chunk_size=10, unit="lines")
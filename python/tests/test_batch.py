@@ -0,0 +1,528 @@
+import asyncio
+import json
+import logging
+from pathlib import Path
+
+import pytest
+from rs_document import (
+    Document,
+    IncrementalProcessor,
+    Pipeline,
+    SplitterChain,
+    clean_and_split_docs,
+    clean_and_split_docs_async,
+    coalesce_chunks,
+    dedupe_docs,
+    near_dedupe_docs,
+    set_log_level,
+    transform_metadata,
+)
+
+
+def test_dedupe_on_content() -> None:
+    docs = [
+        Document(page_content="alpha", metadata={"source": "a"}),
+        Document(page_content="beta", metadata={}),
+        Document(page_content="alpha", metadata={"source": "b"}),
+        Document(page_content="alpha", metadata={"source": "a"}),
+    ]
+    survivors, dropped = dedupe_docs(docs)
+    assert [doc.page_content for doc in survivors] == ["alpha", "beta"]
+    assert survivors[0].metadata == {"source": "a"}
+    assert dropped == [2, 3]
+
+
+def test_dedupe_on_content_and_metadata() -> None:
+    docs = [
+        Document(page_content="alpha", metadata={"source": "a"}),
+        Document(page_content="alpha", metadata={"source": "b"}),
+        Document(page_content="alpha", metadata={"source": "a"}),
+    ]
+    survivors, dropped = dedupe_docs(docs, key="content+metadata")
+    assert [doc.metadata["source"] for doc in survivors] == ["a", "b"]
+    assert dropped == [2]
+
+
+def test_dedupe_rejects_unknown_key() -> None:
+    with pytest.raises(ValueError):
+        dedupe_docs([], key="metadata")
+
+
+def test_near_dedupe_collapses_boilerplate_variants() -> None:
+    with open("python/tests/lorem.txt") as textfile:
+        content = textfile.read()
+    docs = [
+        Document(page_content=content, metadata={"source": "original"}),
+        Document(page_content=content + " Share this article!", metadata={}),
+        Document(page_content="A completely unrelated page about rust.", metadata={}),
+    ]
+    survivors, dropped = near_dedupe_docs(docs, threshold=0.8)
+    assert dropped == [1]
+    assert [doc.metadata.get("source") for doc in survivors] == ["original", None]
+
+
+def test_near_dedupe_rejects_bad_threshold() -> None:
+    with pytest.raises(ValueError):
+        near_dedupe_docs([], threshold=1.5)
+
+
+def test_batch_accepts_generators_and_dicts() -> None:
+    def loader():
+        yield {"page_content": "alpha", "metadata": {"source": "dict"}}
+        yield Document(page_content="alpha", metadata={})
+        yield {"page_content": "alpha"}
+
+    survivors, dropped = dedupe_docs(loader())
+    assert survivors[0].metadata == {"source": "dict"}
+    assert dropped == [1, 2]
+
+    split = clean_and_split_docs(({"page_content": "A" * 20} for _ in range(2)), 10)
+    assert all(doc.metadata == {} for doc in split)
+
+
+def test_batch_rejects_unknown_items() -> None:
+    with pytest.raises(TypeError):
+        clean_and_split_docs([42], 10)
+
+
+def test_coalesce_merges_small_siblings() -> None:
+    chunks = [
+        Document(page_content="# Heading", metadata={"parent_id": "a"}),
+        Document(page_content="A" * 50, metadata={"parent_id": "a"}),
+        Document(page_content="B" * 50, metadata={"parent_id": "a"}),
+        Document(page_content="tiny", metadata={"parent_id": "b"}),
+    ]
+    coalesced = coalesce_chunks(chunks, min_size=20, max_size=100)
+    assert [doc.page_content for doc in coalesced] == [
+        "# Heading " + "A" * 50,
+        "B" * 50,
+        "tiny",
+    ]
+
+
+def test_coalesce_drops_overlap_and_repeated_headers() -> None:
+    text = (
+        "The quick brown fox jumps over the lazy dog.\n\nA short one.\n\n"
+        "And then another paragraph that is fairly long indeed."
+    )
+    doc = Document(page_content=text, metadata={"title": "Foxes"})
+    chunks = doc.recursive_character_splitter(40, context_template="{title}\n\n")
+    assert len(chunks) > 1
+    coalesced = coalesce_chunks(chunks, min_size=200, max_size=200)
+    assert [chunk.page_content for chunk in coalesced] == ["Foxes\n\n" + text]
+    assert coalesced[0].metadata["content_end"] == str(len("Foxes\n\n" + text))
+
+
+def test_coalesce_counts_characters() -> None:
+    docs = [
+        Document(page_content="Grüße aus Köln", metadata={}),
+        Document(page_content="schöne Straße", metadata={}),
+    ]
+    # 28 characters, but 33 bytes.
+    chunks = coalesce_chunks(docs, min_size=15, max_size=30)
+    assert [chunk.page_content for chunk in chunks] == [
+        "Grüße aus Köln schöne Straße"
+    ]
+
+
+def test_coalesce_renumbers_provenance() -> None:
+    doc = Document(page_content="A" * 20 + "\n\nB", metadata={})
+    chunks = doc.split_on_num_characters(10, provenance=True)
+    coalesced = coalesce_chunks(chunks, min_size=5, max_size=100)
+    assert len(coalesced) == 2
+    assert [chunk.metadata["chunk_index"] for chunk in coalesced] == ["0", "1"]
+    assert all(chunk.metadata["total_chunks"] == "2" for chunk in coalesced)
+
+
+def test_batch_accepts_duck_typed_documents() -> None:
+    class LoaderDocument:
+        def __init__(self, page_content: str, metadata: dict) -> None:
+            self.page_content = page_content
+            self.metadata = metadata
+
+    docs = [LoaderDocument("A" * 20, {"source": "a.pdf", "page": 3})]
+    split = clean_and_split_docs(docs, 10)
+    assert split[0].metadata == {"source": "a.pdf", "page": "3"}
+
+
+def test_langchain_round_trip() -> None:
+    langchain_documents = pytest.importorskip("langchain_core.documents")
+    docs = [langchain_documents.Document(page_content="A" * 20, metadata={"page": 1})]
+    split = clean_and_split_docs(docs, 10, output_type="langchain")
+    assert all(isinstance(doc, langchain_documents.Document) for doc in split)
+    assert split[0].metadata == {"page": "1"}
+
+    doc = Document(page_content="hello", metadata={"source": "a"})
+    converted = doc.to_langchain()
+    assert converted.page_content == "hello"
+    assert converted.metadata == {"source": "a"}
+
+
+def test_batch_rejects_unknown_output_type() -> None:
+    with pytest.raises(ValueError):
+        clean_and_split_docs([], 10, output_type="pandas")
+
+
+MARKDOWN = """Preamble.
+
+# Guide
+
+Intro.
+
+## Install
+
+```sh
+# not a heading
+pip install rs_document
+```
+
+## Usage
+
+""" + "Call it. " * 40
+
+
+def test_splitter_chain_headers_and_provenance() -> None:
+    doc = Document(page_content=MARKDOWN, metadata={"id": "guide"})
+    chain = SplitterChain().markdown_headers(2).recursive(120)
+    chunks = chain.split(doc, provenance=True)
+
+    assert chunks[0].page_content == "Preamble."
+    assert "h1" not in chunks[0].metadata
+    install = [chunk for chunk in chunks if chunk.metadata.get("h2") == "Install"]
+    assert len(install) == 1
+    assert "# not a heading" in install[0].page_content
+    assert install[0].metadata["h1"] == "Guide"
+    usage = [chunk for chunk in chunks if chunk.metadata.get("h2") == "Usage"]
+    assert len(usage) > 1
+    assert [chunk.metadata["chunk_index"] for chunk in chunks] == [
+        str(index) for index in range(len(chunks))
+    ]
+    assert all(chunk.metadata["parent_id"] == "guide" for chunk in chunks)
+
+
+def test_splitter_chain_merges_within_sections() -> None:
+    doc = Document(page_content="# A\n\none\n\n# B\n\ntwo", metadata={})
+    chain = SplitterChain().markdown_headers().recursive(10).merge_small(20, 100)
+    chunks = chain.split(doc)
+    assert [chunk.page_content for chunk in chunks] == ["# A\n\none", "# B\n\ntwo"]
+    assert [chunk.metadata["h1"] for chunk in chunks] == ["A", "B"]
+
+
+def test_splitter_chain_merge_small_keeps_text_once() -> None:
+    text = (
+        "The quick brown fox jumps over the lazy dog.\n\nA short one.\n\n"
+        "And then another paragraph that is fairly long indeed."
+    )
+    doc = Document(page_content=text, metadata={})
+    chain = SplitterChain().recursive(40).merge_small(100, 200)
+    assert len(SplitterChain().recursive(40).split(doc)) > 1
+    assert [chunk.page_content for chunk in chain.split(doc)] == [text]
+
+
+GERMAN = "# Köln\n\n" + "Grüße aus Köln, schöne Straße. " * 20
+
+
+@pytest.mark.parametrize(
+    "chain, size",
+    [
+        (SplitterChain().recursive(60), 60),
+        (SplitterChain().markdown(60), 60),
+        (SplitterChain().sentences(100, 1), 100),
+        (SplitterChain().recursive(30).merge_small(45, 60), 60),
+    ],
+)
+def test_splitter_chain_counts_characters(chain: SplitterChain, size: int) -> None:
+    chunks = chain.filter(10).split(Document(page_content=GERMAN, metadata={}))
+    assert all(10 <= len(chunk.page_content) <= size for chunk in chunks)
+    assert any(len(chunk.page_content.encode()) > size for chunk in chunks)
+
+
+def test_splitter_chain_batch_matches_single() -> None:
+    docs = [
+        Document(page_content=MARKDOWN, metadata={"id": "one"}),
+        {"page_content": "# Other\n\nText.", "metadata": {"id": "two"}},
+    ]
+    chain = SplitterChain().markdown_headers().characters(50, overlap=10)
+    chunks = chain.split_docs(docs, provenance=True)
+    expected = chain.split(Document(page_content=MARKDOWN, metadata={"id": "one"}))
+    assert len(chunks) == len(expected) + 1
+    assert chunks[-1].metadata["parent_id"] == "two"
+
+
+def test_splitter_chain_rejects_bad_steps() -> None:
+    with pytest.raises(ValueError, match="min_size"):
+        SplitterChain().merge_small(100, 10)
+    with pytest.raises(ValueError, match="max_level"):
+        SplitterChain().markdown_headers(7)
+
+
+def test_transform_metadata() -> None:
+    docs = [
+        Document(page_content="a", metadata={"file_path": "a.txt", "page": "3"}),
+        {"page_content": "b", "metadata": {"noise": "x"}},
+    ]
+    result = transform_metadata(
+        docs,
+        rename={"file_path": "source"},
+        add={"collection": "handbook"},
+        compute={"citation": "{source}, page {page}"},
+        drop=["page", "noise"],
+    )
+    assert result[0].metadata == {
+        "source": "a.txt",
+        "collection": "handbook",
+        "citation": "a.txt, page 3",
+    }
+    assert result[1].metadata == {"collection": "handbook", "citation": ", page "}
+    assert docs[0].metadata["file_path"] == "a.txt"
+
+
+def test_transform_metadata_swaps_renamed_keys() -> None:
+    doc = Document(page_content="a", metadata={"a": "1", "b": "2"})
+    (result,) = transform_metadata([doc], rename={"a": "b", "b": "a"})
+    assert result.metadata == {"a": "2", "b": "1"}
+
+
+def test_splitter_chain_filter() -> None:
+    doc = Document(page_content="# A\n\nSome text here.\n\n# B\n\n", metadata={})
+    chunks = SplitterChain().markdown_headers().recursive(10).filter(4).split(doc)
+    assert all(len(chunk.page_content) >= 4 for chunk in chunks)
+    assert "# B" not in [chunk.page_content for chunk in chunks]
+
+
+class RecordingHandler(logging.Handler):
+    def __init__(self) -> None:
+        super().__init__(level=1)
+        self.messages: list[str] = []
+
+    def emit(self, record: logging.LogRecord) -> None:
+        self.messages.append(record.getMessage())
+
+
+def test_set_log_level_logs_batch_timings() -> None:
+    logger = logging.getLogger("rs_document")
+    handler = RecordingHandler()
+    logger.addHandler(handler)
+    logger.setLevel(logging.DEBUG)
+    docs = [Document(page_content="One. Two. Three.", metadata={})] * 2
+    try:
+        set_log_level("debug")
+        clean_and_split_docs(docs, chunk_size=10)
+        set_log_level(None)
+        clean_and_split_docs(docs, chunk_size=10)
+    finally:
+        set_log_level(None)
+        logger.removeHandler(handler)
+        logger.setLevel(logging.NOTSET)
+    assert [message.split(" took ")[0] for message in handler.messages] == [
+        "extract_documents",
+        "clean_and_split",
+        "convert",
+        "clean_and_split_docs",
+    ]
+    assert handler.messages[-1].endswith("docs=2 chunks=4")
+
+
+def test_set_log_level_rejects_unknown_levels() -> None:
+    with pytest.raises(ValueError, match="Unknown log level"):
+        set_log_level("loud")
+
+
+def test_clean_and_split_docs_async_matches_blocking() -> None:
+    docs = [
+        Document(page_content=f"Document {index}. " * 40, metadata={"index": str(index)})
+        for index in range(20)
+    ]
+
+    async def split() -> list:
+        return await asyncio.gather(
+            clean_and_split_docs_async(docs, 100, provenance=True),
+            clean_and_split_docs_async(docs, 50),
+        )
+
+    chunks, _ = asyncio.run(split())
+    expected = clean_and_split_docs(docs, 100, provenance=True)
+    assert [chunk.page_content for chunk in chunks] == [
+        chunk.page_content for chunk in expected
+    ]
+    assert [chunk.metadata for chunk in chunks] == [chunk.metadata for chunk in expected]
+
+
+def test_clean_and_split_docs_async_checks_arguments() -> None:
+    async def split() -> None:
+        await clean_and_split_docs_async([], 100, output_type="pandas")
+
+    with pytest.raises(ValueError, match="Unknown output_type"):
+        asyncio.run(split())
+
+
+PIPELINE_YAML = """
+cleaners:
+  - clean_footnotes: {mode: inline}
+  - clean_ligatures
+splitter:
+  - markdown_headers: {max_level: 1}
+  - recursive: {chunk_size: 40}
+provenance: true
+id_key: doc_id
+"""
+
+
+def test_pipeline_from_yaml_config(tmp_path: Path) -> None:
+    path = tmp_path / "pipeline.yaml"
+    path.write_text(PIPELINE_YAML)
+    pipeline = Pipeline.from_config(path)
+    doc = Document(
+        page_content="# Intro\n\nIt  works.[1]\n\n[1] Mostly.",
+        metadata={"doc_id": "a"},
+    )
+    chunks = pipeline.run(doc)
+    assert [chunk.page_content for chunk in chunks] == ["# Intro\n\nIt  works. (Mostly.)"]
+    assert chunks[0].metadata["h1"] == "Intro"
+    assert chunks[0].metadata["parent_id"] == "a"
+    chunks = pipeline.run_docs([doc, {"page_content": "# Other", "metadata": {}}])
+    assert [chunk.metadata["h1"] for chunk in chunks] == ["Intro", "Other"]
+
+
+def test_pipeline_from_json_config_matches_python(tmp_path: Path) -> None:
+    path = tmp_path / "pipeline.json"
+    config = {"splitter": [{"characters": {"num_characters": 8, "overlap": 2}}]}
+    path.write_text(json.dumps(config))
+    doc = Document(page_content="Some  text to split up.", metadata={})
+    built = Pipeline(splitter=SplitterChain().characters(8, overlap=2))
+    chunks = Pipeline.from_config(path).run(doc)
+    assert [chunk.page_content for chunk in chunks] == [
+        chunk.page_content for chunk in built.run(doc)
+    ]
+    assert chunks[0].page_content == "Some tex"
+
+
+def test_pipeline_counts_characters(tmp_path: Path) -> None:
+    path = tmp_path / "pipeline.yaml"
+    path.write_text(
+        "cleaners: []\nsplitter:\n  - recursive: {chunk_size: 60}\n"
+        "  - filter: {min_chunk_size: 10}\n"
+    )
+    chunks = Pipeline.from_config(path).run(Document(page_content=GERMAN, metadata={}))
+    assert all(10 <= len(chunk.page_content) <= 60 for chunk in chunks)
+    assert any(len(chunk.page_content.encode()) > 60 for chunk in chunks)
+
+
+@pytest.mark.parametrize(
+    "config, message",
+    [
+        ("cleaners: [clean_everything]", "Unknown cleaner"),
+        ("splitter:\n  - recursive: {chunk_sise: 40}", "unknown field `chunk_sise`"),
+        ("splitter:\n  - tokens: {chunk_size: 40}", "unknown variant `tokens`"),
+        ("splitter:\n  - markdown_headers: {max_level: 9}", "splitter step 1"),
+        ("chunk_size: 40", "unknown field `chunk_size`"),
+    ],
+)
+def test_pipeline_config_errors(tmp_path: Path, config: str, message: str) -> None:
+    path = tmp_path / "pipeline.yaml"
+    path.write_text(config)
+    with pytest.raises(ValueError, match=message):
+        Pipeline.from_config(path)
+
+
+def test_pipeline_rejects_unknown_config_format(tmp_path: Path) -> None:
+    path = tmp_path / "pipeline.toml"
+    path.write_text("")
+    with pytest.raises(ValueError, match="expected a .yaml, .yml or .json file"):
+        Pipeline.from_config(path)
+
+
+INCREMENTAL_DOCS = [
+    Document("First sentence here. Second sentence there.", {"id": "a"}),
+    Document("Another document.", {"id": "b"}),
+    Document("A document without an id.", {}),
+]
+
+
+def test_incremental_processor_reuses_unchanged_documents(tmp_path: Path) -> None:
+    cache = tmp_path / "cache.db"
+    first = IncrementalProcessor(cache, 25).process(INCREMENTAL_DOCS)
+    assert (first["processed"], first["reused"], first["removed"]) == (3, 0, [])
+    ids = [chunk.metadata["id"] for chunk in first["chunks"]]
+    assert first["added"] == ids
+    expected = clean_and_split_docs(INCREMENTAL_DOCS, 25)
+    assert [chunk.page_content for chunk in first["chunks"]] == [
+        chunk.page_content for chunk in expected
+    ]
+
+    second = IncrementalProcessor(cache, 25).process(INCREMENTAL_DOCS)
+    assert (second["processed"], second["reused"]) == (0, 3)
+    assert (second["added"], second["removed"]) == ([], [])
+    assert [chunk.metadata["id"] for chunk in second["chunks"]] == ids
+
+
+def test_incremental_processor_reports_changed_chunks(tmp_path: Path) -> None:
+    processor = IncrementalProcessor(tmp_path / "cache.db", 25)
+    first = processor.process(INCREMENTAL_DOCS)
+    old_ids = [chunk.metadata["id"] for chunk in first["chunks"]]
+    changed = Document("First sentence here. Second sentence changed.", {"id": "a"})
+    result = processor.process([changed, INCREMENTAL_DOCS[1]])
+    assert (result["processed"], result["reused"]) == (1, 1)
+    new_ids = [chunk.metadata["id"] for chunk in result["chunks"]]
+    assert new_ids[:2] == old_ids[:2]
+    assert result["added"] == [new_ids[2]]
+    assert result["removed"] == [old_ids[2], *old_ids[4:]]
+
+
+def test_incremental_processor_without_pruning(tmp_path: Path) -> None:
+    processor = IncrementalProcessor(tmp_path / "cache.db", 25)
+    processor.process(INCREMENTAL_DOCS)
+    result = processor.process(INCREMENTAL_DOCS[:1], prune=False)
+    assert (result["processed"], result["removed"]) == (0, [])
+    assert processor.process(INCREMENTAL_DOCS)["processed"] == 0
+    processor.clear()
+    assert processor.process(INCREMENTAL_DOCS)["processed"] == 3
+
+
+def test_incremental_processor_invalidates_on_new_settings(tmp_path: Path) -> None:
+    IncrementalProcessor(tmp_path / "cache.db", 25).process(INCREMENTAL_DOCS)
+    result = IncrementalProcessor(tmp_path / "cache.db", 40).process(INCREMENTAL_DOCS)
+    assert result["processed"] == 3
+
+
+def test_incremental_processor_rejects_duplicate_ids(tmp_path: Path) -> None:
+    processor = IncrementalProcessor(tmp_path / "cache.db", 25)
+    with pytest.raises(ValueError, match="unique"):
+        processor.process([INCREMENTAL_DOCS[0], INCREMENTAL_DOCS[0]])
+
+
+def test_log_env_var_typo_only_warns() -> None:
+    import os
+    import subprocess
+    import sys
+
+    path = os.pathsep.join(sys.path)
+    env = dict(os.environ, RS_DOCUMENT_LOG="verbose", PYTHONPATH=path)
+    result = subprocess.run(
+        [sys.executable, "-c", "import rs_document"],
+        env=env,
+        capture_output=True,
+        text=True,
+    )
+    assert result.returncode == 0
+    assert "RuntimeWarning" in result.stderr
+    assert "RS_DOCUMENT_LOG" in result.stderr
+
+
+def test_trace_records_without_the_gil_are_capped() -> None:
+    logger = logging.getLogger("rs_document")
+    handler = RecordingHandler()
+    logger.addHandler(handler)
+    logger.setLevel(1)
+    docs = [Document(page_content="One. Two. Three.", metadata={})] * 5_000
+    try:
+        set_log_level("trace")
+        clean_and_split_docs(docs, chunk_size=10)
+    finally:
+        set_log_level(None)
+        logger.removeHandler(handler)
+        logger.setLevel(logging.NOTSET)
+    dropped = [message for message in handler.messages if message.startswith("dropped")]
+    assert len(dropped) == 1
+    assert len(handler.messages) <= 10_000 + 10
+
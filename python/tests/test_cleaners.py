@@ -41,3 +41,218 @@ def test_extra_whitespace_cleanup() -> None:
     doc = Document(page_content="ITEM 1.     BUSINESS ", metadata={})
     doc.clean_extra_whitespace()
     assert str(doc) == 'Document(page_content="ITEM 1. BUSINESS", metadata={})'
+
+
+def test_extra_whitespace_cleanup_newlines_and_nbsp() -> None:
+    doc = Document(page_content=" \u00a0Caf\u00e9 \n\n au \u00a0 lait\u00a2 ", metadata={})
+    doc.clean_extra_whitespace()
+    assert doc.page_content == "Caf\u00e9 au lait\u00a2"
+
+
+def test_non_ascii_characters_cleanup_multibyte() -> None:
+    doc = Document(page_content="na\u00efve caf\u00e9 \U0001f389 done", metadata={})
+    doc.clean_non_ascii_chars()
+    assert doc.page_content == "nave caf  done"
+
+
+def test_group_broken_paragraphs() -> None:
+    doc = Document(
+        page_content="The big red fox\nis walking down the lane.\n\n"
+        "At the end of the lane\nthe fox met a bear.",
+        metadata={},
+    )
+    doc.group_broken_paragraphs()
+    assert doc.page_content == (
+        "The big red fox is walking down the lane.\n\n"
+        "At the end of the lane the fox met a bear."
+    )
+
+
+def test_group_broken_bullet_paragraphs() -> None:
+    doc = Document(
+        page_content="○ The big red fox\nis walking down the lane.\n\n"
+        "○ At the end of the lane\nthe fox met a friendly bear.",
+        metadata={},
+    )
+    doc.group_broken_paragraphs()
+    assert doc.page_content == (
+        "○ The big red fox is walking down the lane.\n\n"
+        "○ At the end of the lane the fox met a friendly bear."
+    )
+
+
+def test_group_broken_ocr_bullet_paragraphs() -> None:
+    # OCR often reads a bullet as an "e", which is turned back into a bullet.
+    doc = Document(
+        page_content="e The big red fox\nis walking down the lane.\n\n"
+        "e At the end of the lane\nthe fox met a bear.",
+        metadata={},
+    )
+    doc.group_broken_paragraphs()
+    assert doc.page_content == (
+        "· The big red fox is walking down the lane.\n\n"
+        "· At the end of the lane the fox met a bear."
+    )
+
+
+def test_group_broken_bullet_paragraphs_keep_wrapped_e() -> None:
+    # Only an "e" that starts the paragraph is a bullet, not one that wrapped.
+    doc = Document(
+        page_content="· Vowels such as a, i, o and\ne are common in English words.",
+        metadata={},
+    )
+    doc.group_broken_paragraphs()
+    assert doc.page_content == (
+        "· Vowels such as a, i, o and e are common in English words."
+    )
+
+
+def test_clean_bullets_only_strips_leading_bullet() -> None:
+    doc = Document(page_content="A well-known point", metadata={})
+    doc.clean_bullets()
+    assert doc.page_content == "A well-known point"
+
+
+FOOTNOTED = """The court held that the statute applied.¹ It was appealed [2].
+
+¹ See the 2019 decision,
+which was later overruled.
+[2] Case No. 12-345.
+
+The area was 40 m² in total.³
+
+¹ A note that restarts numbering on a new page."""
+
+
+def test_footnotes_strip() -> None:
+    doc = Document(page_content=FOOTNOTED, metadata={})
+    doc.clean_footnotes()
+    assert doc.page_content == (
+        "The court held that the statute applied. It was appealed.\n\n"
+        "The area was 40 m² in total.³"
+    )
+
+
+def test_footnotes_inline() -> None:
+    doc = Document(page_content=FOOTNOTED, metadata={})
+    doc.clean_footnotes(mode="inline")
+    assert doc.page_content.startswith(
+        "The court held that the statute applied. (See the 2019 decision, which was"
+        " later overruled.) It was appealed (Case No. 12-345.)."
+    )
+
+
+def test_footnotes_metadata() -> None:
+    doc = Document(
+        page_content="Shown in prior work.¹\n\n¹ Smith 2020.\n\nNext page.²",
+        metadata={"source": "paper.pdf"},
+    )
+    doc.clean_footnotes(mode="metadata")
+    assert doc.page_content == "Shown in prior work.\n\nNext page.²"
+    assert doc.metadata == {"source": "paper.pdf", "footnote_1": "Smith 2020."}
+
+
+def test_footnotes_restart_numbering_per_page() -> None:
+    text = "First.¹\n¹ Note one.\n\nSecond.¹\n¹ Note two."
+    doc = Document(page_content=text, metadata={})
+    doc.clean_footnotes(mode="inline")
+    assert doc.page_content == "First. (Note one.)\n\nSecond. (Note two.)"
+
+
+def test_footnotes_keep_numbered_lists() -> None:
+    text = (
+        "The result [1] is shown.\n\n"
+        "1. Install the package\n2. Run the tool\n3. Check the output"
+    )
+    doc = Document(page_content=text, metadata={})
+    doc.clean_footnotes("strip")
+    assert doc.page_content == (
+        "The result is shown.\n\n"
+        "1. Install the package\n2. Run the tool\n3. Check the output"
+    )
+
+
+def test_footnotes_end_at_the_next_label() -> None:
+    text = "Shown here.¹\n\n¹ First note\n² Not a note\ncontinued"
+    doc = Document(page_content=text, metadata={})
+    doc.clean_footnotes("metadata")
+    assert doc.page_content == "Shown here.\n\n² Not a note\ncontinued"
+    assert doc.metadata == {"footnote_1": "First note"}
+
+
+def test_footnotes_keep_indexes() -> None:
+    doc = Document(page_content="Read arr[1] first [2].\n\n[2] A note.", metadata={})
+    doc.clean_footnotes()
+    assert doc.page_content == "Read arr[1] first."
+
+
+def test_footnotes_unknown_mode() -> None:
+    doc = Document(page_content="Text.", metadata={})
+    with pytest.raises(ValueError, match="Unknown footnote mode"):
+        doc.clean_footnotes(mode="move")
+
+
+TWO_COLUMNS = """Annual Report
+The fox ran down the lane     and met a bear at the
+at the end of a long day,     end of it.
+without stopping at all.
+                              They walked home
+Then it rested for a while.   together after that.
+
+Page 2"""
+
+
+def test_reflow_two_columns() -> None:
+    doc = Document(page_content=TWO_COLUMNS, metadata={})
+    doc.reflow_columns()
+    assert doc.page_content.split("\n") == [
+        "Annual Report",
+        "The fox ran down the lane",
+        "at the end of a long day,",
+        "without stopping at all.",
+        "",
+        "Then it rested for a while.",
+        "and met a bear at the",
+        "end of it.",
+        "",
+        "They walked home",
+        "together after that.",
+        "",
+        "Page 2",
+    ]
+
+
+def test_reflow_three_columns() -> None:
+    rows = [
+        ("First column, line one", "Second column, line one", "Third column, line one"),
+        ("First column, line two", "Second column, line two", "Third column, line two"),
+        ("First column, line six", "Second column, line six", "Third column, line six"),
+    ]
+    text = "\n".join(f"{a:<26}{b:<27}{c}" for a, b, c in rows)
+    doc = Document(page_content=text, metadata={})
+    doc.reflow_columns()
+    assert doc.page_content.split("\n") == [row[column] for column in range(3) for row in rows]
+
+
+def test_reflow_leaves_tables_alone() -> None:
+    text = "Name      Age   City\nAlice     30    Paris\nBob       41    Lyon"
+    doc = Document(page_content=text, metadata={})
+    doc.reflow_columns()
+    assert doc.page_content == text
+
+
+def test_clean_leaves_aligned_text_alone() -> None:
+    # reflow_columns would take this for two columns, so clean() must not call it.
+    text = (
+        "Option name                  What it does\n"
+        "Configuration file path      The path to the YAML file to read\n"
+        "Output directory path        Where the chunks are written\n"
+        "Logging verbosity level      How much the tool prints"
+    )
+    doc = Document(page_content=text, metadata={})
+    doc.clean()
+    assert doc.page_content == (
+        "Option name What it does Configuration file path The path to the YAML file to"
+        " read Output directory path Where the chunks are written Logging verbosity"
+        " level How much the tool prints"
+    )
@@ -0,0 +1,477 @@
+import asyncio
+from pathlib import Path
+
+import pytest
+from rs_document import (
+    Document,
+    load_csv,
+    load_jsonl,
+    load_markdown,
+    load_parquet,
+    load_sqlite,
+    load_text_files,
+    load_text_files_async,
+    load_url,
+    load_url_async,
+    load_urls,
+    load_urls_async,
+    write_parquet,
+)
+
+
+def test_from_file(tmp_path: Path) -> None:
+    path = tmp_path / "note.txt"
+    path.write_text("Hello from disk!")
+    doc = Document.from_file(path)
+    assert doc.page_content == "Hello from disk!"
+    assert doc.metadata["source"] == str(path)
+    assert doc.metadata["size"] == "16"
+    assert int(doc.metadata["mtime"]) > 0
+
+
+def test_from_file_with_encoding(tmp_path: Path) -> None:
+    path = tmp_path / "latin.txt"
+    path.write_bytes("café".encode("latin-1"))
+    assert Document.from_file(path, encoding="latin1").page_content == "café"
+    with pytest.raises(ValueError, match="is not valid UTF-8"):
+        Document.from_file(path, errors="error")
+
+
+CP1252_TEXT = "Ça coûte 5 € à Zürich, n’est-ce pas ?"
+LATIN1_TEXT = "Grüße aus Köln, schöne Straße und Bäume."
+
+
+@pytest.mark.parametrize(
+    "data, text",
+    [
+        (CP1252_TEXT.encode("cp1252"), CP1252_TEXT),
+        (LATIN1_TEXT.encode("latin-1"), LATIN1_TEXT),
+        ("\ufeffUTF-8 with a BOM: café".encode("utf-8"), "UTF-8 with a BOM: café"),
+        ("UTF-16 café".encode("utf-16"), "UTF-16 café"),
+        ("\ufeffUTF-16BE café".encode("utf-16-be"), "UTF-16BE café"),
+    ],
+)
+def test_from_file_detects_encoding(tmp_path: Path, data: bytes, text: str) -> None:
+    path = tmp_path / "legacy.txt"
+    path.write_bytes(data)
+    assert Document.from_file(path).page_content == text
+    assert load_text_files([path])[0].page_content == text
+
+
+def test_from_file_replaces_invalid_bytes(tmp_path: Path) -> None:
+    path = tmp_path / "broken.txt"
+    path.write_bytes(b"valid \xff text")
+    doc = Document.from_file(path, errors="replace")
+    assert doc.page_content == "valid \ufffd text"
+    with pytest.raises(ValueError, match="Unknown errors"):
+        load_text_files([path], errors="ignore")
+
+
+def test_load_markdown_detects_encoding(tmp_path: Path) -> None:
+    path = tmp_path / "legacy.md"
+    path.write_bytes("---\ntitle: Café\n---\n# Crème brûlée".encode("latin-1"))
+    doc = load_markdown(path)
+    assert doc.metadata["title"] == "Café"
+    assert doc.page_content == "# Crème brûlée"
+
+
+def test_from_missing_file(tmp_path: Path) -> None:
+    with pytest.raises(FileNotFoundError):
+        Document.from_file(tmp_path / "missing.txt")
+
+
+def test_load_text_files(tmp_path: Path) -> None:
+    paths = []
+    for index in range(3):
+        path = tmp_path / f"{index}.txt"
+        path.write_text(f"file {index}")
+        paths.append(path)
+    docs = load_text_files(paths)
+    assert [doc.page_content for doc in docs] == ["file 0", "file 1", "file 2"]
+
+
+def test_load_text_files_async(tmp_path: Path) -> None:
+    path = tmp_path / "note.txt"
+    path.write_text("async file")
+
+    async def load(path: Path) -> list[Document]:
+        return await load_text_files_async([path])
+
+    docs = asyncio.run(load(path))
+    assert [doc.page_content for doc in docs] == ["async file"]
+    with pytest.raises(OSError):
+        asyncio.run(load(tmp_path / "missing.txt"))
+
+
+def test_load_text_files_async_more_than_the_pool(tmp_path: Path) -> None:
+    path = tmp_path / "note.txt"
+    path.write_text("async file")
+
+    async def load() -> list:
+        return await asyncio.gather(
+            *(load_text_files_async([path]) for _ in range(64))
+        )
+
+    results = asyncio.run(load())
+    assert len(results) == 64
+    assert all(docs[0].page_content == "async file" for docs in results)
+
+
+def test_async_loaders_need_a_running_loop(tmp_path: Path) -> None:
+    with pytest.raises(RuntimeError):
+        load_text_files_async([tmp_path / "note.txt"])
+
+
+HTML_PAGE = """
+<html>
+  <head>
+    <title>  Fox News   Weekly </title>
+    <meta name="description" content="All about foxes">
+    <link rel="canonical" href="https://example.com/foxes">
+    <script>var tracking = true;</script>
+  </head>
+  <body>
+    <nav><a href="/">Home</a></nav>
+    <main>
+      <h1>The big red fox</h1>
+      <p>The fox is <b>walking</b>
+         down the lane.</p>
+      <p>At the end of the lane the fox met a bear.</p>
+    </main>
+    <footer>Copyright</footer>
+  </body>
+</html>
+"""
+
+
+def test_from_html() -> None:
+    doc = Document.from_html(HTML_PAGE)
+    assert doc.page_content == (
+        "The big red fox\n\n"
+        "The fox is walking down the lane.\n\n"
+        "At the end of the lane the fox met a bear."
+    )
+    assert doc.metadata == {
+        "title": "Fox News Weekly",
+        "description": "All about foxes",
+        "canonical_url": "https://example.com/foxes",
+    }
+
+
+def test_from_html_keeps_inline_text_together() -> None:
+    doc = Document.from_html(
+        '<html><head><link rel="canonical nofollow" href="https://example.com/water">'
+        "</head><body><p>H<sub>2</sub>O is <a href='/water'>water</a>.</p>"
+        "<table><tr><th>Name</th><th>Formula</th></tr>"
+        "<tr><td>Water</td><td>H<sub>2</sub>O</td></tr></table></body></html>"
+    )
+    assert doc.page_content == "H2O is water.\n\nName Formula\n\nWater H2O"
+    assert doc.metadata == {"canonical_url": "https://example.com/water"}
+
+
+def test_from_html_file(tmp_path: Path) -> None:
+    path = tmp_path / "page.html"
+    path.write_text(HTML_PAGE)
+    doc = Document.from_html(path)
+    assert doc.page_content.startswith("The big red fox")
+    assert doc.metadata["source"] == str(path)
+
+
+def test_load_markdown_yaml_frontmatter(tmp_path: Path) -> None:
+    path = tmp_path / "post.md"
+    path.write_text(
+        "---\ntitle: Foxes\ntags: [animals, red]\ndraft: false\n---\n\n# Foxes\n\nHi!\n"
+    )
+    doc = load_markdown(path)
+    assert doc.page_content == "# Foxes\n\nHi!\n"
+    assert doc.metadata["title"] == "Foxes"
+    assert doc.metadata["tags"] == '["animals","red"]'
+    assert doc.metadata["draft"] == "false"
+    assert doc.metadata["source"] == str(path)
+
+
+def test_load_markdown_toml_frontmatter(tmp_path: Path) -> None:
+    path = tmp_path / "post.md"
+    path.write_text('+++\ntitle = "Foxes"\ndate = 2024-01-02\n+++\nBody')
+    doc = load_markdown(path)
+    assert doc.page_content == "Body"
+    assert doc.metadata["title"] == "Foxes"
+    assert doc.metadata["date"] == "2024-01-02"
+
+
+def test_load_markdown_without_frontmatter(tmp_path: Path) -> None:
+    path = tmp_path / "post.md"
+    path.write_text("---- not frontmatter\nBody")
+    assert load_markdown(path).page_content == "---- not frontmatter\nBody"
+
+
+def test_load_csv(tmp_path: Path) -> None:
+    path = tmp_path / "tickets.csv"
+    path.write_text("id,title,body\n1,Broken,It does not work\n2,Slow,\"Takes, ages\"\n")
+    docs = load_csv(path, content_columns=["title", "body"])
+    assert [doc.page_content for doc in docs] == [
+        "title: Broken\nbody: It does not work",
+        "title: Slow\nbody: Takes, ages",
+    ]
+    assert docs[1].metadata == {"id": "2", "source": str(path), "row": "1"}
+
+
+def test_load_csv_column_named_like_loader_metadata(tmp_path: Path) -> None:
+    path = tmp_path / "pages.csv"
+    path.write_text("source,text\nhttps://example.com,Hello\n")
+    with pytest.raises(ValueError, match="source"):
+        load_csv(path, content_columns=["text"])
+    docs = load_csv(path, content_columns=["text"], metadata_columns=[])
+    assert docs[0].metadata == {"source": str(path), "row": "0"}
+
+
+def test_load_csv_detects_encoding(tmp_path: Path) -> None:
+    path = tmp_path / "legacy.csv"
+    data = f'id,text\n1,"{CP1252_TEXT}"\n'
+    for encoding in ["cp1252", "utf-16"]:
+        path.write_bytes(data.encode(encoding))
+        docs = load_csv(path, metadata_columns=["id"])
+        assert docs[0].page_content == f"text: {CP1252_TEXT}"
+    path.write_bytes(b"id,text\n1,caf\xe9\n")
+    assert load_csv(path, encoding="latin1")[0].page_content == "id: 1\ntext: caf\u00e9"
+    with pytest.raises(ValueError, match="not valid UTF-8"):
+        load_csv(path, errors="error")
+
+
+def test_load_csv_delimiter_and_missing_column(tmp_path: Path) -> None:
+    path = tmp_path / "tickets.tsv"
+    path.write_text("id\ttitle\n1\tBroken\n")
+    docs = load_csv(path, metadata_columns=["id"], delimiter="\t")
+    assert docs[0].page_content == "title: Broken"
+    with pytest.raises(ValueError):
+        load_csv(path, content_columns=["missing"], delimiter="\t")
+
+
+def test_load_jsonl(tmp_path: Path) -> None:
+    path = tmp_path / "dataset.jsonl"
+    path.write_text(
+        '{"text": "first", "url": "a", "score": 1.5}\n'
+        "\n"
+        '{"text": "second", "url": "b", "score": null}\n'
+    )
+    docs = list(load_jsonl(path))
+    assert [doc.page_content for doc in docs] == ["first", "second"]
+    assert docs[0].metadata == {
+        "url": "a",
+        "score": "1.5",
+        "source": str(path),
+        "row": "0",
+    }
+
+    docs = list(load_jsonl(path, metadata_keys=["url"]))
+    # Rows are line numbers, blank lines included.
+    assert docs[1].metadata == {"url": "b", "source": str(path), "row": "2"}
+
+
+def test_load_jsonl_errors_name_the_line(tmp_path: Path) -> None:
+    path = tmp_path / "dataset.jsonl"
+    path.write_text('{"text": "first"}\n\n\n{"text": oops}\n')
+    with pytest.raises(ValueError, match="line 4 "):
+        list(load_jsonl(path))
+
+
+def test_load_jsonl_key_named_like_loader_metadata(tmp_path: Path) -> None:
+    path = tmp_path / "dataset.jsonl"
+    path.write_text('{"text": "first", "source": "https://example.com", "url": "a"}\n')
+    with pytest.raises(ValueError, match="source"):
+        list(load_jsonl(path))
+    with pytest.raises(ValueError, match="source"):
+        load_jsonl(path, metadata_keys=["source"])
+    docs = list(load_jsonl(path, metadata_keys=["url"]))
+    assert docs[0].metadata == {"url": "a", "source": str(path), "row": "0"}
+
+
+def test_load_jsonl_detects_encoding(tmp_path: Path) -> None:
+    path = tmp_path / "legacy.jsonl"
+    line = f'{{"text": "{CP1252_TEXT}"}}\n'
+    path.write_bytes((line * 3).encode("cp1252"))
+    assert [doc.page_content for doc in load_jsonl(path)] == [CP1252_TEXT] * 3
+    path.write_bytes(f"[{line}]".encode("utf-16"))
+    assert [doc.page_content for doc in load_jsonl(path)] == [CP1252_TEXT]
+    path.write_bytes(b'{"text": "caf\xe9"}\n')
+    assert next(load_jsonl(path, errors="replace")).page_content == "caf\ufffd"
+    with pytest.raises(ValueError, match="not valid UTF-8"):
+        list(load_jsonl(path, errors="error"))
+
+
+def test_load_json_array(tmp_path: Path) -> None:
+    path = tmp_path / "dataset.json"
+    path.write_text('  [{"body": "first"}, {"body": "second"}]')
+    docs = list(load_jsonl(path, content_key="body"))
+    assert [doc.page_content for doc in docs] == ["first", "second"]
+
+
+def test_load_jsonl_missing_content_key(tmp_path: Path) -> None:
+    path = tmp_path / "dataset.jsonl"
+    path.write_text('{"body": "first"}\n')
+    with pytest.raises(ValueError):
+        list(load_jsonl(path))
+
+
+@pytest.fixture()
+def web_server():
+    import threading
+    from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_GET(self) -> None:  # noqa: N802
+            if self.path == "/missing":
+                self.send_response(404)
+                self.send_header("Content-Type", "text/plain")
+                self.end_headers()
+                self.wfile.write(b"Not found")
+                return
+            self.send_response(200)
+            self.send_header("Content-Type", "text/html; charset=utf-8")
+            self.end_headers()
+            self.wfile.write(HTML_PAGE.encode())
+
+        def log_message(self, *args) -> None:
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    yield f"http://127.0.0.1:{server.server_address[1]}"
+    server.shutdown()
+
+
+def test_load_url(web_server: str) -> None:
+    doc = load_url(f"{web_server}/foxes")
+    assert doc.page_content.startswith("The big red fox")
+    assert doc.metadata["title"] == "Fox News Weekly"
+    assert doc.metadata["source"] == f"{web_server}/foxes"
+    assert doc.metadata["status"] == "200"
+    assert int(doc.metadata["fetched_at"]) > 0
+
+
+def test_load_urls(web_server: str) -> None:
+    docs = load_urls([f"{web_server}/foxes", f"{web_server}/missing"], timeout=5)
+    assert docs[0].metadata["status"] == "200"
+    assert docs[1].metadata["status"] == "404"
+    assert docs[1].page_content == "Not found"
+
+
+def test_load_urls_async(web_server: str) -> None:
+    async def load() -> tuple:
+        return await asyncio.gather(
+            load_url_async(f"{web_server}/foxes"),
+            load_urls_async([f"{web_server}/missing", f"{web_server}/foxes"]),
+        )
+
+    doc, docs = asyncio.run(load())
+    assert doc.metadata["title"] == "Fox News Weekly"
+    assert [doc.metadata["status"] for doc in docs] == ["404", "200"]
+
+
+def test_load_url_async_more_than_the_pool(web_server: str) -> None:
+    async def load() -> list:
+        return await asyncio.gather(
+            *(load_url_async(f"{web_server}/foxes") for _ in range(40))
+        )
+
+    docs = asyncio.run(load())
+    assert len(docs) == 40
+    assert all(doc.metadata["status"] == "200" for doc in docs)
+
+
+def test_load_url_connection_error() -> None:
+    with pytest.raises(OSError):
+        load_url("http://127.0.0.1:9/", timeout=1)
+
+
+def test_load_parquet(tmp_path: Path) -> None:
+    pa = pytest.importorskip("pyarrow")
+    pq = pytest.importorskip("pyarrow.parquet")
+    path = tmp_path / "dataset.parquet"
+    table = pa.table({"text": ["a", "b", "c"], "score": [1, None, 3]})
+    pq.write_table(table, path, row_group_size=2)
+
+    docs = load_parquet(path)
+    assert [doc.page_content for doc in docs] == ["a", "b", "c"]
+    assert docs[0].metadata == {"score": "1", "source": str(path), "row": "0"}
+    assert docs[1].metadata == {"source": str(path), "row": "1"}
+    assert docs[2].metadata["row"] == "2"
+
+    with pytest.raises(ValueError):
+        load_parquet(path, content_column="missing")
+
+
+def test_load_parquet_skips_struct_columns(tmp_path: Path) -> None:
+    pa = pytest.importorskip("pyarrow")
+    pq = pytest.importorskip("pyarrow.parquet")
+    path = tmp_path / "nested.parquet"
+    table = pa.table(
+        {"text": ["a", "b"], "score": [1, 2], "info": [{"x": 5}, {"x": 6}]}
+    )
+    pq.write_table(table, path)
+
+    docs = load_parquet(path)
+    assert [doc.page_content for doc in docs] == ["a", "b"]
+    assert docs[0].metadata == {"score": "1", "source": str(path), "row": "0"}
+
+    with pytest.raises(ValueError, match="info"):
+        load_parquet(path, metadata_columns=["info"])
+
+
+def test_load_parquet_column_named_like_loader_metadata(tmp_path: Path) -> None:
+    path = tmp_path / "pages.parquet"
+    write_parquet([Document("Hello", {"source": "https://example.com"})], path)
+    with pytest.raises(ValueError, match="source"):
+        load_parquet(path, content_column="page_content")
+    docs = load_parquet(path, content_column="page_content", metadata_columns=[])
+    assert docs[0].metadata == {"source": str(path), "row": "0"}
+
+
+def test_load_sqlite(tmp_path: Path) -> None:
+    import sqlite3
+
+    path = tmp_path / "wiki.db"
+    connection = sqlite3.connect(path)
+    connection.execute("CREATE TABLE pages (id INTEGER, title TEXT, body TEXT)")
+    connection.executemany(
+        "INSERT INTO pages VALUES (?, ?, ?)",
+        [(1, "Home", "Welcome!"), (2, None, "About us")],
+    )
+    connection.commit()
+    connection.close()
+
+    docs = load_sqlite(path, "SELECT * FROM pages ORDER BY id", "body")
+    assert [doc.page_content for doc in docs] == ["Welcome!", "About us"]
+    assert docs[0].metadata == {
+        "id": "1",
+        "title": "Home",
+        "source": str(path),
+        "row": "0",
+    }
+    assert "title" not in docs[1].metadata
+
+    with pytest.raises(ValueError):
+        load_sqlite(path, "SELECT id FROM pages", "body")
+
+
+def test_load_sqlite_column_named_like_loader_metadata(tmp_path: Path) -> None:
+    import sqlite3
+
+    path = tmp_path / "pages.db"
+    connection = sqlite3.connect(path)
+    connection.execute("CREATE TABLE pages (source TEXT, row INTEGER, body TEXT)")
+    connection.execute("INSERT INTO pages VALUES ('https://example.com', 7, 'Hi')")
+    connection.commit()
+    connection.close()
+
+    with pytest.raises(ValueError, match="source"):
+        load_sqlite(path, "SELECT * FROM pages", "body")
+    with pytest.raises(ValueError, match="row"):
+        load_sqlite(path, "SELECT row, body FROM pages", "body")
+    docs = load_sqlite(path, "SELECT source AS url, body FROM pages", "body")
+    assert docs[0].metadata == {
+        "url": "https://example.com",
+        "source": str(path),
+        "row": "0",
+    }
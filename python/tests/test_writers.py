@@ -0,0 +1,176 @@
+import gzip
+import json
+from pathlib import Path
+
+import pytest
+from rs_document import (
+    Document,
+    docs_from_msgpack,
+    docs_to_arrow_table,
+    docs_to_msgpack,
+    load_jsonl,
+    load_parquet,
+    to_arrow,
+    to_vector_payloads,
+    write_jsonl,
+    write_parquet,
+)
+
+
+def test_write_jsonl(tmp_path: Path) -> None:
+    path = tmp_path / "chunks.jsonl"
+    docs = [
+        Document(page_content="first", metadata={"source": "a"}),
+        Document(page_content="second\nline", metadata={}),
+    ]
+    assert write_jsonl(docs, path) == 2
+    assert write_jsonl(docs[:1], path, append=True) == 1
+
+    lines = path.read_text().splitlines()
+    assert [json.loads(line) for line in lines] == [
+        {"page_content": "first", "metadata": {"source": "a"}},
+        {"page_content": "second\nline", "metadata": {}},
+        {"page_content": "first", "metadata": {"source": "a"}},
+    ]
+    reloaded = list(load_jsonl(path, content_key="page_content"))
+    assert reloaded[1].page_content == "second\nline"
+
+
+def test_write_jsonl_gzip(tmp_path: Path) -> None:
+    path = tmp_path / "chunks.jsonl.gz"
+    docs = [Document(page_content="first", metadata={})]
+    write_jsonl(docs, path)
+    write_jsonl(docs, path, append=True)
+    with gzip.open(path, "rt") as file:
+        assert len(file.read().splitlines()) == 2
+
+
+def test_write_parquet_round_trip(tmp_path: Path) -> None:
+    path = tmp_path / "chunks.parquet"
+    docs = [
+        Document(page_content="first", metadata={"source": "a", "page": "1"}),
+        Document(page_content="second", metadata={"source": "b"}),
+    ]
+    assert write_parquet(docs, path) == 2
+
+    # The "source" column would overwrite the source load_parquet adds.
+    reloaded = load_parquet(
+        path, content_column="page_content", metadata_columns=["page"]
+    )
+    assert [doc.page_content for doc in reloaded] == ["first", "second"]
+    assert reloaded[0].metadata["page"] == "1"
+    assert "page" not in reloaded[1].metadata
+
+
+def test_to_arrow() -> None:
+    pytest.importorskip("pyarrow")
+    docs = [
+        Document(page_content="first", metadata={"source": "a"}),
+        Document(page_content="second", metadata={"page": "2"}),
+    ]
+    table = to_arrow(docs)
+    assert table.column_names == ["page_content", "page", "source"]
+    assert table.to_pydict() == {
+        "page_content": ["first", "second"],
+        "page": [None, "2"],
+        "source": ["a", None],
+    }
+
+
+def test_docs_to_arrow_table() -> None:
+    docs = [
+        Document(page_content="first", metadata={"source": "a"}),
+        Document(page_content="second", metadata={"page": "2"}),
+    ]
+    table = docs_to_arrow_table(docs)
+    assert table.num_rows == len(table) == 2
+    assert table.column_names == ["page_content", "page", "source"]
+    assert "arrow_array_stream" in repr(table.__arrow_c_stream__())
+    assert "arrow_schema" in repr(table.__arrow_c_schema__())
+
+
+def test_docs_to_arrow_table_pyarrow() -> None:
+    pa = pytest.importorskip("pyarrow")
+    docs = [
+        Document(page_content="first", metadata={"source": "a"}),
+        Document(page_content="second", metadata={"page": "2"}),
+    ]
+    assert pa.table(docs_to_arrow_table(docs)).to_pydict() == {
+        "page_content": ["first", "second"],
+        "page": [None, "2"],
+        "source": ["a", None],
+    }
+
+
+def test_msgpack_round_trip() -> None:
+    doc = Document(page_content="hello", metadata={"source": "a"})
+    data = doc.to_msgpack()
+    assert isinstance(data, bytes)
+    assert str(Document.from_msgpack(data)) == str(doc)
+
+    docs = [doc, Document(page_content="world", metadata={})]
+    reloaded = docs_from_msgpack(docs_to_msgpack(docs))
+    assert [str(reloaded_doc) for reloaded_doc in reloaded] == [str(d) for d in docs]
+
+    with pytest.raises(ValueError):
+        docs_from_msgpack(b"not msgpack")
+
+
+def test_to_vector_payloads() -> None:
+    docs = [
+        Document(
+            page_content="first",
+            metadata={
+                "source file": "a.md",
+                "2nd": "x",
+                "tags": '["rust", "python"]',
+                "author": '{"name": "Sam", "email": null}',
+                "empty": "",
+            },
+        ),
+        Document(page_content="second", metadata={}),
+    ]
+    payloads = to_vector_payloads(docs)
+    assert [text for _, text, _ in payloads] == ["first", "second"]
+    assert payloads[0][2] == {
+        "source_file": "a.md",
+        "_2nd": "x",
+        "tags": ["rust", "python"],
+        "author_name": "Sam",
+    }
+    assert payloads[1][2] == {}
+
+    ids = [id for id, _, _ in payloads]
+    assert len(set(ids)) == 2
+    assert all(len(id) == 36 and id[14] == "5" for id in ids)
+    assert [id for id, _, _ in to_vector_payloads(docs)] == ids
+
+
+def test_to_vector_payloads_id_strategies() -> None:
+    docs = [
+        Document(page_content="first", metadata={"id": "doc-1"}),
+        Document(page_content="second", metadata={"id": "doc-2"}),
+    ]
+    assert [id for id, _, _ in to_vector_payloads(docs, id_strategy="metadata")] == [
+        "doc-1",
+        "doc-2",
+    ]
+    assert [id for id, _, _ in to_vector_payloads(docs, id_strategy="index")] == [0, 1]
+
+    with pytest.raises(ValueError):
+        to_vector_payloads(docs, id_strategy="metadata", id_key="missing")
+    with pytest.raises(ValueError):
+        to_vector_payloads(docs, id_strategy="random")
+
+
+@pytest.mark.parametrize(
+    "metadata",
+    [
+        {"source file": "a.md", "source_file": "b.md"},
+        {"author": '{"name": "Sam"}', "author_name": "Kim"},
+    ],
+)
+def test_to_vector_payloads_key_collisions(metadata: dict) -> None:
+    docs = [Document(page_content="first", metadata=metadata)]
+    with pytest.raises(ValueError, match="more than one metadata key"):
+        to_vector_payloads(docs)
@@ -0,0 +1,103 @@
+//! Awaitable versions of the loaders and batch functions, for asyncio event loops.
+//!
+//! These don't use pyo3-asyncio. It turns Rust futures into Python ones, which needs a
+//! tokio or async-std runtime, while the work here is blocking IO and rayon code with
+//! nothing to await. Resolving an asyncio future from a pool thread with
+//! `call_soon_threadsafe` is all that is needed, without another runtime.
+
+use crate::logging;
+use once_cell::sync::OnceCell;
+use pyo3::exceptions::PyOSError;
+use pyo3::prelude::*;
+use pyo3::types::{PyCFunction, PyDict, PyTuple};
+
+/// The number of threads that run blocking awaitables, like `load_url_async`, at the
+/// same time. The others wait for one of them to free up.
+const IO_THREADS: usize = 16;
+
+static IO_POOL: OnceCell<rayon::ThreadPool> = OnceCell::new();
+
+/// The thread pool for blocking awaitables, built the first time one is awaited.
+///
+/// It is separate from the global rayon pool, so that waiting on the network doesn't
+/// take threads from CPU-bound work.
+fn io_pool() -> PyResult<&'static rayon::ThreadPool> {
+    IO_POOL.get_or_try_init(|| {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(IO_THREADS)
+            .thread_name(|index| format!("rs_document-io-{}", index))
+            .build()
+            .map_err(|error| PyOSError::new_err(error.to_string()))
+    })
+}
+
+/// Which thread pool the work of an awaitable runs on.
+#[derive(Clone, Copy)]
+pub(crate) enum Work {
+    /// Work that mostly waits, like fetching URLs, which runs on a pool of its own.
+    Blocking,
+    /// CPU-bound work, like cleaning and splitting, which runs on the global rayon pool
+    /// along with its parallel sections.
+    Compute,
+}
+
+/// Runs `work` on the thread pool for `kind` and returns an asyncio future, on the
+/// running event loop, that resolves to its result after `convert` has turned it into a
+/// Python object.
+///
+/// The pool doesn't hold the GIL while `work` runs, so neither the event loop nor other
+/// Python threads are blocked. When every thread of the pool is busy, `work` waits for
+/// one to free up. Cancelling the future doesn't stop `work`, but its result is thrown
+/// away.
+///
+/// Raises `RuntimeError` when no event loop is running in the calling thread.
+pub(crate) fn spawn_awaitable<'py, T, W, C>(
+    py: Python<'py>,
+    kind: Work,
+    work: W,
+    convert: C,
+) -> PyResult<&'py PyAny>
+where
+    T: Send + 'static,
+    W: FnOnce() -> PyResult<T> + Send + 'static,
+    C: FnOnce(Python, T) -> PyResult<PyObject> + Send + 'static,
+{
+    let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+    let future = event_loop.call_method0("create_future")?;
+    let (event_loop, awaitable): (PyObject, PyObject) = (event_loop.into(), future.into());
+    let future = awaitable.clone_ref(py);
+    let job = move || {
+        let result = work();
+        Python::with_gil(|py| {
+            // The records logged by `work` waited for the GIL.
+            logging::flush(py);
+            let (method, value) = match result.and_then(|value| convert(py, value)) {
+                Ok(value) => ("set_result", value),
+                Err(error) => ("set_exception", error.into_py(py)),
+            };
+            let resolve = PyCFunction::new_closure(
+                py,
+                None,
+                None,
+                move |args: &PyTuple, _kwargs: Option<&PyDict>| -> PyResult<()> {
+                    let py = args.py();
+                    let future = future.as_ref(py);
+                    if !future.call_method0("done")?.is_true()? {
+                        future.call_method1(method, (value.clone_ref(py),))?;
+                    }
+                    Ok(())
+                },
+            );
+            // The loop may have been closed while the work ran, and then nobody is
+            // waiting for the result.
+            if let Ok(resolve) = resolve {
+                let _ = event_loop.call_method1(py, "call_soon_threadsafe", (resolve,));
+            }
+        });
+    };
+    match kind {
+        Work::Blocking => io_pool()?.spawn(job),
+        Work::Compute => rayon::spawn(job),
+    }
+    Ok(awaitable.into_ref(py))
+}
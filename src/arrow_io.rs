@@ -0,0 +1,178 @@
+use crate::{extract_documents, Document};
+use arrow::array::{ArrayRef, RecordBatch, RecordBatchIterator, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::ffi::FFI_ArrowSchema;
+use arrow::ffi_stream::FFI_ArrowArrayStream;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyCapsule;
+use rayon::prelude::*;
+use std::collections::BTreeSet;
+use std::ffi::CString;
+use std::sync::Arc;
+
+/// The name of the column holding each document's page_content.
+const CONTENT_COLUMN: &str = "page_content";
+
+/// Returns the column name used for a metadata key.
+///
+/// Keys are used as column names directly, except for a key that would clash with the
+/// page_content column, which is prefixed with `metadata.`.
+fn metadata_column_name(key: &str) -> String {
+    if key == CONTENT_COLUMN {
+        format!("metadata.{}", key)
+    } else {
+        key.to_string()
+    }
+}
+
+/// Converts documents into an Arrow record batch.
+///
+/// The batch has a `page_content` column followed by one nullable string column per
+/// metadata key found in any of the documents, in sorted order. Documents missing a key
+/// have a null in that column. Columns are built in parallel.
+pub(crate) fn documents_to_record_batch(documents: &[Document]) -> PyResult<RecordBatch> {
+    let keys: BTreeSet<&String> = documents
+        .iter()
+        .flat_map(|document| document.metadata.keys())
+        .collect();
+
+    let content: ArrayRef = Arc::new(StringArray::from_iter_values(
+        documents
+            .iter()
+            .map(|document| document.page_content.as_str()),
+    ));
+    let metadata_columns: Vec<ArrayRef> = keys
+        .par_iter()
+        .map(|key| {
+            let values = StringArray::from_iter(
+                documents
+                    .iter()
+                    .map(|document| document.metadata.get(*key).map(String::as_str)),
+            );
+            Arc::new(values) as ArrayRef
+        })
+        .collect();
+
+    let mut fields = vec![Field::new(CONTENT_COLUMN, DataType::Utf8, false)];
+    fields.extend(
+        keys.iter()
+            .map(|key| Field::new(metadata_column_name(key), DataType::Utf8, true)),
+    );
+    let mut columns = vec![content];
+    columns.extend(metadata_columns);
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+        .map_err(|error| PyValueError::new_err(error.to_string()))
+}
+
+/// Converts documents into a `pyarrow.Table`.
+///
+/// The table has a `page_content` column followed by one string column per metadata key,
+/// with nulls where a document doesn't have that key. The data is handed to pyarrow
+/// through the Arrow C stream interface, so pyarrow must be installed.
+///
+/// # Arguments
+///
+/// * `docs` - The documents to convert.
+#[pyfunction]
+pub fn to_arrow(py: Python, docs: &PyAny) -> PyResult<PyObject> {
+    let doc_vec: Vec<Document> = extract_documents(docs)?;
+    let batch = py.allow_threads(|| documents_to_record_batch(&doc_vec))?;
+
+    let pyarrow = py.import("pyarrow")?;
+    let schema = batch.schema();
+    let reader = RecordBatchIterator::new(vec![Ok(batch)], schema);
+    let mut stream = FFI_ArrowArrayStream::new(Box::new(reader));
+    let stream_pointer = &mut stream as *mut FFI_ArrowArrayStream as usize;
+    let reader = pyarrow
+        .getattr("RecordBatchReader")?
+        .call_method1("_import_from_c", (stream_pointer,))?;
+    Ok(reader.call_method0("read_all")?.into())
+}
+
+/// A table of documents that can be handed to other Arrow libraries without copying.
+///
+/// The table implements the Arrow PyCapsule interface (`__arrow_c_stream__` and
+/// `__arrow_c_schema__`), so `pyarrow.table(...)`, `polars.DataFrame(...)` and other
+/// consumers read the columns straight out of the buffers built in Rust.
+#[pyclass]
+pub struct ArrowTable {
+    batch: RecordBatch,
+}
+
+#[pymethods]
+impl ArrowTable {
+    /// The number of rows, which is the number of documents.
+    #[getter]
+    fn num_rows(&self) -> usize {
+        self.batch.num_rows()
+    }
+
+    /// The column names, starting with `page_content`.
+    #[getter]
+    fn column_names(&self) -> Vec<String> {
+        self.batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.batch.num_rows()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ArrowTable(num_rows={}, columns={:?})",
+            self.batch.num_rows(),
+            self.column_names()
+        )
+    }
+
+    /// Exports the schema as an `arrow_schema` PyCapsule.
+    fn __arrow_c_schema__<'py>(&self, py: Python<'py>) -> PyResult<&'py PyCapsule> {
+        let schema = FFI_ArrowSchema::try_from(self.batch.schema().as_ref())
+            .map_err(|error| PyValueError::new_err(error.to_string()))?;
+        PyCapsule::new(py, schema, Some(CString::new("arrow_schema").unwrap()))
+    }
+
+    /// Exports the table as an `arrow_array_stream` PyCapsule.
+    ///
+    /// `requested_schema` is accepted for compatibility with the interface, but the table
+    /// is always exported with its own schema.
+    #[pyo3(signature = (requested_schema = None))]
+    fn __arrow_c_stream__<'py>(
+        &self,
+        py: Python<'py>,
+        requested_schema: Option<&PyAny>,
+    ) -> PyResult<&'py PyCapsule> {
+        let _ = requested_schema;
+        let reader = RecordBatchIterator::new(vec![Ok(self.batch.clone())], self.batch.schema());
+        let stream = FFI_ArrowArrayStream::new(Box::new(reader));
+        PyCapsule::new(
+            py,
+            stream,
+            Some(CString::new("arrow_array_stream").unwrap()),
+        )
+    }
+}
+
+/// Converts documents into an `ArrowTable` implementing the Arrow PyCapsule interface.
+///
+/// Unlike `to_arrow`, this doesn't need pyarrow. The table can be passed to any library
+/// that understands `__arrow_c_stream__`, such as `pyarrow.table` or `polars.DataFrame`,
+/// which read the strings without copying them one by one.
+/// The columns are the same as for `to_arrow`.
+///
+/// # Arguments
+///
+/// * `docs` - The documents to convert.
+#[pyfunction]
+pub fn docs_to_arrow_table(py: Python, docs: &PyAny) -> PyResult<ArrowTable> {
+    let doc_vec: Vec<Document> = extract_documents(docs)?;
+    let batch = py.allow_threads(|| documents_to_record_batch(&doc_vec))?;
+    Ok(ArrowTable { batch })
+}
@@ -0,0 +1,248 @@
+//! Finds the regions of markdown that `Document::markdown_splitter` keeps whole, and the
+//! sections under its headings.
+
+use std::ops::Range;
+
+/// The smallest number of rows, header included, that make a table of aligned columns.
+const MIN_ALIGNED_ROWS: usize = 3;
+
+/// What kind of region a `Block` is.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum BlockKind {
+    /// A fenced code block.
+    Fence,
+    /// A pipe table or a table of aligned columns. `body_start` is the byte offset of its
+    /// first row after the header (and the delimiter row of a pipe table).
+    Table { body_start: usize },
+}
+
+/// A region of text that splitters should keep whole.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Block {
+    /// The byte range of the block, covering whole lines without the line break after
+    /// the last one.
+    pub(crate) range: Range<usize>,
+    pub(crate) kind: BlockKind,
+}
+
+/// A run of markdown from one heading to the next.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Section {
+    /// The byte range of the section, starting at its heading and without the whitespace
+    /// at its end.
+    pub(crate) range: Range<usize>,
+    /// The level and title of the section's heading and of each heading above it,
+    /// outermost first.
+    pub(crate) headings: Vec<(usize, String)>,
+}
+
+/// Splits `text` into lines, each paired with its byte offset and without its line break.
+fn lines_with_offsets(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line.trim_end_matches(['\n', '\r'])))
+    })
+}
+
+/// Returns the fence character and length when `line` opens or closes a code fence: three
+/// or more backticks or tildes, indented by at most three spaces.
+fn fence(line: &str) -> Option<(char, usize)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let character = rest.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let length = rest.len() - rest.trim_start_matches(character).len();
+    (length >= 3).then_some((character, length))
+}
+
+/// Returns the level and title when `line` is an ATX heading such as `## Install`: one to
+/// six `#` followed by a space or the end of the line, indented by at most three spaces.
+/// A closing run of `#` is not part of the title.
+fn atx_heading(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let level = trimmed.len() - trimmed.trim_start_matches('#').len();
+    let rest = &trimmed[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    let title = rest.trim();
+    let without_closing = title.trim_end_matches('#');
+    if without_closing.is_empty() || without_closing.ends_with([' ', '\t']) {
+        Some((level, without_closing.trim_end()))
+    } else {
+        Some((level, title))
+    }
+}
+
+/// Whether `line` is the delimiter row under a table header, such as `|---|:--:|`.
+fn is_table_delimiter(line: &str) -> bool {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    !line.is_empty()
+        && line.split('|').all(|cell| {
+            let cell = cell.trim();
+            let cell = cell.strip_prefix(':').unwrap_or(cell);
+            let cell = cell.strip_suffix(':').unwrap_or(cell);
+            !cell.is_empty() && cell.chars().all(|c| c == '-')
+        })
+}
+
+/// Returns the character columns of the gaps between the cells of `line`: runs of two or
+/// more spaces, or any run containing a tab, between two non-blank characters.
+fn column_gaps(line: &str) -> Vec<Range<usize>> {
+    let characters: Vec<char> = line.trim_end().chars().collect();
+    let first = characters
+        .iter()
+        .position(|c| !c.is_whitespace())
+        .unwrap_or(characters.len());
+    let mut gaps = Vec::new();
+    let mut column = first;
+    while column < characters.len() {
+        if !characters[column].is_whitespace() {
+            column += 1;
+            continue;
+        }
+        let start = column;
+        while characters[column].is_whitespace() {
+            column += 1;
+        }
+        let gap = &characters[start..column];
+        if gap.len() >= 2 || gap.contains(&'\t') {
+            gaps.push(start..column);
+        }
+    }
+    gaps
+}
+
+/// Returns the index of the last line of the table of aligned columns starting at
+/// `lines[first]`, if there is one.
+///
+/// Every row needs the same number of gaps between its cells, and each gap has to line
+/// up with the same gap in every other row, so that left and right aligned columns both
+/// count.
+fn aligned_table_end(lines: &[(usize, &str)], first: usize) -> Option<usize> {
+    let mut shared = column_gaps(lines[first].1);
+    if shared.is_empty() {
+        return None;
+    }
+    let mut last = first;
+    for (index, &(_, line)) in lines.iter().enumerate().skip(first + 1) {
+        let gaps = column_gaps(line);
+        if line.trim().is_empty() || gaps.len() != shared.len() {
+            break;
+        }
+        let overlap: Vec<Range<usize>> = shared
+            .iter()
+            .zip(&gaps)
+            .map(|(shared, gap)| shared.start.max(gap.start)..shared.end.min(gap.end))
+            .collect();
+        if overlap.iter().any(Range::is_empty) {
+            break;
+        }
+        shared = overlap;
+        last = index;
+    }
+    (last + 1 - first >= MIN_ALIGNED_ROWS).then_some(last)
+}
+
+/// Returns the fenced code blocks and tables in `text`, in order.
+///
+/// A fence runs from its opening line to a closing line of the same character that is at
+/// least as long, or to the end of the text when it is never closed. A pipe table is a
+/// header row, a delimiter row, and the rows with a `|` that follow them. A table of
+/// aligned columns is three or more lines whose cells, separated by two or more spaces or
+/// by tabs, line up.
+pub(crate) fn find_blocks(text: &str) -> Vec<Block> {
+    let lines: Vec<(usize, &str)> = lines_with_offsets(text).collect();
+    let line_end = |index: usize| lines[index].0 + lines[index].1.len();
+    let mut blocks = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let (start, line) = lines[index];
+        let (last, kind) = if let Some((character, length)) = fence(line) {
+            let close = (index + 1..lines.len()).find(|&candidate| {
+                let candidate_line = lines[candidate].1;
+                matches!(fence(candidate_line), Some((c, l)) if c == character && l >= length)
+                    && candidate_line.trim().chars().all(|c| c == character)
+            });
+            (close.unwrap_or(lines.len() - 1), BlockKind::Fence)
+        } else if line.contains('|')
+            && lines
+                .get(index + 1)
+                .is_some_and(|(_, next)| is_table_delimiter(next))
+        {
+            let mut last = index + 1;
+            while lines
+                .get(last + 1)
+                .is_some_and(|(_, row)| row.contains('|') && !row.trim().is_empty())
+            {
+                last += 1;
+            }
+            let body_start = if last > index + 1 {
+                lines[index + 2].0
+            } else {
+                line_end(last)
+            };
+            (last, BlockKind::Table { body_start })
+        } else if let Some(last) = aligned_table_end(&lines, index) {
+            let body_start = lines[index + 1].0;
+            (last, BlockKind::Table { body_start })
+        } else {
+            index += 1;
+            continue;
+        };
+        blocks.push(Block {
+            range: start..line_end(last),
+            kind,
+        });
+        index = last + 1;
+    }
+    blocks
+}
+
+/// Splits `text` into sections at its ATX headings of `max_level` or shallower, ignoring
+/// lines that look like headings inside fenced code blocks.
+///
+/// Any text before the first heading is a section without headings. Sections that are
+/// only whitespace are left out.
+pub(crate) fn heading_sections(text: &str, max_level: usize) -> Vec<Section> {
+    let fences: Vec<Range<usize>> = find_blocks(text)
+        .into_iter()
+        .filter(|block| block.kind == BlockKind::Fence)
+        .map(|block| block.range)
+        .collect();
+    let mut sections = Vec::new();
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    let mut push_section = |range: Range<usize>, headings: &[(usize, String)]| {
+        let end = range.start + text[range.clone()].trim_end().len();
+        if end > range.start {
+            sections.push(Section {
+                range: range.start..end,
+                headings: headings.to_vec(),
+            });
+        }
+    };
+    let mut start = 0;
+    for (offset, line) in lines_with_offsets(text) {
+        let Some((level, title)) = atx_heading(line).filter(|&(level, _)| level <= max_level)
+        else {
+            continue;
+        };
+        if fences.iter().any(|fence| fence.contains(&offset)) {
+            continue;
+        }
+        push_section(start..offset, &headings);
+        headings.retain(|&(outer, _)| outer < level);
+        headings.push((level, title.to_string()));
+        start = offset;
+    }
+    push_section(start..text.len(), &headings);
+    sections
+}
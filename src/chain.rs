@@ -0,0 +1,415 @@
+use crate::blocks::heading_sections;
+use crate::coalesce::coalesce;
+#[cfg(feature = "python")]
+use crate::extract_documents;
+use crate::splitters::char_count;
+use crate::{Document, Error, SizeUnit};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use rayon::prelude::*;
+
+/// One step of a `SplitterChain`.
+#[derive(Clone, Debug, PartialEq)]
+enum Step {
+    MarkdownHeaders {
+        max_level: usize,
+    },
+    Recursive {
+        chunk_size: usize,
+    },
+    Markdown {
+        chunk_size: usize,
+    },
+    Sentences {
+        chunk_size: usize,
+        overlap_sentences: usize,
+    },
+    Characters {
+        num_characters: u32,
+        overlap: u32,
+        unit: SizeUnit,
+    },
+    MergeSmall {
+        min_size: usize,
+        max_size: usize,
+    },
+    Filter {
+        min_chunk_size: usize,
+        drop_empty: bool,
+    },
+}
+
+impl Step {
+    /// Returns `Error::InvalidArgument` when the step's arguments are out of range.
+    fn check(&self) -> Result<(), Error> {
+        let message = match *self {
+            Step::MarkdownHeaders { max_level } if !(1..=6).contains(&max_level) => {
+                "max_level must be between 1 and 6"
+            }
+            Step::Characters {
+                num_characters: 0, ..
+            } => "num_characters must be greater than 0",
+            Step::Characters {
+                num_characters,
+                overlap,
+                ..
+            } if overlap >= num_characters => "overlap must be smaller than num_characters",
+            Step::MergeSmall { min_size, max_size } if min_size > max_size => {
+                "min_size must not be larger than max_size"
+            }
+            _ => return Ok(()),
+        };
+        Err(Error::InvalidArgument(message.to_string()))
+    }
+
+    /// Runs the step over every piece, keeping the pieces in order. `source` is the text
+    /// of the document the pieces were split from.
+    fn apply(&self, pieces: Vec<Document>, source: &str) -> Result<Vec<Document>, Error> {
+        let split_each = |split: &dyn Fn(&Document) -> Vec<Document>| {
+            pieces.iter().flat_map(split).collect::<Vec<Document>>()
+        };
+        Ok(match *self {
+            Step::MarkdownHeaders { max_level } => {
+                split_each(&|piece| split_at_headings(piece, max_level))
+            }
+            Step::Recursive { chunk_size } => {
+                split_each(&|piece| piece.recursive_character_splitter(chunk_size, false, "", None))
+            }
+            Step::Markdown { chunk_size } => {
+                split_each(&|piece| piece.markdown_splitter(chunk_size, false, "", None))
+            }
+            Step::Sentences {
+                chunk_size,
+                overlap_sentences,
+            } => split_each(&|piece| {
+                piece.sentence_overlap_splitter(chunk_size, overlap_sentences, false, "", None)
+            }),
+            Step::Characters {
+                num_characters,
+                overlap,
+                unit,
+            } => {
+                let mut chunks = Vec::new();
+                for piece in &pieces {
+                    chunks.extend(piece.split_on_num_characters(
+                        num_characters,
+                        overlap,
+                        unit,
+                        false,
+                        "",
+                    )?);
+                }
+                chunks
+            }
+            Step::MergeSmall { min_size, max_size } => {
+                coalesce(pieces, min_size, max_size, Some(source))
+            }
+            Step::Filter {
+                min_chunk_size,
+                drop_empty,
+            } => {
+                let mut pieces = pieces;
+                Document::filter_chunks(&mut pieces, min_chunk_size, drop_empty, &char_count);
+                pieces
+            }
+        })
+    }
+}
+
+/// Splits `document` into its markdown sections, giving each section `h1` to `h6`
+/// metadata with the titles of the headings it is under.
+fn split_at_headings(document: &Document, max_level: usize) -> Vec<Document> {
+    heading_sections(&document.page_content, max_level)
+        .into_iter()
+        .map(|section| {
+            let mut metadata = document.metadata.clone();
+            for (level, title) in section.headings {
+                metadata.insert(format!("h{}", level), title);
+            }
+            Document {
+                page_content: document.page_content[section.range].to_string(),
+                metadata,
+            }
+        })
+        .collect()
+}
+
+/// Several splitters run one after another over each document, in a single pass.
+///
+/// Every step splits each piece the step before it produced, and the pieces keep the
+/// metadata they were given on the way, such as the headings from `markdown_headers`.
+/// Provenance and context headers are only added at the end, so a chunk's
+/// `chunk_index` and `parent_id` point back at the original document rather than at an
+/// intermediate piece.
+///
+/// ```
+/// use rs_document::{Document, SplitterChain};
+/// use std::collections::HashMap;
+///
+/// let document = Document::new("# Intro\n\nHello.\n\n# Usage\n\nRun it.", HashMap::new());
+/// let chain = SplitterChain::new().markdown_headers(2).recursive(1000);
+/// let chunks = chain.split(&document, true, "id", None).unwrap();
+/// assert_eq!(chunks[1].page_content, "# Usage\n\nRun it.");
+/// assert_eq!(chunks[1].metadata["h1"], "Usage");
+/// assert_eq!(chunks[1].metadata["chunk_index"], "1");
+/// ```
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SplitterChain {
+    steps: Vec<Step>,
+}
+
+impl SplitterChain {
+    /// Returns a chain without any steps, which leaves documents whole.
+    pub fn new() -> Self {
+        SplitterChain::default()
+    }
+
+    /// Splits at markdown headings of `max_level` (1 to 6) or shallower. Each section
+    /// starts at its heading and gets `h1` to `h6` metadata with the titles of the
+    /// headings it is under.
+    pub fn markdown_headers(mut self, max_level: usize) -> Self {
+        self.steps.push(Step::MarkdownHeaders { max_level });
+        self
+    }
+
+    /// Splits with `Document::recursive_character_splitter`.
+    pub fn recursive(mut self, chunk_size: usize) -> Self {
+        self.steps.push(Step::Recursive { chunk_size });
+        self
+    }
+
+    /// Splits with `Document::markdown_splitter`.
+    pub fn markdown(mut self, chunk_size: usize) -> Self {
+        self.steps.push(Step::Markdown { chunk_size });
+        self
+    }
+
+    /// Splits with `Document::sentence_overlap_splitter`.
+    pub fn sentences(mut self, chunk_size: usize, overlap_sentences: usize) -> Self {
+        self.steps.push(Step::Sentences {
+            chunk_size,
+            overlap_sentences,
+        });
+        self
+    }
+
+    /// Splits with `Document::split_on_num_characters`.
+    pub fn characters(mut self, num_characters: u32, overlap: u32, unit: SizeUnit) -> Self {
+        self.steps.push(Step::Characters {
+            num_characters,
+            overlap,
+            unit,
+        });
+        self
+    }
+
+    /// Merges neighbouring pieces while either is shorter than `min_size`, without
+    /// growing past `max_size`, like `coalesce_chunks` from Python. Only pieces with the
+    /// same metadata are merged, so pieces from different sections stay apart. Merged
+    /// pieces are cut from the document's text, so the text that overlapping pieces
+    /// share appears once and the separators between them are the ones it had.
+    pub fn merge_small(mut self, min_size: usize, max_size: usize) -> Self {
+        self.steps.push(Step::MergeSmall { min_size, max_size });
+        self
+    }
+
+    /// Leaves out pieces shorter than `min_chunk_size` and, when `drop_empty` is true,
+    /// pieces that are only whitespace. See `Document::filter_chunks`.
+    pub fn filter(mut self, min_chunk_size: usize, drop_empty: bool) -> Self {
+        self.steps.push(Step::Filter {
+            min_chunk_size,
+            drop_empty,
+        });
+        self
+    }
+
+    /// Returns `Error::InvalidArgument`, naming the step, when the arguments of a step
+    /// are out of range.
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        for (index, step) in self.steps.iter().enumerate() {
+            step.check().map_err(|Error::InvalidArgument(message)| {
+                Error::InvalidArgument(format!("splitter step {}: {}", index + 1, message))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Runs every step over `document`.
+    ///
+    /// When `provenance` is true, every chunk gets `chunk_index`, `total_chunks` and
+    /// `parent_id` metadata, and `context_template` is rendered into a header on every
+    /// chunk, as with the splitters on `Document`.
+    ///
+    /// Returns `Error::InvalidArgument` when the arguments of a step are out of range.
+    #[tracing::instrument(level = "trace", skip_all, fields(bytes = document.page_content.len()))]
+    pub fn split(
+        &self,
+        document: &Document,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+    ) -> Result<Vec<Document>, Error> {
+        self.check()?;
+        let mut chunks = vec![document.clone()];
+        for step in &self.steps {
+            chunks = step.apply(chunks, &document.page_content)?;
+        }
+        document.finish_chunks(&mut chunks, provenance, id_key, context_template);
+        Ok(chunks)
+    }
+}
+
+#[cfg(feature = "python")]
+impl SplitterChain {
+    /// Adds `step` after checking its arguments, for the Python builder methods.
+    fn push_step(&mut self, step: Step) -> PyResult<()> {
+        step.check()?;
+        self.steps.push(step);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SplitterChain {
+    #[new]
+    fn py_new() -> Self {
+        SplitterChain::new()
+    }
+
+    /// Splits at markdown headings of `max_level` or shallower, adding `h1` to `h6`
+    /// metadata with the titles of the headings each section is under.
+    #[pyo3(name = "markdown_headers", signature = (max_level = 6))]
+    fn py_markdown_headers(
+        mut slf: PyRefMut<'_, Self>,
+        max_level: usize,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.push_step(Step::MarkdownHeaders { max_level })?;
+        Ok(slf)
+    }
+
+    /// Splits with `Document.recursive_character_splitter`.
+    #[pyo3(name = "recursive")]
+    fn py_recursive(
+        mut slf: PyRefMut<'_, Self>,
+        chunk_size: usize,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.push_step(Step::Recursive { chunk_size })?;
+        Ok(slf)
+    }
+
+    /// Splits with `Document.markdown_splitter`.
+    #[pyo3(name = "markdown")]
+    fn py_markdown(mut slf: PyRefMut<'_, Self>, chunk_size: usize) -> PyResult<PyRefMut<'_, Self>> {
+        slf.push_step(Step::Markdown { chunk_size })?;
+        Ok(slf)
+    }
+
+    /// Splits with `Document.recursive_character_splitter` and `overlap_sentences`.
+    #[pyo3(name = "sentences", signature = (chunk_size, overlap_sentences = 1))]
+    fn py_sentences(
+        mut slf: PyRefMut<'_, Self>,
+        chunk_size: usize,
+        overlap_sentences: usize,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.push_step(Step::Sentences {
+            chunk_size,
+            overlap_sentences,
+        })?;
+        Ok(slf)
+    }
+
+    /// Splits with `Document.split_on_num_characters`.
+    #[pyo3(name = "characters", signature = (num_characters, overlap = 0, unit = "graphemes"))]
+    fn py_characters<'a>(
+        mut slf: PyRefMut<'a, Self>,
+        num_characters: u32,
+        overlap: u32,
+        unit: &str,
+    ) -> PyResult<PyRefMut<'a, Self>> {
+        slf.push_step(Step::Characters {
+            num_characters,
+            overlap,
+            unit: unit.parse()?,
+        })?;
+        Ok(slf)
+    }
+
+    /// Merges neighbouring pieces from the same section while either is shorter than
+    /// `min_size`, without growing past `max_size`.
+    #[pyo3(name = "merge_small")]
+    fn py_merge_small(
+        mut slf: PyRefMut<'_, Self>,
+        min_size: usize,
+        max_size: usize,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.push_step(Step::MergeSmall { min_size, max_size })?;
+        Ok(slf)
+    }
+
+    /// Leaves out pieces shorter than `min_chunk_size` and, when `drop_empty` is true,
+    /// pieces that are only whitespace.
+    #[pyo3(name = "filter", signature = (min_chunk_size = 0, drop_empty = true))]
+    fn py_filter(
+        mut slf: PyRefMut<'_, Self>,
+        min_chunk_size: usize,
+        drop_empty: bool,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.push_step(Step::Filter {
+            min_chunk_size,
+            drop_empty,
+        })?;
+        Ok(slf)
+    }
+
+    /// Runs every step over `document`, adding provenance and context headers at the end.
+    #[pyo3(
+        name = "split",
+        signature = (document, provenance = false, id_key = "id", context_template = None)
+    )]
+    fn py_split(
+        &self,
+        py: Python,
+        document: Document,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+    ) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.split(&document, provenance, id_key, context_template))?)
+    }
+
+    /// Runs every step over each document in parallel, returning the chunks of all of
+    /// them in order.
+    #[pyo3(
+        name = "split_docs",
+        signature = (docs, provenance = false, id_key = "id", context_template = None)
+    )]
+    fn py_split_docs(
+        &self,
+        py: Python,
+        docs: &PyAny,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+    ) -> PyResult<Vec<Document>> {
+        let doc_vec = extract_documents(docs)?;
+        let span = tracing::info_span!(
+            "split_docs",
+            docs = doc_vec.len(),
+            chunks = tracing::field::Empty
+        )
+        .entered();
+        let chunks = py
+            .allow_threads(|| {
+                doc_vec
+                    .par_iter()
+                    .map(|document| self.split(document, provenance, id_key, context_template))
+                    .collect::<Result<Vec<_>, Error>>()
+            })?
+            .concat();
+        span.record("chunks", chunks.len());
+        Ok(chunks)
+    }
+}
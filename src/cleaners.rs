@@ -0,0 +1,394 @@
+use crate::Document;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Characters that mark the start of a bulleted item.
+const UNICODE_BULLETS: &[&str] = &[
+    "\u{0095}", "\u{2022}", "\u{2023}", "\u{2043}", "\u{3164}", "\u{204C}", "\u{204D}", "\u{2219}",
+    "\u{25CB}", "\u{25CF}", "\u{25D8}", "\u{25E6}", "\u{2619}", "\u{2765}", "\u{2767}", "\u{29BE}",
+    "\u{29BF}", "\u{002D}", "\u{F0B7}", "*", "·",
+];
+
+/// Ligatures and the letters they are replaced with.
+const LIGATURES: &[(char, &str)] = &[
+    ('æ', "ae"),
+    ('Æ', "AE"),
+    ('ﬀ', "ff"),
+    ('ﬁ', "fi"),
+    ('ﬂ', "fl"),
+    ('ﬃ', "ffi"),
+    ('ﬄ', "ffl"),
+    ('ﬅ', "ft"),
+    ('ʪ', "ls"),
+    ('œ', "oe"),
+    ('Œ', "OE"),
+    ('ȹ', "qp"),
+    ('ﬆ', "st"),
+    ('ʦ', "ts"),
+];
+
+/// An alternation of every bullet in `UNICODE_BULLETS`, escaped for use in a regex.
+static BULLETS_PATTERN: Lazy<String> = Lazy::new(|| {
+    UNICODE_BULLETS
+        .iter()
+        .map(|bullet| regex::escape(bullet))
+        .collect::<Vec<String>>()
+        .join("|")
+});
+
+/// A bullet at the start of the text, after any leading whitespace.
+static UNICODE_BULLETS_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(&format!(r"^\s*(?:{})", *BULLETS_PATTERN)).unwrap());
+
+/// A run of one or more consecutive bullets.
+static BULLET_RUN_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(&format!(r"(?:{})+", *BULLETS_PATTERN)).unwrap());
+
+/// An `e` at the start of a paragraph that OCR produced from a bullet.
+///
+/// It isn't matched at the start of other lines, where it is more likely to be a word
+/// or a letter that wrapped onto a new line.
+static E_BULLET_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^e(\s)").unwrap());
+
+/// A line break along with the whitespace around it.
+static PARAGRAPH_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s*\n\s*").unwrap());
+
+/// Two line breaks in a row, which separate paragraphs.
+static DOUBLE_PARAGRAPH_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:\s*\n\s*){2}").unwrap());
+
+/// Finds the runs of whitespace that `clean_extra_whitespace` collapses into a single
+/// space: runs of spaces, newlines and non-breaking spaces (0xC2 0xA0 in UTF-8) that
+/// contain a newline, a non-breaking space, or two spaces in a row.
+///
+/// Lone spaces between words, by far the most common whitespace, are left alone, and
+/// the text between runs is skipped over with `memchr`.
+struct WhitespaceEdits<'a> {
+    text: &'a [u8],
+    position: usize,
+    /// The next newline or non-breaking space at or after `position`, if already found.
+    next_break: Option<usize>,
+    /// The next pair of spaces at or after `position`, if already found.
+    next_double_space: Option<usize>,
+}
+
+impl<'a> WhitespaceEdits<'a> {
+    fn new(text: &'a [u8]) -> Self {
+        WhitespaceEdits {
+            text,
+            position: 0,
+            next_break: None,
+            next_double_space: None,
+        }
+    }
+
+    /// The length of the whitespace character at `index`, or 0 if there isn't one.
+    fn whitespace_len(&self, index: usize) -> usize {
+        match self.text.get(index) {
+            Some(b' ' | b'\n') => 1,
+            Some(0xc2) if self.text.get(index + 1) == Some(&0xa0) => 2,
+            _ => 0,
+        }
+    }
+
+    fn find_break(&self) -> usize {
+        let mut from = self.position;
+        while let Some(offset) = memchr::memchr2(b'\n', 0xc2, &self.text[from..]) {
+            if self.whitespace_len(from + offset) > 0 {
+                return from + offset;
+            }
+            from += offset + 1;
+        }
+        self.text.len()
+    }
+
+    fn find_double_space(&self) -> usize {
+        memchr::memmem::find(&self.text[self.position..], b"  ")
+            .map_or(self.text.len(), |offset| self.position + offset)
+    }
+}
+
+impl Iterator for WhitespaceEdits<'_> {
+    type Item = std::ops::Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next_break = match self.next_break {
+            Some(index) if index >= self.position => index,
+            _ => self.find_break(),
+        };
+        let next_double_space = match self.next_double_space {
+            Some(index) if index >= self.position => index,
+            _ => self.find_double_space(),
+        };
+        self.next_break = Some(next_break);
+        self.next_double_space = Some(next_double_space);
+
+        let mut start = next_break.min(next_double_space);
+        if start == self.text.len() {
+            return None;
+        }
+        // Spaces right before a newline belong to the same run.
+        while start > self.position && self.text[start - 1] == b' ' {
+            start -= 1;
+        }
+        let mut end = start;
+        loop {
+            match self.whitespace_len(end) {
+                0 => break,
+                len => end += len,
+            }
+        }
+        self.position = end;
+        Some(start..end)
+    }
+}
+
+impl Document {
+    /// Remove Non Ascii characters from document's page_content.
+    ///
+    ///For Example:
+    ///
+    /// \x88This text contains non-ascii characters!\x88
+    ///     -> This text contains non-ascii characters!
+    /// """
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn clean_non_ascii_chars(&mut self) {
+        if self.page_content.is_ascii() {
+            return;
+        }
+        // Every byte of a non-ASCII character is outside the ASCII range, so dropping
+        // those bytes drops exactly the non-ASCII characters, in place.
+        let mut bytes = std::mem::take(&mut self.page_content).into_bytes();
+        bytes.retain(u8::is_ascii);
+        self.page_content = String::from_utf8(bytes).expect("ASCII bytes are valid UTF-8");
+    }
+    pub(crate) fn _unicode_bullets_pattern() -> String {
+        BULLETS_PATTERN.clone()
+    }
+
+    /// Remove bullets from page_content using a regular expression pattern.
+    ///
+    /// For Example:
+    ///
+    /// ●  This is an excellent point! -> This is an excellent point!
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn clean_bullets(&mut self) {
+        if UNICODE_BULLETS_RE.is_match(&self.page_content) {
+            let cleaned_text = UNICODE_BULLETS_RE.replace(&self.page_content, "");
+            self.page_content = cleaned_text.trim().to_string();
+        }
+    }
+
+    /// Replace common ligatures like æ in page_content.
+    ///
+    /// For Example:
+    ///
+    /// The beneﬁts -> The benefits
+    /// High quality ﬁnancial -> High quality financial
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn clean_ligatures(&mut self) {
+        if self.page_content.is_ascii() {
+            return;
+        }
+        let mut cleaned_text = String::with_capacity(self.page_content.len());
+        for character in self.page_content.chars() {
+            let ligature = if character.is_ascii() {
+                None
+            } else {
+                LIGATURES
+                    .iter()
+                    .find(|(ligature, _)| *ligature == character)
+            };
+            match ligature {
+                Some((_, replacement)) => cleaned_text.push_str(replacement),
+                None => cleaned_text.push(character),
+            }
+        }
+        self.page_content = cleaned_text;
+    }
+
+    /// Remove extraneous whitespace from page_content
+    ///
+    /// For Example:
+    ///
+    /// ITEM 1.     BUSINESS -> ITEM 1. BUSINESS
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn clean_extra_whitespace(&mut self) {
+        let text = self.page_content.as_bytes();
+        let mut edits = WhitespaceEdits::new(text);
+        let mut cleaned = match edits.next() {
+            Some(first) => {
+                let mut cleaned = Vec::with_capacity(text.len());
+                let mut copied = 0;
+                let mut edit = Some(first);
+                while let Some(range) = edit {
+                    cleaned.extend_from_slice(&text[copied..range.start]);
+                    if cleaned.last() != Some(&b' ') {
+                        cleaned.push(b' ');
+                    }
+                    copied = range.end;
+                    edit = edits.next();
+                }
+                cleaned.extend_from_slice(&text[copied..]);
+                String::from_utf8(cleaned).expect("only whole characters were replaced")
+            }
+            None => std::mem::take(&mut self.page_content),
+        };
+        cleaned.truncate(cleaned.trim_end().len());
+        let leading = cleaned.len() - cleaned.trim_start().len();
+        cleaned.drain(..leading);
+        self.page_content = cleaned;
+    }
+
+    /// Groups paragraphs that have bullets and line breaks
+    ///
+    /// For example:
+    ///
+    /// '''○ The big red fox
+    /// is walking down the lane.
+    ///
+    /// ○ At the end of the lane
+    /// the fox met a friendly bear.'''
+    ///
+    /// Gets converted to
+    ///
+    /// '''○ The big red fox is walking down the lane.
+    /// ○ At the end of the land the fox met a bear.'''
+    pub(crate) fn _group_bullet_paragraph(paragraph: &str) -> Vec<String> {
+        let cleaned_paragraph = E_BULLET_RE.replace(paragraph.trim(), "·$1");
+        let cleaned_paragraph = cleaned_paragraph.trim();
+
+        // Split right before every run of bullets, keeping the bullets with the text
+        // that follows them.
+        let mut starts: Vec<usize> = BULLET_RUN_RE
+            .find_iter(cleaned_paragraph)
+            .map(|bullets| bullets.start())
+            .collect();
+        starts.push(cleaned_paragraph.len());
+        let mut clean_paragraphs = Vec::new();
+        let mut previous = 0;
+        for start in starts {
+            let bullet = cleaned_paragraph[previous..start].trim();
+            if !bullet.is_empty() {
+                clean_paragraphs.push(PARAGRAPH_RE.replace_all(bullet, " ").to_string());
+            }
+            previous = start;
+        }
+
+        clean_paragraphs
+    }
+
+    /// Groups paragraphs in page_content that have line breaks.
+    ///
+    /// For example:
+    ///
+    /// '''The big red fox
+    /// is walking down the lane.
+    ///
+    /// At the end of the lane
+    /// the fox met a bear.'''
+    ///
+    /// Gets converted to
+    ///
+    /// '''The big red fox is walking down the lane.
+    /// At the end of the land the fox met a bear.'''
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn group_broken_paragraphs(&mut self) {
+        let text = &self.page_content;
+        let paragraphs: Vec<&str> = DOUBLE_PARAGRAPH_RE.split(text).collect();
+        let mut clean_paragraphs = Vec::new();
+
+        for paragraph in paragraphs {
+            if !paragraph.trim().is_empty() {
+                let para_split: Vec<&str> = paragraph.split("\n").collect();
+                let all_lines_short = para_split
+                    .iter()
+                    .all(|line| line.split_whitespace().count() < 5);
+                let trimmed = paragraph.trim();
+                if UNICODE_BULLETS_RE.is_match(trimmed) || E_BULLET_RE.is_match(trimmed) {
+                    clean_paragraphs.extend(Document::_group_bullet_paragraph(paragraph));
+                } else if all_lines_short {
+                    clean_paragraphs.extend(
+                        para_split
+                            .iter()
+                            .filter(|line| !line.trim().is_empty())
+                            .map(|line| line.to_string()),
+                    );
+                } else {
+                    clean_paragraphs.push(PARAGRAPH_RE.replace_all(paragraph, " ").to_string());
+                }
+            }
+        }
+
+        self.page_content = clean_paragraphs.join("\n\n");
+    }
+
+    /// Concatenates page_content that has one-line paragraph break pattern.
+    ///
+    /// For example,
+    ///
+    /// Iwan Roberts
+    /// Roberts celebrating after scoring a goal for Norwich City
+    /// in 2004
+    ///
+    /// Will be returned as:
+    ///
+    /// Iwan Roberts\n\nRoberts celebrating after scoring a goal for Norwich City\n\nin 2004
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn new_line_grouper(&mut self) {
+        let text = &self.page_content;
+        let paragraphs: Vec<&str> = text.split("\n").collect();
+        let clean_paragraphs: Vec<&str> = paragraphs
+            .iter()
+            .filter(|paragraph| !paragraph.trim().is_empty())
+            .cloned()
+            .collect();
+        self.page_content = clean_paragraphs.join("\n\n")
+    }
+
+    /// Checks the ratio of new line (\n) over the total max_line_count
+    ///
+    /// If the ratio of new line is less than the threshold,
+    /// the document is considered a new-line grouping type
+    /// and return the original text
+    ///
+    /// If the ratio of new line is greater than or equal to the threshold,
+    /// the document is considered a blank-line grouping type
+    /// and passed on to group_broken_paragraphs function
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn auto_paragraph_grouper(&mut self) {
+        let text = &self.page_content;
+        let max_line_count: usize = 2000;
+        let threshold: f64 = 0.1;
+        let lines: Vec<&str> = text.split("\n").collect();
+        let max_line_count = std::cmp::min(lines.len(), max_line_count);
+        let (mut line_count, mut empty_line_count) = (0, 0);
+
+        for line in &lines[..max_line_count] {
+            line_count += 1;
+            if line.trim().is_empty() {
+                empty_line_count += 1;
+            }
+        }
+
+        let ratio = empty_line_count as f64 / line_count as f64;
+
+        if ratio < threshold {
+            self.new_line_grouper()
+        } else {
+            self.group_broken_paragraphs()
+        }
+    }
+
+    /// A helper function that calls all of the cleaning functions at once
+    ///
+    /// `reflow_columns` isn't one of them, since aligned text that isn't in columns,
+    /// like a list of options and what they do, looks the same to it. Call it first.
+    #[tracing::instrument(level = "trace", skip_all, fields(bytes = self.page_content.len()))]
+    pub fn clean(&mut self) {
+        self.clean_extra_whitespace();
+        self.clean_ligatures();
+        self.clean_bullets();
+        self.clean_non_ascii_chars();
+        self.auto_paragraph_grouper();
+    }
+}
@@ -0,0 +1,268 @@
+use crate::html::parse_html;
+use crate::{Document, FootnoteMode, SizeUnit};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Clean and split documents into JSON Lines chunks.
+#[derive(Parser)]
+#[command(name = "rs-document", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Clean documents and write them out whole.
+    Clean(CommonArgs),
+    /// Clean documents and split them into chunks.
+    Split {
+        #[command(flatten)]
+        common: CommonArgs,
+        /// The maximum number of characters in a chunk, or of graphemes with
+        /// `--splitter characters`.
+        #[arg(long, default_value_t = 1000)]
+        chunk_size: usize,
+        /// The splitter used to chunk each document.
+        #[arg(long, value_enum, default_value_t = Splitter::Recursive)]
+        splitter: Splitter,
+        /// Add `chunk_index`, `total_chunks` and `parent_id` metadata to every chunk.
+        #[arg(long)]
+        provenance: bool,
+    },
+}
+
+#[derive(Args)]
+struct CommonArgs {
+    /// Files or directories to read. Directories are read recursively, and `-` or no
+    /// inputs at all reads from stdin.
+    inputs: Vec<PathBuf>,
+    /// Where to write the JSON Lines output. Defaults to stdout, and paths ending in
+    /// `.gz` are gzip compressed.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// The cleaners to apply, comma separated.
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "all")]
+    clean: Vec<Cleaner>,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Cleaner {
+    /// Every cleaner, like `Document.clean()`.
+    All,
+    /// No cleaning.
+    None,
+    ExtraWhitespace,
+    Ligatures,
+    Bullets,
+    NonAscii,
+    BrokenParagraphs,
+    /// Footnote markers and footnotes, which are stripped.
+    Footnotes,
+    /// Text in two or three columns, which is reflowed into reading order.
+    Columns,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Splitter {
+    /// `recursive_character_splitter`.
+    Recursive,
+    /// `split_on_num_characters`.
+    Characters,
+    /// `markdown_splitter`.
+    Markdown,
+}
+
+/// Writes every line to `writer`, terminating each with a newline.
+fn write_lines<W: Write>(writer: &mut W, lines: &[String]) -> io::Result<()> {
+    for line in lines {
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Applies the selected cleaners to a document, in the order they were given.
+fn apply_cleaners(document: &mut Document, cleaners: &[Cleaner]) {
+    for cleaner in cleaners {
+        match cleaner {
+            Cleaner::All => document.clean(),
+            Cleaner::None => {}
+            Cleaner::ExtraWhitespace => document.clean_extra_whitespace(),
+            Cleaner::Ligatures => document.clean_ligatures(),
+            Cleaner::Bullets => document.clean_bullets(),
+            Cleaner::NonAscii => document.clean_non_ascii_chars(),
+            Cleaner::BrokenParagraphs => document.auto_paragraph_grouper(),
+            Cleaner::Footnotes => document.clean_footnotes(FootnoteMode::Strip),
+            Cleaner::Columns => document.reflow_columns(),
+        }
+    }
+}
+
+/// Collects the files to read, walking directories recursively in sorted order.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    if path.is_dir() {
+        let mut entries: Vec<PathBuf> = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<_>>()?;
+        entries.sort();
+        for entry in entries {
+            collect_files(&entry, files)?;
+        }
+    } else {
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Reads a file into a Document, extracting the text of HTML files.
+fn read_file(path: &Path) -> Result<Document, String> {
+    let bytes = fs::read(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+    let text = String::from_utf8_lossy(&bytes);
+    let is_html = path
+        .extension()
+        .is_some_and(|ext| ext == "html" || ext == "htm");
+    let mut document = if is_html {
+        parse_html(&text)
+    } else {
+        Document {
+            page_content: text.into_owned(),
+            metadata: HashMap::new(),
+        }
+    };
+    document
+        .metadata
+        .insert("source".to_string(), path.display().to_string());
+    Ok(document)
+}
+
+/// Reads every input into Documents, with stdin becoming a document with source `-`.
+fn read_inputs(inputs: &[PathBuf]) -> Result<Vec<Document>, String> {
+    let stdin = Path::new("-");
+    let mut files = Vec::new();
+    for input in inputs {
+        if input != stdin {
+            collect_files(input, &mut files)
+                .map_err(|error| format!("{}: {}", input.display(), error))?;
+        }
+    }
+    let mut documents: Vec<Document> = files
+        .par_iter()
+        .map(|path| read_file(path))
+        .collect::<Result<_, _>>()?;
+    if inputs.is_empty() || inputs.iter().any(|input| input == stdin) {
+        let mut text = String::new();
+        io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|error| format!("stdin: {}", error))?;
+        documents.push(Document {
+            page_content: text,
+            metadata: HashMap::from([("source".to_string(), "-".to_string())]),
+        });
+    }
+    Ok(documents)
+}
+
+/// Writes documents as JSON Lines to `output`, or to stdout when there is no output path.
+fn write_output(documents: &[Document], output: Option<&Path>) -> Result<(), String> {
+    let lines: Vec<String> = documents
+        .par_iter()
+        .map(serde_json::to_string)
+        .collect::<Result<_, _>>()
+        .map_err(|error| error.to_string())?;
+    let result = match output {
+        Some(path) => File::create(path).and_then(|file| {
+            let writer = BufWriter::new(file);
+            if path.extension().is_some_and(|ext| ext == "gz") {
+                let mut encoder = GzEncoder::new(writer, Compression::default());
+                write_lines(&mut encoder, &lines)?;
+                encoder.finish()?.flush()
+            } else {
+                let mut writer = writer;
+                write_lines(&mut writer, &lines)?;
+                writer.flush()
+            }
+        }),
+        None => {
+            let mut writer = BufWriter::new(io::stdout().lock());
+            write_lines(&mut writer, &lines).and_then(|_| writer.flush())
+        }
+    };
+    result.map_err(|error| match output {
+        Some(path) => format!("{}: {}", path.display(), error),
+        None => format!("stdout: {}", error),
+    })
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+    match cli.command {
+        Command::Clean(common) => {
+            let mut documents = read_inputs(&common.inputs)?;
+            documents
+                .par_iter_mut()
+                .for_each(|document| apply_cleaners(document, &common.clean));
+            write_output(&documents, common.output.as_deref())
+        }
+        Command::Split {
+            common,
+            chunk_size,
+            splitter,
+            provenance,
+        } => {
+            if chunk_size == 0 {
+                return Err("--chunk-size must be greater than 0".to_string());
+            }
+            let documents = read_inputs(&common.inputs)?;
+            let chunks: Vec<Vec<Document>> = documents
+                .into_par_iter()
+                .map(|mut document| {
+                    apply_cleaners(&mut document, &common.clean);
+                    Ok(match splitter {
+                        Splitter::Recursive => document
+                            .recursive_character_splitter(chunk_size, provenance, "id", None),
+                        Splitter::Characters => {
+                            let num_characters = u32::try_from(chunk_size).map_err(|_| {
+                                format!(
+                                    "--chunk-size must be at most {} with the characters splitter",
+                                    u32::MAX
+                                )
+                            })?;
+                            document
+                                .split_on_num_characters(
+                                    num_characters,
+                                    0,
+                                    SizeUnit::Graphemes,
+                                    provenance,
+                                    "id",
+                                )
+                                .map_err(|error| error.to_string())?
+                        }
+                        Splitter::Markdown => {
+                            document.markdown_splitter(chunk_size, provenance, "id", None)
+                        }
+                    })
+                })
+                .collect::<Result<_, String>>()?;
+            let chunks: Vec<Document> = chunks.into_iter().flatten().collect();
+            write_output(&chunks, common.output.as_deref())
+        }
+    }
+}
+
+/// Runs the `rs-document` command line tool.
+pub fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("rs-document: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
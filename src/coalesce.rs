@@ -0,0 +1,243 @@
+#[cfg(feature = "python")]
+use crate::extract_documents;
+use crate::splitters::char_count;
+use crate::Document;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// The metadata `add_context_header` records the range of the text after the header in.
+const HEADER_RANGE_KEYS: [&str; 2] = ["content_start", "content_end"];
+
+/// Returns true when two adjacent chunks were split from the same document.
+///
+/// Chunks with provenance metadata are compared by `parent_id`. Chunks without it
+/// are only considered siblings when their metadata is identical, apart from the
+/// range of the text after their context headers.
+fn same_parent(a: &Document, b: &Document) -> bool {
+    match (a.metadata.get("parent_id"), b.metadata.get("parent_id")) {
+        (Some(a_parent), Some(b_parent)) => a_parent == b_parent,
+        (None, None) => {
+            let own = |key: &&String| !HEADER_RANGE_KEYS.contains(&key.as_str());
+            let keys = |chunk: &Document| chunk.metadata.keys().filter(own).count();
+            keys(a) == keys(b)
+                && a.metadata
+                    .iter()
+                    .filter(|(key, _)| own(key))
+                    .all(|(key, value)| b.metadata.get(key) == Some(value))
+        }
+        _ => false,
+    }
+}
+
+/// The byte range of the split text within a chunk's page_content: the part after the
+/// context header, if `add_context_header` added one, and otherwise all of it.
+fn body_range(chunk: &Document) -> Range<usize> {
+    let text = &chunk.page_content;
+    let offset = |key: &str| chunk.metadata.get(key)?.parse::<usize>().ok();
+    let byte = |chars: usize| {
+        text.char_indices()
+            .nth(chars)
+            .map_or(text.len(), |(i, _)| i)
+    };
+    match (offset("content_start"), offset("content_end")) {
+        (Some(start), Some(end)) if start <= end => byte(start)..byte(end),
+        _ => 0..text.len(),
+    }
+}
+
+/// Finds the byte range of every chunk's text in `source`, the text they were split
+/// from in order, or `None` for the chunks that aren't in it.
+///
+/// Each chunk is searched for after the start of the one before it, since chunks can
+/// overlap but don't go backwards.
+fn locate(source: &str, chunks: &[Document]) -> Vec<Option<Range<usize>>> {
+    let mut cursor = 0;
+    chunks
+        .iter()
+        .map(|chunk| {
+            let body = &chunk.page_content[body_range(chunk)];
+            let start = cursor + source[cursor..].find(body)?;
+            cursor = start + body.chars().next().map_or(0, char::len_utf8);
+            Some(start..start + body.len())
+        })
+        .collect()
+}
+
+/// Returns the length of the longest run of whole words that `a` ends with and `b`
+/// starts with, which is where two overlapping chunks repeat each other.
+fn shared_overlap(a: &str, b: &str) -> usize {
+    let starts = a
+        .char_indices()
+        .filter(|&(start, _)| start == 0 || a[..start].ends_with(char::is_whitespace));
+    for (start, _) in starts {
+        let len = a.len() - start;
+        let ends_at_word = b.len() == len
+            || b.get(len..)
+                .is_some_and(|rest| rest.starts_with(char::is_whitespace));
+        if ends_at_word && b.starts_with(&a[start..]) {
+            return len;
+        }
+    }
+    0
+}
+
+/// Joins the text of `next` onto `current`, unless the result would be longer than
+/// `max_size`. Returns whether it did.
+///
+/// Chunks are merged by their byte ranges in `source` when both were found in it, so
+/// that the text between them is the text that separated them there. Otherwise the
+/// part of `next` that repeats the end of `current` is left out, and what is left is
+/// joined with a space unless either side already has whitespace there. A context
+/// header at the start of `next` is dropped, and the one of `current` is kept.
+fn merge(
+    current: &mut (Document, Option<Range<usize>>),
+    next: &(Document, Option<Range<usize>>),
+    source: Option<&str>,
+    max_size: usize,
+) -> bool {
+    let current_body = body_range(&current.0);
+    let next_body = &next.0.page_content[body_range(&next.0)];
+    let (body, range) = match (source, &current.1, &next.1) {
+        (Some(source), Some(a), Some(b))
+            if source[a.end.min(b.start)..b.start].trim().is_empty() =>
+        {
+            let range = a.start..a.end.max(b.end);
+            (source[range.clone()].to_string(), Some(range))
+        }
+        _ => {
+            let body = &current.0.page_content[current_body.clone()];
+            let overlap = shared_overlap(body, next_body);
+            let rest = &next_body[overlap..];
+            let needs_space = overlap == 0
+                && !body.is_empty()
+                && !rest.is_empty()
+                && !body.ends_with(char::is_whitespace)
+                && !rest.starts_with(char::is_whitespace);
+            let joiner = if needs_space { " " } else { "" };
+            (format!("{}{}{}", body, joiner, rest), None)
+        }
+    };
+    let header = &current.0.page_content[..current_body.start];
+    let page_content = format!(
+        "{}{}{}",
+        header,
+        body,
+        &current.0.page_content[current_body.end..]
+    );
+    if char_count(&page_content) > max_size {
+        return false;
+    }
+    if current.0.metadata.contains_key("content_end") {
+        let content_end = header.chars().count() + body.chars().count();
+        current
+            .0
+            .metadata
+            .insert("content_end".to_string(), content_end.to_string());
+    }
+    current.0.page_content = page_content;
+    current.1 = range;
+    true
+}
+
+/// Merges adjacent sibling chunks while either of them is smaller than `min_size`,
+/// as long as the merged chunk does not grow past `max_size`.
+///
+/// `source` is the text the chunks were split from, when it is known, so that merged
+/// chunks are cut from it. Merged chunks keep the metadata of the first chunk. If the
+/// chunks carry provenance metadata, `chunk_index` and `total_chunks` are renumbered
+/// afterwards.
+pub(crate) fn coalesce(
+    chunks: Vec<Document>,
+    min_size: usize,
+    max_size: usize,
+    source: Option<&str>,
+) -> Vec<Document> {
+    let ranges = match source {
+        Some(source) => locate(source, &chunks),
+        None => vec![None; chunks.len()],
+    };
+    let mut result: Vec<(Document, Option<Range<usize>>)> = Vec::new();
+    for next in chunks.into_iter().zip(ranges) {
+        if let Some(current) = result.last_mut() {
+            let is_small = char_count(&current.0.page_content) < min_size
+                || char_count(&next.0.page_content) < min_size;
+            if is_small
+                && same_parent(&current.0, &next.0)
+                && merge(current, &next, source, max_size)
+            {
+                continue;
+            }
+        }
+        result.push(next);
+    }
+    let mut result: Vec<Document> = result.into_iter().map(|(chunk, _)| chunk).collect();
+    renumber_chunks(&mut result);
+    result
+}
+
+/// Recomputes `chunk_index` and `total_chunks` for chunks that carry provenance metadata.
+fn renumber_chunks(chunks: &mut [Document]) {
+    let mut totals: HashMap<String, usize> = HashMap::new();
+    for chunk in chunks.iter() {
+        if let Some(parent_id) = chunk.metadata.get("parent_id") {
+            *totals.entry(parent_id.clone()).or_default() += 1;
+        }
+    }
+
+    let mut indices: HashMap<String, usize> = HashMap::new();
+    for chunk in chunks.iter_mut() {
+        if !chunk.metadata.contains_key("chunk_index") {
+            continue;
+        }
+        if let Some(parent_id) = chunk.metadata.get("parent_id").cloned() {
+            let index = indices.entry(parent_id.clone()).or_default();
+            chunk
+                .metadata
+                .insert("chunk_index".to_string(), index.to_string());
+            chunk
+                .metadata
+                .insert("total_chunks".to_string(), totals[&parent_id].to_string());
+            *index += 1;
+        }
+    }
+}
+
+/// Merges chunks that are too small to be useful into their neighbors.
+///
+/// Splitters can emit tiny fragments, like a lone heading, that carry little meaning on
+/// their own. Adjacent chunks from the same parent document are joined while either of
+/// them is shorter than `min_size`, without letting a merged chunk exceed `max_size`.
+/// The text that overlapping chunks share is only kept once, chunks that don't overlap
+/// are joined with a space, and the context header of every chunk but the first of a
+/// merge is dropped.
+///
+/// # Arguments
+///
+/// * `docs` - The chunks to coalesce, in the order the splitter produced them.
+/// * `min_size` - Chunks shorter than this are merged with a neighbor.
+/// * `max_size` - Merged chunks will never be longer than this.
+///
+/// # Returns
+///
+/// The coalesced chunks, in their original order.
+#[cfg(feature = "python")]
+#[pyfunction]
+pub fn coalesce_chunks(
+    py: Python,
+    docs: &PyAny,
+    min_size: usize,
+    max_size: usize,
+) -> PyResult<Vec<Document>> {
+    if min_size > max_size {
+        return Err(PyValueError::new_err(
+            "min_size must not be larger than max_size",
+        ));
+    }
+    let doc_vec: Vec<Document> = extract_documents(docs)?;
+
+    Ok(py.allow_threads(|| coalesce(doc_vec, min_size, max_size, None)))
+}
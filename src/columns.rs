@@ -0,0 +1,241 @@
+use crate::Document;
+use std::ops::Range;
+
+/// The narrowest gap between two columns, in characters.
+const MIN_GUTTER: usize = 2;
+
+/// How many lines of a block must have text on both sides of a gutter, so that a couple
+/// of lines with wide gaps, like a signature or a table row, aren't taken for columns.
+const MIN_COLUMN_LINES: usize = 3;
+
+/// The shortest average line of a column, in characters. The cells of text tables are
+/// usually shorter than this, and lines of prose are longer.
+const MIN_COLUMN_WIDTH: usize = 15;
+
+/// The most columns a block is reflowed into. More than that is most likely a table.
+const MAX_COLUMNS: usize = 3;
+
+/// Which character positions are whitespace on every line of a block so far.
+///
+/// Positions past the end of a line count as whitespace, so `blank` is as long as the
+/// longest line, and a line that is longer extends it.
+struct Gutters {
+    blank: Vec<bool>,
+    /// The position of the leftmost text on any line.
+    left_margin: usize,
+}
+
+impl Gutters {
+    fn new() -> Self {
+        Gutters {
+            blank: Vec::new(),
+            left_margin: usize::MAX,
+        }
+    }
+
+    /// Adds a line that isn't blank.
+    fn add(&mut self, line: &[char]) {
+        if self.blank.len() < line.len() {
+            self.blank.resize(line.len(), true);
+        }
+        for (blank, character) in self.blank.iter_mut().zip(line) {
+            *blank &= character.is_whitespace();
+        }
+        let margin = line.iter().position(|character| !character.is_whitespace());
+        self.left_margin = self.left_margin.min(margin.unwrap_or(usize::MAX));
+    }
+
+    /// The runs of positions that are whitespace on every line, at least `MIN_GUTTER`
+    /// wide and with text on both sides.
+    fn ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        let mut start = None;
+        for (position, &blank) in self.blank.iter().enumerate() {
+            match (blank, start) {
+                (true, None) => start = Some(position),
+                (false, Some(run_start)) => {
+                    if run_start > self.left_margin && position - run_start >= MIN_GUTTER {
+                        ranges.push(run_start..position);
+                    }
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        ranges
+    }
+}
+
+/// Splits `line` into the text of each column, with the gutters between them removed.
+fn column_texts(line: &[char], gutters: &[Range<usize>]) -> Vec<String> {
+    let mut bounds = vec![0];
+    for gutter in gutters {
+        bounds.push(gutter.start.min(line.len()));
+        bounds.push(gutter.end.min(line.len()));
+    }
+    bounds.push(line.len());
+    bounds
+        .chunks(2)
+        .map(|bounds| {
+            line[bounds[0]..bounds[1]]
+                .iter()
+                .collect::<String>()
+                .trim()
+                .to_string()
+        })
+        .collect()
+}
+
+/// Returns whether the lines of a block split at `gutters` look like columns of prose:
+/// at most `MAX_COLUMNS` columns, with enough lines that have text on both sides of
+/// every gutter, and lines that are long enough on average.
+fn is_column_layout(rows: &[Vec<String>], gutters: &[Range<usize>]) -> bool {
+    if gutters.is_empty() || gutters.len() + 1 > MAX_COLUMNS {
+        return false;
+    }
+    let interleaved = (0..gutters.len()).all(|gutter| {
+        rows.iter()
+            .filter(|row| !row[gutter].is_empty() && !row[gutter + 1].is_empty())
+            .count()
+            >= MIN_COLUMN_LINES
+    });
+    interleaved
+        && (0..=gutters.len()).all(|column| {
+            let lines: Vec<usize> = rows
+                .iter()
+                .map(|row| row[column].chars().count())
+                .filter(|&length| length > 0)
+                .collect();
+            lines.iter().sum::<usize>() >= MIN_COLUMN_WIDTH * lines.len()
+        })
+}
+
+/// Joins each column of `rows` into lines, one column after the other. A row where a
+/// column has no text is a paragraph break in that column.
+fn reading_order(rows: &[Vec<String>], columns: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for column in 0..columns {
+        let mut paragraph_break = false;
+        let mut column_started = false;
+        for row in rows {
+            let text = &row[column];
+            if text.is_empty() {
+                paragraph_break = column_started;
+                continue;
+            }
+            if paragraph_break {
+                lines.push(String::new());
+                paragraph_break = false;
+            }
+            lines.push(text.clone());
+            column_started = true;
+        }
+    }
+    lines
+}
+
+/// Returns whether `line` has text on both sides of one of `gutters`.
+fn straddles(line: &[char], gutters: &[Range<usize>]) -> bool {
+    let has_text = |characters: &[char]| characters.iter().any(|c| !c.is_whitespace());
+    gutters.iter().any(|gutter| {
+        gutter.end < line.len() && has_text(&line[..gutter.start]) && has_text(&line[gutter.end..])
+    })
+}
+
+/// Finds the block of lines at the start of `lines` that share at least one gutter,
+/// returning how many lines it has and its gutters.
+///
+/// The block ends at the last line with text before a line that would close every
+/// gutter, or before a blank line that isn't followed by a line with text on both sides
+/// of a gutter, like a page footer. A block without gutters is a single line.
+fn column_block(lines: &[Vec<char>]) -> (usize, Vec<Range<usize>>) {
+    let mut gutters = Gutters::new();
+    let mut end = 0;
+    let mut after_blank = false;
+    for (index, line) in lines.iter().enumerate() {
+        if line.is_empty() {
+            after_blank = end > 0;
+            continue;
+        }
+        let blank = gutters.blank.clone();
+        let left_margin = gutters.left_margin;
+        gutters.add(line);
+        let ranges = gutters.ranges();
+        if ranges.is_empty() || (after_blank && !straddles(line, &ranges)) {
+            if index == 0 {
+                return (1, Vec::new());
+            }
+            gutters.blank = blank;
+            gutters.left_margin = left_margin;
+            break;
+        }
+        end = index + 1;
+        after_blank = false;
+    }
+    (end.max(1), gutters.ranges())
+}
+
+impl Document {
+    /// Reflows text laid out in two or three columns, as extracted from PDFs with the
+    /// columns side by side on every line, into reading order: the whole first column,
+    /// then the second, then the third.
+    ///
+    /// A block of columns is a run of lines with a gutter, a gap of two or more spaces
+    /// at the same character positions on every line, that at least three of the lines
+    /// have text on both sides of. Blocks whose columns are narrow on average are left
+    /// alone, since they are more likely to be tables than prose. Rows where a column
+    /// has no text become paragraph breaks in that column, and text before and after a
+    /// block is kept where it is.
+    ///
+    /// For example:
+    ///
+    /// ```text
+    /// The fox ran down the lane     and met a bear at the
+    /// at the end of a long day,     end of it. They walked
+    /// without stopping at all,      home together.
+    /// ```
+    ///
+    /// Gets converted to
+    ///
+    /// ```text
+    /// The fox ran down the lane
+    /// at the end of a long day,
+    /// without stopping at all,
+    /// and met a bear at the
+    /// end of it. They walked
+    /// home together.
+    /// ```
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn reflow_columns(&mut self) {
+        // Columns are always at least two spaces apart.
+        if memchr::memmem::find(self.page_content.as_bytes(), b"  ").is_none() {
+            return;
+        }
+        let raw_lines: Vec<&str> = self.page_content.split('\n').collect();
+        let lines: Vec<Vec<char>> = raw_lines
+            .iter()
+            .map(|line| line.trim_end().chars().collect())
+            .collect();
+        let mut reflowed: Vec<String> = Vec::with_capacity(lines.len());
+        let mut changed = false;
+        let mut start = 0;
+        while start < lines.len() {
+            let (end, gutters) = column_block(&lines[start..]);
+            let end = start + end;
+            let rows: Vec<Vec<String>> = lines[start..end]
+                .iter()
+                .map(|line| column_texts(line, &gutters))
+                .collect();
+            if is_column_layout(&rows, &gutters) {
+                reflowed.extend(reading_order(&rows, gutters.len() + 1));
+                changed = true;
+            } else {
+                reflowed.extend(raw_lines[start..end].iter().map(|line| line.to_string()));
+            }
+            start = end;
+        }
+        if changed {
+            self.page_content = reflowed.join("\n");
+        }
+    }
+}
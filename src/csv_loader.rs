@@ -0,0 +1,154 @@
+use crate::loaders::{check_loader_keys, decode, DecodeErrors};
+use crate::Document;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Converts a CSV error into a Python exception, keeping I/O errors as `OSError`s.
+fn csv_error(error: csv::Error) -> PyErr {
+    let message = error.to_string();
+    match error.into_kind() {
+        csv::ErrorKind::Io(io_error) => io_error.into(),
+        _ => PyValueError::new_err(message),
+    }
+}
+
+/// Finds the position of every column in `columns` within the CSV `headers`.
+fn column_indices(headers: &csv::StringRecord, columns: &[String]) -> PyResult<Vec<usize>> {
+    columns
+        .iter()
+        .map(|column| {
+            headers
+                .iter()
+                .position(|header| header == column)
+                .ok_or_else(|| {
+                    PyValueError::new_err(format!("Column \"{}\" is not in the CSV file", column))
+                })
+        })
+        .collect()
+}
+
+/// Loads a CSV file, turning every row into a Document.
+///
+/// The content columns are rendered into page_content as one `column: value` line per
+/// column, and the metadata columns are copied into metadata along with the `source`
+/// path and the zero based `row` number, so a metadata column can't be named `source` or
+/// `row`. Rows are read sequentially and turned into documents in parallel.
+///
+/// # Arguments
+///
+/// * `path` - The path of the CSV file. The first row must be a header row.
+/// * `content_columns` - The columns rendered into page_content. Defaults to every
+///   column that isn't a metadata column.
+/// * `metadata_columns` - The columns copied into metadata. Defaults to every column
+///   that isn't a content column.
+/// * `delimiter` - The single character separating fields.
+/// * `encoding` - The encoding of the file. Defaults to UTF-8.
+/// * `errors` - What to do when the file isn't valid in `encoding`, as in
+///   `load_text_files`.
+#[pyfunction]
+#[pyo3(signature = (
+    path,
+    content_columns = None,
+    metadata_columns = None,
+    delimiter = ",",
+    encoding = None,
+    errors = "detect",
+))]
+pub fn load_csv(
+    py: Python,
+    path: PathBuf,
+    content_columns: Option<Vec<String>>,
+    metadata_columns: Option<Vec<String>>,
+    delimiter: &str,
+    encoding: Option<&str>,
+    errors: &str,
+) -> PyResult<Vec<Document>> {
+    let errors = errors.parse::<DecodeErrors>()?;
+    let delimiter = match delimiter.as_bytes() {
+        [byte] => *byte,
+        _ => {
+            return Err(PyValueError::new_err(
+                "delimiter must be a single ASCII character",
+            ))
+        }
+    };
+
+    py.allow_threads(|| {
+        let text = decode(&fs::read(&path)?, encoding, errors, &path)?;
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .from_reader(text.as_bytes());
+        let headers = reader.headers().map_err(csv_error)?.clone();
+
+        let all_columns: Vec<String> = headers.iter().map(str::to_string).collect();
+        let (content_columns, metadata_columns) = match (content_columns, metadata_columns) {
+            (Some(content), Some(metadata)) => (content, metadata),
+            (Some(content), None) => {
+                let metadata = all_columns
+                    .iter()
+                    .filter(|column| !content.contains(column))
+                    .cloned()
+                    .collect();
+                (content, metadata)
+            }
+            (None, Some(metadata)) => {
+                let content = all_columns
+                    .iter()
+                    .filter(|column| !metadata.contains(column))
+                    .cloned()
+                    .collect();
+                (content, metadata)
+            }
+            (None, None) => (all_columns, Vec::new()),
+        };
+        check_loader_keys(
+            "load_csv",
+            "Column",
+            metadata_columns.iter().map(String::as_str),
+            "leave it out of metadata_columns or rename it",
+        )?;
+        let content_indices = column_indices(&headers, &content_columns)?;
+        let metadata_indices = column_indices(&headers, &metadata_columns)?;
+
+        let records = reader
+            .records()
+            .collect::<Result<Vec<csv::StringRecord>, csv::Error>>()
+            .map_err(csv_error)?;
+
+        let source = path.display().to_string();
+        Ok(records
+            .par_iter()
+            .enumerate()
+            .map(|(row, record)| {
+                let page_content = content_columns
+                    .iter()
+                    .zip(&content_indices)
+                    .map(|(column, &index)| {
+                        format!("{}: {}", column, record.get(index).unwrap_or_default())
+                    })
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                let mut metadata: HashMap<String, String> = metadata_columns
+                    .iter()
+                    .zip(&metadata_indices)
+                    .map(|(column, &index)| {
+                        (
+                            column.clone(),
+                            record.get(index).unwrap_or_default().to_string(),
+                        )
+                    })
+                    .collect();
+                metadata.insert("source".to_string(), source.clone());
+                metadata.insert("row".to_string(), row.to_string());
+                Document {
+                    page_content,
+                    metadata,
+                }
+            })
+            .collect())
+    })
+}
@@ -0,0 +1,128 @@
+use crate::{extract_documents, Document};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Which parts of a document are considered when looking for duplicates.
+#[derive(Clone, Copy)]
+enum DedupeKey {
+    /// Only `page_content` is compared.
+    Content,
+    /// `page_content` and every metadata key/value pair are compared.
+    ContentAndMetadata,
+}
+
+impl DedupeKey {
+    fn parse(key: &str) -> PyResult<Self> {
+        match key {
+            "content" => Ok(DedupeKey::Content),
+            "content+metadata" => Ok(DedupeKey::ContentAndMetadata),
+            other => Err(PyValueError::new_err(format!(
+                "Unknown dedupe key \"{}\", expected \"content\" or \"content+metadata\"",
+                other
+            ))),
+        }
+    }
+
+    /// Returns true when both documents are identical for this key.
+    fn same(&self, a: &Document, b: &Document) -> bool {
+        match self {
+            DedupeKey::Content => a.page_content == b.page_content,
+            DedupeKey::ContentAndMetadata => {
+                a.page_content == b.page_content && a.metadata == b.metadata
+            }
+        }
+    }
+}
+
+/// Hashes a document according to `key`.
+///
+/// Metadata is hashed in sorted key order so that the hash does not depend on
+/// the iteration order of the underlying `HashMap`.
+fn hash_document(document: &Document, key: DedupeKey) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    document.page_content.hash(&mut hasher);
+    if let DedupeKey::ContentAndMetadata = key {
+        let mut pairs: Vec<(&String, &String)> = document.metadata.iter().collect();
+        pairs.sort();
+        pairs.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Finds the indices of documents that are exact duplicates of an earlier document.
+///
+/// Hashes are computed in parallel, and documents whose hashes collide are compared
+/// in full so that a hash collision never drops a distinct document. The first
+/// occurrence of every document is always kept.
+fn duplicate_indices(documents: &[Document], key: DedupeKey) -> Vec<usize> {
+    let hashes: Vec<u64> = documents
+        .par_iter()
+        .map(|document| hash_document(document, key))
+        .collect();
+
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut dropped = Vec::new();
+    for (index, hash) in hashes.into_iter().enumerate() {
+        let kept = seen.entry(hash).or_default();
+        if kept
+            .iter()
+            .any(|&other| key.same(&documents[other], &documents[index]))
+        {
+            dropped.push(index);
+        } else {
+            kept.push(index);
+        }
+    }
+    dropped
+}
+
+/// Removes exact duplicate documents from a list.
+///
+/// # Arguments
+///
+/// * `docs` - The documents to deduplicate.
+/// * `key` - Either `"content"` to compare only page_content, or `"content+metadata"`
+///   to also require identical metadata.
+///
+/// # Returns
+///
+/// A tuple of the surviving documents (in their original order) and the indices of
+/// the documents that were dropped.
+#[pyfunction]
+#[pyo3(signature = (docs, key = "content"))]
+pub fn dedupe_docs(py: Python, docs: &PyAny, key: &str) -> PyResult<(Vec<Document>, Vec<usize>)> {
+    let key = DedupeKey::parse(key)?;
+    let doc_vec: Vec<Document> = extract_documents(docs)?;
+    let span = tracing::info_span!(
+        "dedupe_docs",
+        docs = doc_vec.len(),
+        dropped = tracing::field::Empty
+    )
+    .entered();
+
+    let result = py.allow_threads(|| {
+        let dropped = duplicate_indices(&doc_vec, key);
+        let mut dropped_iter = dropped.iter().peekable();
+        let survivors: Vec<Document> = doc_vec
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| {
+                if dropped_iter.peek() == Some(&index) {
+                    dropped_iter.next();
+                    false
+                } else {
+                    true
+                }
+            })
+            .map(|(_, document)| document)
+            .collect();
+        (survivors, dropped)
+    });
+
+    span.record("dropped", result.1.len());
+    Ok(result)
+}
@@ -0,0 +1,79 @@
+use once_cell::sync::Lazy;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// A Document struct that adheres to LangChain's [Document Class](https://api.python.langchain.com/en/latest/documents/langchain_core.documents.base.Document.html).
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    /// The text of the document.
+    pub page_content: String,
+    /// Metadata for the document. Currently only supports strings as keys *and* values.
+    pub metadata: HashMap<String, String>,
+}
+
+impl Document {
+    /// Returns a new document with page content and metadata.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_content` - A string that holds the content of the document.
+    /// * `metadata` - The metadata for the document.
+    pub fn new(page_content: impl Into<String>, metadata: HashMap<String, String>) -> Self {
+        Document {
+            page_content: page_content.into(),
+            metadata,
+        }
+    }
+
+    /// Prepends a context header, rendered from the document's metadata, to page_content.
+    ///
+    /// `{key}` placeholders in `template` are replaced with the matching metadata value,
+    /// and placeholders for missing keys are left empty. The character range of the
+    /// original text within the new page_content is stored in the `content_start` and
+    /// `content_end` metadata so the header can be stripped again later.
+    ///
+    /// For example:
+    ///
+    /// "{title} > {section}\n\n" with metadata {"title": "Guide", "section": "Setup"}
+    ///     -> "Guide > Setup\n\n<original page_content>"
+    pub fn add_context_header(&mut self, template: &str) {
+        let header = render_template(template, &self.metadata);
+        let content_start = header.chars().count();
+        let content_end = content_start + self.page_content.chars().count();
+        self.page_content.insert_str(0, &header);
+        self.metadata
+            .insert("content_start".to_string(), content_start.to_string());
+        self.metadata
+            .insert("content_end".to_string(), content_end.to_string());
+    }
+}
+
+/// A `{key}` placeholder in a context header template.
+static PLACEHOLDER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([^{}]+)\}").unwrap());
+
+/// Renders `{key}` placeholders in `template` with values from `metadata`.
+///
+/// Placeholders for keys that are not present in the metadata render as empty strings.
+pub(crate) fn render_template(template: &str, metadata: &HashMap<String, String>) -> String {
+    PLACEHOLDER_RE
+        .replace_all(template, |captures: &regex::Captures| {
+            metadata.get(&captures[1]).cloned().unwrap_or_default()
+        })
+        .to_string()
+}
+
+/// Returns the hex encoded SHA-256 digest of `text`.
+///
+/// Unlike `std`'s hashers this is stable across Rust versions and platforms, so it is
+/// safe to persist.
+pub(crate) fn content_hash(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
@@ -0,0 +1,81 @@
+use crate::{extract_documents, Document};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Named regular expressions whose matches are stored in document metadata.
+pub(crate) struct EntityPatterns {
+    patterns: Vec<(String, Regex)>,
+}
+
+impl EntityPatterns {
+    /// Compiles every pattern, naming the metadata key of any pattern that is invalid.
+    pub(crate) fn new(patterns: HashMap<String, String>) -> PyResult<Self> {
+        let patterns = patterns
+            .into_iter()
+            .map(|(key, pattern)| match Regex::new(&pattern) {
+                Ok(regex) => Ok((key, regex)),
+                Err(error) => Err(PyValueError::new_err(format!(
+                    "Invalid pattern for \"{}\": {}",
+                    key, error
+                ))),
+            })
+            .collect::<PyResult<_>>()?;
+        Ok(EntityPatterns { patterns })
+    }
+
+    /// Stores the unique matches of every pattern in the document's metadata, as a JSON
+    /// array in order of first appearance.
+    ///
+    /// When a pattern has capture groups, the first group is stored instead of the whole
+    /// match. Patterns without any matches leave the metadata alone.
+    pub(crate) fn apply(&self, document: &mut Document) {
+        for (key, regex) in &self.patterns {
+            let mut matches: Vec<&str> = Vec::new();
+            for captures in regex.captures_iter(&document.page_content) {
+                let found = captures.get(1).or_else(|| captures.get(0));
+                if let Some(found) = found.map(|found| found.as_str()) {
+                    if !matches.contains(&found) {
+                        matches.push(found);
+                    }
+                }
+            }
+            if !matches.is_empty() {
+                let json = serde_json::to_string(&matches).expect("strings serialize to JSON");
+                document.metadata.insert(key.clone(), json);
+            }
+        }
+    }
+}
+
+/// Runs named regular expressions over every document and stores the matches in its
+/// metadata, in parallel.
+///
+/// See `Document.extract_entities` for how matches are stored.
+///
+/// # Arguments
+///
+/// * `docs` - The documents to enrich.
+/// * `patterns` - A dictionary from metadata key to regular expression, in the syntax of
+///   Rust's `regex` crate.
+///
+/// # Returns
+///
+/// Copies of the documents with the entity metadata added.
+#[pyfunction]
+pub fn extract_entities_batch(
+    py: Python,
+    docs: &PyAny,
+    patterns: HashMap<String, String>,
+) -> PyResult<Vec<Document>> {
+    let patterns = EntityPatterns::new(patterns)?;
+    let mut doc_vec: Vec<Document> = extract_documents(docs)?;
+    py.allow_threads(|| {
+        doc_vec
+            .par_iter_mut()
+            .for_each(|document| patterns.apply(document))
+    });
+    Ok(doc_vec)
+}
@@ -0,0 +1,28 @@
+use std::fmt;
+
+/// The errors returned by the core cleaning and splitting API.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// An argument was out of range, such as a child chunk size that isn't smaller than
+    /// its parent chunk size.
+    InvalidArgument(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidArgument(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(feature = "python")]
+impl From<Error> for pyo3::PyErr {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::InvalidArgument(message) => pyo3::exceptions::PyValueError::new_err(message),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use crate::{Document, Error};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+/// A footnote marker in running text, `[12]` or superscript digits, along with the spaces
+/// before it.
+static MARKER_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[ \t]*(?:\[(\d{1,3})\]|([⁰¹²³⁴⁵⁶⁷⁸⁹]+))").unwrap());
+
+/// A line that starts with a footnote label, `[12]`, superscript digits or a plain
+/// number, followed by the footnote text.
+static DEFINITION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?:\[(\d{1,3})\]|([⁰¹²³⁴⁵⁶⁷⁸⁹]+)|(\d{1,3})[.)]?)\s+(\S.*)$").unwrap()
+});
+
+/// What `Document::clean_footnotes` does with the text of the footnotes it finds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FootnoteMode {
+    /// Drop the footnotes along with their markers.
+    Strip,
+    /// Replace each marker with the text of its footnote, in parentheses.
+    Inline,
+    /// Store each footnote under a `footnote_<number>` metadata key.
+    Metadata,
+}
+
+impl FromStr for FootnoteMode {
+    type Err = Error;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "strip" => Ok(FootnoteMode::Strip),
+            "inline" => Ok(FootnoteMode::Inline),
+            "metadata" => Ok(FootnoteMode::Metadata),
+            _ => Err(Error::InvalidArgument(format!(
+                "Unknown footnote mode \"{}\", expected \"strip\", \"inline\" or \"metadata\"",
+                mode
+            ))),
+        }
+    }
+}
+
+/// Converts superscript digits such as `¹²` to the number they stand for.
+fn superscript_number(digits: &str) -> Option<u32> {
+    digits.chars().try_fold(0u32, |number, digit| {
+        let value = "⁰¹²³⁴⁵⁶⁷⁸⁹".chars().position(|c| c == digit)? as u32;
+        number.checked_mul(10)?.checked_add(value)
+    })
+}
+
+/// The footnote number in the first group of `captures` that matched, which is either
+/// plain digits or superscript digits.
+fn label_number(captures: &Captures) -> Option<u32> {
+    captures
+        .iter()
+        .skip(1)
+        .flatten()
+        .next()
+        .and_then(|label| match label.as_str().parse() {
+            Ok(number) => Some(number),
+            Err(_) => superscript_number(label.as_str()),
+        })
+}
+
+/// Returns the footnote number and text when the first of `lines` is a footnote
+/// definition for a number that was already referenced by a marker.
+///
+/// A plain number like `1.` is also how a numbered list starts, so it is only taken as
+/// a label when every line up to the next blank line that looks like a footnote is one
+/// for a referenced number, as in a block of notes at the bottom of a page.
+fn definition<'a>(lines: &[&'a str], referenced: &HashSet<u32>) -> Option<(u32, &'a str)> {
+    let is_referenced = |captures: &Captures| {
+        label_number(captures).is_some_and(|number| referenced.contains(&number))
+    };
+    let captures = DEFINITION_RE.captures(lines[0])?;
+    if !is_referenced(&captures) {
+        return None;
+    }
+    if captures.get(3).is_some() {
+        let notes_block = lines
+            .iter()
+            .take_while(|line| !line.trim().is_empty())
+            .filter_map(|line| DEFINITION_RE.captures(line))
+            .all(|captures| is_referenced(&captures));
+        if !notes_block {
+            return None;
+        }
+    }
+    // The label groups come before the text group, and only one of them matches.
+    let number = label_number(&captures)?;
+    let text = captures.get(4)?.as_str().trim_end();
+    Some((number, text))
+}
+
+/// Removes the markers in `text`, or replaces them with their footnote in parentheses
+/// for `FootnoteMode::Inline`.
+fn resolve_markers(text: &str, footnotes: &HashMap<u32, String>, mode: FootnoteMode) -> String {
+    MARKER_RE
+        .replace_all(text, |captures: &Captures| {
+            let footnote = label_number(captures).and_then(|number| footnotes.get(&number));
+            let marker = captures.get(0).expect("the whole match");
+            // A bracketed number right after a word, like arr[1], is probably an index.
+            let after_word = marker.as_str().starts_with('[')
+                && text[..marker.start()]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|c| c.is_alphanumeric() || c == '_');
+            match (footnote, mode) {
+                (Some(footnote), FootnoteMode::Inline) => format!(" ({})", footnote),
+                // A superscript without a footnote is probably an exponent.
+                (None, _) if captures.get(2).is_some() || after_word => marker.as_str().to_string(),
+                _ => String::new(),
+            }
+        })
+        .into_owned()
+}
+
+impl Document {
+    /// Finds footnote markers and the footnotes they point to, and strips them or moves
+    /// the footnote text inline or into metadata, depending on `mode`.
+    ///
+    /// Markers are bracketed numbers like `[12]` and superscript digits like `¹²`. A
+    /// footnote is a line that starts with the label of a marker found earlier in the
+    /// text, `[12]`, `¹²` or `12`, and runs on over the following lines up to a blank
+    /// line or a line that starts with another label, like the notes at the bottom of a
+    /// page of an extracted PDF. Plain numbers are only labels in a block of lines that
+    /// are all notes, so numbered lists are kept. Text after a run of footnotes starts a
+    /// new page, so numbering that restarts on every page is matched up correctly.
+    ///
+    /// Bracketed markers are removed unless they come right after a word and no footnote
+    /// was found for them, so indexes like `arr[1]` are kept. Superscript digits are
+    /// only removed when a footnote with their number was found, so units like m² are
+    /// kept. With `FootnoteMode::Metadata`, footnotes from different pages with the same
+    /// number are stored under one key, separated by line breaks.
+    ///
+    /// For example, with `FootnoteMode::Inline`:
+    ///
+    /// "The ruling was appealed.¹\n\n¹ See the 2019 decision."
+    ///     -> "The ruling was appealed. (See the 2019 decision.)"
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn clean_footnotes(&mut self, mode: FootnoteMode) {
+        let mut pages: Vec<String> = Vec::new();
+        let mut referenced = HashSet::new();
+        // The body lines and footnotes of the current page.
+        let mut body: Vec<&str> = Vec::new();
+        let mut footnotes: HashMap<u32, String> = HashMap::new();
+        // The number of the footnote whose continuation lines are being collected.
+        let mut current: Option<u32> = None;
+        // Resolves the markers of the current page. The blank lines that were before its
+        // footnotes shrink to one, or to none at the end of the text.
+        let mut flush = |body: &mut Vec<&str>, footnotes: &mut HashMap<u32, String>, last: bool| {
+            if !footnotes.is_empty() {
+                let mut blank = false;
+                while body.last().is_some_and(|line| line.trim().is_empty()) {
+                    body.pop();
+                    blank = true;
+                }
+                if blank && !last {
+                    body.push("");
+                }
+            }
+            pages.push(resolve_markers(&body.join("\n"), footnotes, mode));
+            if mode == FootnoteMode::Metadata {
+                for (number, footnote) in footnotes.drain() {
+                    self.metadata
+                        .entry(format!("footnote_{}", number))
+                        .and_modify(|existing| {
+                            existing.push('\n');
+                            existing.push_str(&footnote);
+                        })
+                        .or_insert(footnote);
+                }
+            }
+            body.clear();
+            footnotes.clear();
+        };
+        let lines: Vec<&str> = self.page_content.split('\n').collect();
+        for (index, &line) in lines.iter().enumerate() {
+            if let Some((number, text)) = definition(&lines[index..], &referenced) {
+                footnotes.insert(number, text.to_string());
+                current = Some(number);
+                continue;
+            }
+            if line.trim().is_empty() {
+                // The blank line that ends a footnote goes with it, unless the body needs
+                // it to keep paragraphs apart.
+                if current.take().is_some() && body.last().is_none_or(|last| last.trim().is_empty())
+                {
+                    continue;
+                }
+            } else if let Some(number) = current.filter(|_| !DEFINITION_RE.is_match(line)) {
+                let footnote = footnotes.get_mut(&number).expect("current footnote exists");
+                footnote.push(' ');
+                footnote.push_str(line.trim());
+                continue;
+            } else if !footnotes.is_empty() {
+                // A labelled line that isn't a footnote ends the notes, like any other text.
+                current = None;
+                flush(&mut body, &mut footnotes, false);
+            }
+            for captures in MARKER_RE.captures_iter(line) {
+                referenced.extend(label_number(&captures));
+            }
+            body.push(line);
+        }
+        flush(&mut body, &mut footnotes, true);
+        self.page_content = pages.join("\n");
+    }
+}
@@ -0,0 +1,196 @@
+#[cfg(feature = "python")]
+use crate::loaders::{file_metadata, read_text_file};
+use crate::Document;
+use scraper::node::Node;
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashMap;
+#[cfg(feature = "python")]
+use std::path::Path;
+
+/// Elements that never contain readable content.
+const SKIPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "canvas", "iframe", "nav", "header",
+    "footer", "aside", "form", "button", "select", "head",
+];
+
+/// Elements that start a new block of text.
+const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "blockquote",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "li",
+    "main",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "tr",
+    "ul",
+];
+
+/// Inline elements whose text is kept apart from the text around them, like the cells
+/// of a table row.
+const SPACED_TAGS: &[&str] = &["td", "th"];
+
+/// Selectors tried in order to find the main content of a page, before falling back to
+/// the whole `<body>`.
+const MAIN_CONTENT_SELECTORS: &[&str] = &["main", "[role=main]", "article"];
+
+/// Collects the readable text under an element into blocks.
+///
+/// Text inside an inline element is appended to the current block as it is, so that
+/// `H<sub>2</sub>O` stays one word, while block level elements and `<br>` start a new
+/// one. Whitespace is collapsed within each block.
+struct TextCollector {
+    blocks: Vec<String>,
+    current: String,
+}
+
+impl TextCollector {
+    fn new() -> Self {
+        TextCollector {
+            blocks: Vec::new(),
+            current: String::new(),
+        }
+    }
+
+    fn break_block(&mut self) {
+        let block = self
+            .current
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ");
+        if !block.is_empty() {
+            self.blocks.push(block);
+        }
+        self.current.clear();
+    }
+
+    fn collect(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.current.push_str(text),
+                Node::Element(child_element) => {
+                    let name = child_element.name();
+                    if SKIPPED_TAGS.contains(&name) {
+                        continue;
+                    }
+                    let is_block = BLOCK_TAGS.contains(&name) || name == "br";
+                    let is_spaced = SPACED_TAGS.contains(&name);
+                    if is_block {
+                        self.break_block();
+                    } else if is_spaced {
+                        self.current.push(' ');
+                    }
+                    if let Some(child_ref) = ElementRef::wrap(child) {
+                        self.collect(child_ref);
+                    }
+                    if is_block {
+                        self.break_block();
+                    } else if is_spaced {
+                        self.current.push(' ');
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.break_block();
+        self.blocks.join("\n\n")
+    }
+}
+
+/// Returns the text of the first element matching `selector`, with whitespace collapsed.
+fn select_text(html: &Html, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).unwrap();
+    html.select(&selector).next().map(|element| {
+        element
+            .text()
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+    })
+}
+
+/// Returns the `attribute` of the first element matching `selector`.
+fn select_attribute(html: &Html, selector: &str, attribute: &str) -> Option<String> {
+    let selector = Selector::parse(selector).unwrap();
+    html.select(&selector)
+        .filter_map(|element| element.value().attr(attribute))
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+}
+
+/// Picks the element most likely to hold the main content of the page.
+///
+/// `<main>` and `[role=main]` win outright. Otherwise the `<article>` with the most text
+/// is used, falling back to `<body>` and finally the whole document.
+fn main_content(html: &Html) -> ElementRef<'_> {
+    for selector in MAIN_CONTENT_SELECTORS {
+        let selector = Selector::parse(selector).unwrap();
+        let best = html
+            .select(&selector)
+            .max_by_key(|element| element.text().map(str::len).sum::<usize>());
+        if let Some(element) = best {
+            return element;
+        }
+    }
+    let body = Selector::parse("body").unwrap();
+    html.select(&body)
+        .next()
+        .unwrap_or_else(|| html.root_element())
+}
+
+/// Extracts the readable text and page metadata from an HTML string.
+///
+/// The `title`, `description` (from `<meta name="description">`) and `canonical_url`
+/// (from `<link rel="canonical">`) metadata are only set when the page has them.
+pub(crate) fn parse_html(html: &str) -> Document {
+    let html = Html::parse_document(html);
+    let mut collector = TextCollector::new();
+    collector.collect(main_content(&html));
+
+    let mut metadata = HashMap::new();
+    if let Some(title) = select_text(&html, "title").filter(|title| !title.is_empty()) {
+        metadata.insert("title".to_string(), title);
+    }
+    if let Some(description) = select_attribute(&html, r#"meta[name="description"]"#, "content") {
+        metadata.insert("description".to_string(), description);
+    }
+    if let Some(canonical_url) = select_attribute(&html, r#"link[rel~="canonical"]"#, "href") {
+        metadata.insert("canonical_url".to_string(), canonical_url);
+    }
+
+    Document {
+        page_content: collector.finish(),
+        metadata,
+    }
+}
+
+/// Loads an HTML file into a Document, adding the file metadata to the page metadata.
+#[cfg(feature = "python")]
+pub(crate) fn load_html_file(path: &Path) -> pyo3::PyResult<Document> {
+    let html = read_text_file(path)?;
+    let mut document = parse_html(&html);
+    document.metadata.extend(file_metadata(path)?);
+    Ok(document)
+}
@@ -0,0 +1,365 @@
+use crate::document::content_hash;
+use crate::python::CleanAndSplit;
+use crate::{extract_documents, msgpack, Document};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::IntoPyDict;
+use rayon::prelude::*;
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Converts a SQLite error into a Python `ValueError`.
+fn to_py_error(error: rusqlite::Error) -> PyErr {
+    PyValueError::new_err(format!("Cache error: {}", error))
+}
+
+/// Hashes `document` under the settings it is split with, so that a change to either
+/// invalidates its cached chunks.
+///
+/// Metadata is hashed in sorted key order, since the chunks copy it.
+fn document_hash(settings: &str, document: &Document) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(settings.as_bytes());
+    hasher.update([0]);
+    hasher.update(document.page_content.as_bytes());
+    let mut pairs: Vec<(&String, &String)> = document.metadata.iter().collect();
+    pairs.sort();
+    for (key, value) in pairs {
+        hasher.update([0]);
+        hasher.update(key.as_bytes());
+        hasher.update([0]);
+        hasher.update(value.as_bytes());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Gives every chunk of the document stored under `key` an id under `id_key`, built from
+/// the key and the chunk's text and metadata, so that a chunk keeps its id for as long as
+/// neither changes.
+///
+/// Chunks that are identical are told apart by how many came before them.
+fn assign_chunk_ids(chunks: &mut [Document], key: &str, id_key: &str) -> Vec<String> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    chunks
+        .iter_mut()
+        .map(|chunk| {
+            chunk.metadata.remove(id_key);
+            let mut pairs: Vec<(&String, &String)> = chunk.metadata.iter().collect();
+            pairs.sort();
+            let identity = format!("{}\0{}\0{:?}", key, chunk.page_content, pairs);
+            let occurrence = seen.entry(identity.clone()).or_insert(0);
+            let id = content_hash(&format!("{}\0{}", identity, occurrence));
+            *occurrence += 1;
+            chunk.metadata.insert(id_key.to_string(), id.clone());
+            id
+        })
+        .collect()
+}
+
+/// A document of the corpus, with the key its chunks are cached under.
+struct Entry<'a> {
+    key: String,
+    hash: String,
+    document: &'a Document,
+}
+
+/// The cached chunks of a document.
+struct Cached {
+    hash: String,
+    chunk_ids: Vec<String>,
+}
+
+/// What `IncrementalProcessor.process` did, returned to Python as a dict.
+pub struct ProcessResult {
+    chunks: Vec<Document>,
+    added: Vec<String>,
+    removed: Vec<String>,
+    processed: usize,
+    reused: usize,
+}
+
+impl IntoPy<PyObject> for ProcessResult {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        let items: [(&str, PyObject); 5] = [
+            ("chunks", self.chunks.into_py(py)),
+            ("added", self.added.into_py(py)),
+            ("removed", self.removed.into_py(py)),
+            ("processed", self.processed.into_py(py)),
+            ("reused", self.reused.into_py(py)),
+        ];
+        items.into_py_dict(py).into()
+    }
+}
+
+/// Cleans and splits documents like `clean_and_split_docs`, keeping the chunks of every
+/// document in a SQLite cache so that running it again over a mostly unchanged corpus
+/// only cleans and splits the documents that are new or have changed.
+///
+/// Documents are identified by their `id_key` metadata, or by a hash of their
+/// page_content when they don't have one. A document has changed when its page_content
+/// or metadata has, and every cached chunk is thrown away when the splitting arguments
+/// or the version of this module change.
+#[pyclass]
+pub struct IncrementalProcessor {
+    connection: Mutex<Connection>,
+    task: CleanAndSplit,
+    id_key: String,
+    /// The splitting arguments and the module version, hashed into every document hash.
+    settings: String,
+}
+
+impl IncrementalProcessor {
+    /// Reads the hash and the chunk ids cached for every key.
+    fn cached(connection: &Connection) -> rusqlite::Result<HashMap<String, Cached>> {
+        let mut statement = connection.prepare("SELECT key, hash, chunk_ids FROM documents")?;
+        let rows = statement.query_map([], |row| {
+            let chunk_ids: String = row.get(2)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                Cached {
+                    hash: row.get(1)?,
+                    chunk_ids: chunk_ids.split_whitespace().map(str::to_string).collect(),
+                },
+            ))
+        })?;
+        rows.collect()
+    }
+
+    /// Reads the cached chunks of `key`.
+    fn cached_chunks(connection: &Connection, key: &str) -> PyResult<Vec<Document>> {
+        let chunks: Option<Vec<u8>> = connection
+            .query_row(
+                "SELECT chunks FROM documents WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(to_py_error)?;
+        msgpack::decode(&chunks.unwrap_or_default())
+    }
+}
+
+#[pymethods]
+impl IncrementalProcessor {
+    /// Opens the cache at `path`, creating it if it doesn't exist.
+    ///
+    /// The other arguments work the same way as in `clean_and_split_docs`. Every chunk
+    /// also gets an id under `id_key`, which stays the same for as long as its text and
+    /// metadata do.
+    #[new]
+    #[pyo3(signature = (
+        path,
+        chunk_size,
+        provenance = false,
+        id_key = "id",
+        context_template = None,
+        overlap_sentences = None,
+        unit = "characters",
+        encoding = "cl100k_base",
+        min_chunk_size = 0,
+        drop_empty = false
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        path: PathBuf,
+        chunk_size: usize,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+        overlap_sentences: Option<usize>,
+        unit: &str,
+        encoding: &str,
+        min_chunk_size: usize,
+        drop_empty: bool,
+    ) -> PyResult<Self> {
+        let task = CleanAndSplit::new(
+            chunk_size,
+            provenance,
+            id_key,
+            context_template,
+            "rs_document",
+            overlap_sentences,
+            unit,
+            encoding,
+            min_chunk_size,
+            drop_empty,
+        )?;
+        let settings = format!(
+            "{}\0{}\0{}\0{}\0{:?}\0{:?}\0{}\0{}\0{}\0{}",
+            env!("CARGO_PKG_VERSION"),
+            chunk_size,
+            provenance,
+            id_key,
+            context_template,
+            overlap_sentences,
+            unit,
+            encoding,
+            min_chunk_size,
+            drop_empty
+        );
+        let connection = Connection::open(path).map_err(to_py_error)?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS documents (
+                    key TEXT PRIMARY KEY,
+                    hash TEXT NOT NULL,
+                    chunk_ids TEXT NOT NULL,
+                    chunks BLOB NOT NULL
+                )",
+            )
+            .map_err(to_py_error)?;
+        Ok(IncrementalProcessor {
+            connection: Mutex::new(connection),
+            task,
+            id_key: id_key.to_string(),
+            settings,
+        })
+    }
+
+    /// Cleans and splits the documents that are new or have changed since the last run,
+    /// in parallel, and reuses the cached chunks of the rest.
+    ///
+    /// # Arguments
+    ///
+    /// * `docs` - The documents of the corpus.
+    /// * `prune` - Remove the documents that are cached but aren't in `docs` from the
+    ///   cache, and report their chunks as removed. Turn this off to process part of a
+    ///   corpus at a time.
+    ///
+    /// # Returns
+    ///
+    /// A dictionary with the `chunks` of every document in `docs`, in order, the ids of
+    /// the chunks that were `added` and `removed` since the last run, and the number of
+    /// documents that were `processed` and `reused` from the cache.
+    #[pyo3(signature = (docs, prune = true))]
+    fn process(&self, py: Python, docs: &PyAny, prune: bool) -> PyResult<ProcessResult> {
+        let doc_vec: Vec<Document> = extract_documents(docs)?;
+        let span = tracing::info_span!(
+            "process",
+            docs = doc_vec.len(),
+            processed = tracing::field::Empty
+        )
+        .entered();
+        let result = py.allow_threads(|| self.process_documents(&doc_vec, prune))?;
+        span.record("processed", result.processed);
+        Ok(result)
+    }
+
+    /// Removes every cached document, so the next run processes the whole corpus.
+    fn clear(&self) -> PyResult<()> {
+        let connection = self.connection.lock().unwrap();
+        connection
+            .execute("DELETE FROM documents", [])
+            .map_err(to_py_error)?;
+        Ok(())
+    }
+}
+
+impl IncrementalProcessor {
+    fn process_documents(&self, docs: &[Document], prune: bool) -> PyResult<ProcessResult> {
+        let entries: Vec<Entry> = docs
+            .par_iter()
+            .map(|document| Entry {
+                key: document
+                    .metadata
+                    .get(&self.id_key)
+                    .cloned()
+                    .unwrap_or_else(|| content_hash(&document.page_content)),
+                hash: document_hash(&self.settings, document),
+                document,
+            })
+            .collect();
+        let mut keys = HashSet::new();
+        for entry in &entries {
+            if !keys.insert(entry.key.as_str()) {
+                return Err(PyValueError::new_err(format!(
+                    "Documents must have unique \"{}\" metadata, but \"{}\" appears more than \
+                     once; identical documents without it can be removed with dedupe_docs",
+                    self.id_key, entry.key
+                )));
+            }
+        }
+
+        let mut connection = self.connection.lock().unwrap();
+        let mut cached = Self::cached(&connection).map_err(to_py_error)?;
+        let changed: Vec<bool> = entries
+            .iter()
+            .map(|entry| {
+                !matches!(cached.get(&entry.key), Some(cached) if cached.hash == entry.hash)
+            })
+            .collect();
+
+        let split: Vec<Option<(Vec<Document>, Vec<String>)>> = entries
+            .par_iter()
+            .zip(&changed)
+            .map(|(entry, &changed)| {
+                changed.then(|| {
+                    let mut chunks = self.task.split(entry.document);
+                    let ids = assign_chunk_ids(&mut chunks, &entry.key, &self.id_key);
+                    (chunks, ids)
+                })
+            })
+            .collect();
+
+        let mut chunks = Vec::new();
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        let transaction = connection.transaction().map_err(to_py_error)?;
+        for (entry, split) in entries.iter().zip(split) {
+            let Some((new_chunks, new_ids)) = split else {
+                chunks.extend(Self::cached_chunks(&transaction, &entry.key)?);
+                cached.remove(&entry.key);
+                continue;
+            };
+            let old_ids = cached
+                .remove(&entry.key)
+                .map(|cached| cached.chunk_ids)
+                .unwrap_or_default();
+            let old_set: HashSet<&String> = old_ids.iter().collect();
+            let new_set: HashSet<&String> = new_ids.iter().collect();
+            added.extend(new_ids.iter().filter(|id| !old_set.contains(id)).cloned());
+            removed.extend(old_ids.iter().filter(|id| !new_set.contains(id)).cloned());
+            transaction
+                .execute(
+                    "INSERT OR REPLACE INTO documents (key, hash, chunk_ids, chunks)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        entry.key,
+                        entry.hash,
+                        new_ids.join(" "),
+                        msgpack::encode(&new_chunks)?
+                    ],
+                )
+                .map_err(to_py_error)?;
+            chunks.extend(new_chunks);
+        }
+        if prune {
+            // What is left in `cached` are the documents that aren't in the corpus.
+            let mut stale: Vec<(String, Cached)> = cached.into_iter().collect();
+            stale.sort_by(|a, b| a.0.cmp(&b.0));
+            for (key, stale) in stale {
+                removed.extend(stale.chunk_ids);
+                transaction
+                    .execute("DELETE FROM documents WHERE key = ?1", params![key])
+                    .map_err(to_py_error)?;
+            }
+        }
+        transaction.commit().map_err(to_py_error)?;
+
+        let processed = changed.iter().filter(|&&changed| changed).count();
+        Ok(ProcessResult {
+            chunks,
+            added,
+            removed,
+            processed,
+            reused: entries.len() - processed,
+        })
+    }
+}
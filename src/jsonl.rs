@@ -0,0 +1,318 @@
+use crate::loaders::{
+    check_loader_keys, decode_read_error, metadata_value, DecodeErrors, DecodingReader, LOADER_KEYS,
+};
+use crate::{extract_documents, Document};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+
+/// The number of lines parsed in parallel every time the iterator's buffer runs dry.
+const BATCH_SIZE: usize = 4096;
+
+/// Which fields of each JSON record become the page_content and the metadata.
+struct FieldMapping {
+    source: String,
+    content_key: String,
+    metadata_keys: Option<Vec<String>>,
+}
+
+impl FieldMapping {
+    /// Turns one JSON record into a Document.
+    ///
+    /// `row` is the zero based line of the record in a JSON Lines file, or its position
+    /// in a JSON array, and is stored in the metadata along with the `source` path.
+    fn to_document(&self, row: usize, value: serde_json::Value) -> PyResult<Document> {
+        let mut object = match value {
+            serde_json::Value::Object(object) => object,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Record {} in {} is not a JSON object",
+                    row, self.source
+                )))
+            }
+        };
+        let page_content = match object.remove(&self.content_key) {
+            Some(value) => metadata_value(value),
+            None => {
+                return Err(PyValueError::new_err(format!(
+                    "Record {} in {} has no \"{}\" key",
+                    row, self.source, self.content_key
+                )))
+            }
+        };
+        let mut metadata: HashMap<String, String> = match &self.metadata_keys {
+            Some(keys) => keys
+                .iter()
+                .filter_map(|key| {
+                    object
+                        .remove(key)
+                        .map(|value| (key.clone(), metadata_value(value)))
+                })
+                .collect(),
+            None => {
+                if let Some(key) = LOADER_KEYS.iter().find(|key| object.contains_key(**key)) {
+                    return Err(PyValueError::new_err(format!(
+                        "Record {} in {} has a \"{}\" key, which would overwrite the \"{}\" \
+                         metadata that load_jsonl adds; pass metadata_keys without it",
+                        row, self.source, key, key
+                    )));
+                }
+                object
+                    .into_iter()
+                    .map(|(key, value)| (key, metadata_value(value)))
+                    .collect()
+            }
+        };
+        metadata.insert("source".to_string(), self.source.clone());
+        metadata.insert("row".to_string(), row.to_string());
+        Ok(Document {
+            page_content,
+            metadata,
+        })
+    }
+
+    fn parse_line(&self, row: usize, line: &str) -> PyResult<Document> {
+        let value = serde_json::from_str(line).map_err(|error| {
+            PyValueError::new_err(format!(
+                "Invalid JSON on line {} of {}: {}",
+                row + 1,
+                self.source,
+                error
+            ))
+        })?;
+        self.to_document(row, value)
+    }
+}
+
+/// Where the iterator gets its records from.
+enum Records {
+    /// A JSON Lines file that is read lazily, one batch of lines at a time.
+    Lines(BufReader<DecodingReader<File>>),
+    /// A JSON array, which has to be parsed in one go.
+    Array(std::vec::IntoIter<serde_json::Value>),
+}
+
+/// An iterator over the Documents in a JSON Lines or JSON array file.
+///
+/// Lines are read in batches which are parsed in parallel, so the whole file never has to
+/// be in memory at once.
+#[pyclass]
+pub struct JsonlIterator {
+    mapping: FieldMapping,
+    records: Records,
+    buffer: VecDeque<PyResult<Document>>,
+    next_row: usize,
+}
+
+impl JsonlIterator {
+    /// Refills the buffer with the next batch of documents.
+    fn fill_buffer(&mut self) -> PyResult<()> {
+        match &mut self.records {
+            Records::Lines(reader) => {
+                // Blank lines are skipped but still counted, so that rows are line
+                // numbers in the file.
+                let mut lines = Vec::with_capacity(BATCH_SIZE);
+                while lines.len() < BATCH_SIZE {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).map_err(decode_read_error)? == 0 {
+                        break;
+                    }
+                    if !line.trim().is_empty() {
+                        lines.push((self.next_row, line));
+                    }
+                    self.next_row += 1;
+                }
+                let mapping = &self.mapping;
+                let documents: Vec<PyResult<Document>> = lines
+                    .par_iter()
+                    .map(|(row, line)| mapping.parse_line(*row, line))
+                    .collect();
+                self.buffer.extend(documents);
+            }
+            Records::Array(values) => {
+                for value in values.by_ref().take(BATCH_SIZE) {
+                    let document = self.mapping.to_document(self.next_row, value);
+                    self.buffer.push_back(document);
+                    self.next_row += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl JsonlIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>, py: Python) -> PyResult<Option<Document>> {
+        if slf.buffer.is_empty() {
+            let iterator = &mut *slf;
+            py.allow_threads(|| iterator.fill_buffer())?;
+        }
+        slf.buffer.pop_front().transpose()
+    }
+}
+
+/// Opens `path` for reading, returning an array of records if it holds a JSON array and
+/// a line reader otherwise.
+fn open_records(path: &PathBuf, encoding: Option<&str>, errors: DecodeErrors) -> PyResult<Records> {
+    let file = File::open(path)?;
+    let name = path.display().to_string();
+    let mut reader = BufReader::new(DecodingReader::new(file, encoding, errors, name)?);
+    let starts_with_bracket = loop {
+        let buffer = reader.fill_buf().map_err(decode_read_error)?;
+        match buffer.iter().position(|byte| !byte.is_ascii_whitespace()) {
+            Some(position) => break buffer[position] == b'[',
+            None if buffer.is_empty() => break false,
+            None => {
+                let consumed = buffer.len();
+                reader.consume(consumed);
+            }
+        }
+    };
+
+    if starts_with_bracket {
+        let mut text = String::new();
+        reader
+            .read_to_string(&mut text)
+            .map_err(decode_read_error)?;
+        let values: Vec<serde_json::Value> = serde_json::from_str(&text).map_err(|error| {
+            PyValueError::new_err(format!("Invalid JSON in {}: {}", path.display(), error))
+        })?;
+        Ok(Records::Array(values.into_iter()))
+    } else {
+        Ok(Records::Lines(reader))
+    }
+}
+
+/// Streams the records of a JSON Lines file (or a file holding a JSON array of objects)
+/// as Documents.
+///
+/// Every record must be a JSON object. Values that aren't strings are stored as JSON,
+/// and each document also gets the `source` path and the zero based `row` of its record:
+/// its line in a JSON Lines file, blank lines included, or its position in a JSON array.
+/// Records with a `source` or `row` key of their own raise a `ValueError` unless
+/// `metadata_keys` leaves it out, so that it isn't silently overwritten.
+///
+/// # Arguments
+///
+/// * `path` - The path of the JSONL or JSON file.
+/// * `content_key` - The key whose value becomes the page_content.
+/// * `metadata_keys` - The keys copied into metadata. Defaults to every key other than
+///   `content_key`.
+/// * `encoding` - The encoding of the file. Defaults to UTF-8.
+/// * `errors` - What to do when the file isn't valid in `encoding`, as in
+///   `load_text_files`. The encoding is detected from the first megabyte of the file,
+///   and invalid bytes after it are replaced.
+///
+/// # Returns
+///
+/// An iterator of Documents, which can be passed straight to the batch functions.
+#[pyfunction]
+#[pyo3(signature = (
+    path,
+    content_key = "text",
+    metadata_keys = None,
+    encoding = None,
+    errors = "detect",
+))]
+pub fn load_jsonl(
+    path: PathBuf,
+    content_key: &str,
+    metadata_keys: Option<Vec<String>>,
+    encoding: Option<&str>,
+    errors: &str,
+) -> PyResult<JsonlIterator> {
+    let errors = errors.parse::<DecodeErrors>()?;
+    if let Some(keys) = &metadata_keys {
+        check_loader_keys(
+            "load_jsonl",
+            "Key",
+            keys.iter().map(String::as_str),
+            "leave it out of metadata_keys",
+        )?;
+    }
+    Ok(JsonlIterator {
+        records: open_records(&path, encoding, errors)?,
+        mapping: FieldMapping {
+            source: path.display().to_string(),
+            content_key: content_key.to_string(),
+            metadata_keys,
+        },
+        buffer: VecDeque::new(),
+        next_row: 0,
+    })
+}
+
+/// Writes every line to `writer`, terminating each with a newline.
+fn write_lines<W: Write>(writer: &mut W, lines: &[String]) -> std::io::Result<()> {
+    for line in lines {
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Writes documents to a JSON Lines file, one `{"page_content": ..., "metadata": {...}}`
+/// object per line.
+///
+/// Documents are serialized in parallel and written in order.
+///
+/// # Arguments
+///
+/// * `docs` - The documents to write.
+/// * `path` - The path of the file to write.
+/// * `append` - Append to the file instead of overwriting it.
+/// * `gzip` - Gzip compress the output. Defaults to compressing when `path` ends in `.gz`.
+///   Appending to a gzip file adds a new gzip member, which gzip readers handle.
+///
+/// # Returns
+///
+/// The number of documents written.
+#[pyfunction]
+#[pyo3(signature = (docs, path, append = false, gzip = None))]
+pub fn write_jsonl(
+    py: Python,
+    docs: &PyAny,
+    path: PathBuf,
+    append: bool,
+    gzip: Option<bool>,
+) -> PyResult<usize> {
+    let doc_vec: Vec<Document> = extract_documents(docs)?;
+    let gzip = gzip.unwrap_or_else(|| path.extension().is_some_and(|ext| ext == "gz"));
+
+    py.allow_threads(|| {
+        let lines: Vec<String> = doc_vec
+            .par_iter()
+            .map(serde_json::to_string)
+            .collect::<Result<_, _>>()
+            .map_err(|error| PyValueError::new_err(error.to_string()))?;
+
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(append)
+            .truncate(!append)
+            .open(&path)?;
+        let writer = BufWriter::new(file);
+        if gzip {
+            let mut encoder = GzEncoder::new(writer, Compression::default());
+            write_lines(&mut encoder, &lines)?;
+            encoder.finish()?.flush()?;
+        } else {
+            let mut writer = writer;
+            write_lines(&mut writer, &lines)?;
+            writer.flush()?;
+        }
+        Ok(lines.len())
+    })
+}
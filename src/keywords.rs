@@ -0,0 +1,255 @@
+#[cfg(feature = "python")]
+use crate::extract_documents;
+use crate::{Document, Error};
+use once_cell::sync::Lazy;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+/// Common English words that are never keywords on their own, separated by whitespace.
+const STOPWORDS: &str = "
+    a about above after again against all also am an and any are as at be because been
+    before being below between both but by can could did do does doing down during each
+    few for from further had has have having he her here hers herself him himself his
+    how i if in into is it its itself just may me might more most must my myself no nor
+    not now of off on once only or other our ours ourselves out over own same shall she
+    should so some such than that the their theirs them themselves then there these they
+    this those through to too under until up us very was we were what when where which
+    while who whom why will with would you your yours yourself yourselves
+";
+
+static STOPWORD_SET: Lazy<HashSet<&'static str>> =
+    Lazy::new(|| STOPWORDS.split_whitespace().collect());
+
+/// The longest phrase, in words, that RAKE will return.
+const MAX_PHRASE_WORDS: usize = 3;
+
+/// How `Document::extract_keywords` picks keywords.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeywordMethod {
+    /// Rapid Automatic Keyword Extraction. Candidate phrases are the runs of words between
+    /// stopwords and punctuation, scored by how often their words appear alongside others.
+    Rake,
+    /// Single words scored by term frequency times inverse document frequency.
+    TfIdf,
+}
+
+impl FromStr for KeywordMethod {
+    type Err = Error;
+
+    fn from_str(method: &str) -> Result<Self, Self::Err> {
+        match method {
+            "rake" => Ok(KeywordMethod::Rake),
+            "tfidf" => Ok(KeywordMethod::TfIdf),
+            _ => Err(Error::InvalidArgument(format!(
+                "Unknown keyword method \"{}\", expected \"rake\" or \"tfidf\"",
+                method
+            ))),
+        }
+    }
+}
+
+/// Splits `text` into lowercase words, with `None` wherever punctuation breaks a phrase.
+///
+/// Whitespace and hyphens separate words without breaking phrases.
+pub(crate) fn tokenize(text: &str) -> Vec<Option<String>> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    for character in text.chars() {
+        if character.is_alphanumeric() || (character == '\'' && !word.is_empty()) {
+            word.extend(character.to_lowercase());
+            continue;
+        }
+        if !word.is_empty() {
+            tokens.push(Some(word.trim_end_matches('\'').to_string()));
+            word.clear();
+        }
+        if !character.is_whitespace() && character != '-' {
+            tokens.push(None);
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(Some(word.trim_end_matches('\'').to_string()));
+    }
+    tokens
+}
+
+/// Whether `word` can be part of a keyword: not a stopword, a number or a single letter.
+fn is_content_word(word: &str) -> bool {
+    !STOPWORD_SET.contains(word)
+        && word.chars().nth(1).is_some()
+        && !word.chars().all(char::is_numeric)
+}
+
+/// Counts the content words in `text`.
+pub(crate) fn term_counts(text: &str) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for word in tokenize(text).into_iter().flatten() {
+        if is_content_word(&word) {
+            *counts.entry(word).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Scores terms by their frequency in a document times `idf`, the inverse document
+/// frequency of each term across a corpus.
+pub(crate) fn tfidf_keywords(
+    counts: &HashMap<String, usize>,
+    top_k: usize,
+    idf: impl Fn(&str) -> f64,
+) -> Vec<(String, f64)> {
+    let total = counts.values().sum::<usize>() as f64;
+    let scores = counts
+        .iter()
+        .map(|(term, &count)| (term.clone(), count as f64 / total * idf(term)))
+        .collect();
+    top_scores(scores, top_k)
+}
+
+/// Scores RAKE's candidate phrases in `text`.
+fn rake_keywords(text: &str, top_k: usize) -> Vec<(String, f64)> {
+    let mut phrases: Vec<Vec<String>> = Vec::new();
+    let mut phrase = Vec::new();
+    for token in tokenize(text) {
+        match token {
+            Some(word) if is_content_word(&word) && phrase.len() < MAX_PHRASE_WORDS => {
+                phrase.push(word)
+            }
+            Some(word) if is_content_word(&word) => {
+                phrases.push(std::mem::take(&mut phrase));
+                phrase.push(word);
+            }
+            _ if !phrase.is_empty() => phrases.push(std::mem::take(&mut phrase)),
+            _ => {}
+        }
+    }
+    if !phrase.is_empty() {
+        phrases.push(phrase);
+    }
+
+    // A word's degree is the number of words in the phrases it appears in, so words
+    // that appear in long phrases score higher than words that stand alone.
+    let mut frequency: HashMap<&str, usize> = HashMap::new();
+    let mut degree: HashMap<&str, usize> = HashMap::new();
+    for phrase in &phrases {
+        for word in phrase {
+            *frequency.entry(word).or_insert(0) += 1;
+            *degree.entry(word).or_insert(0) += phrase.len();
+        }
+    }
+    let mut scores: HashMap<String, f64> = HashMap::new();
+    for phrase in &phrases {
+        let score = phrase
+            .iter()
+            .map(|word| degree[word.as_str()] as f64 / frequency[word.as_str()] as f64)
+            .sum();
+        scores.insert(phrase.join(" "), score);
+    }
+    top_scores(scores.into_iter().collect(), top_k)
+}
+
+/// The `top_k` highest scores, with ties broken alphabetically.
+fn top_scores(mut scores: Vec<(String, f64)>, top_k: usize) -> Vec<(String, f64)> {
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    scores.truncate(top_k);
+    scores
+}
+
+impl Document {
+    /// Returns the `top_k` keywords of page_content with their scores, highest first.
+    ///
+    /// `KeywordMethod::Rake` returns phrases of up to three words. `KeywordMethod::TfIdf`
+    /// returns single words; with only one document to go on every word is equally
+    /// rare, so it ranks words by frequency. Use `extract_keywords_batch` from Python to
+    /// weigh words by how rare they are across a corpus.
+    ///
+    /// For example, with `KeywordMethod::Rake`:
+    ///
+    /// "Rust splitters for retrieval augmented generation."
+    ///     -> [("retrieval augmented generation", 9.0), ("rust splitters", 4.0)]
+    pub fn extract_keywords(&self, top_k: usize, method: KeywordMethod) -> Vec<(String, f64)> {
+        match method {
+            KeywordMethod::Rake => rake_keywords(&self.page_content, top_k),
+            KeywordMethod::TfIdf => {
+                tfidf_keywords(&term_counts(&self.page_content), top_k, |_| 1.0)
+            }
+        }
+    }
+}
+
+/// Adds the `top_k` keywords of every document to its metadata, in parallel.
+///
+/// `method` defaults to `"rake"`, like `Document.extract_keywords`. With
+/// `method="tfidf"` inverse document frequencies are computed across all of `docs`, so
+/// words that appear in most documents are ranked down. Keywords are stored under
+/// `metadata_key` as a comma separated list, highest scoring first.
+///
+/// # Returns
+///
+/// Copies of the documents with the keyword metadata added.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (docs, top_k = 10, method = "rake", metadata_key = "keywords"))]
+pub fn extract_keywords_batch(
+    py: Python,
+    docs: &PyAny,
+    top_k: usize,
+    method: &str,
+    metadata_key: &str,
+) -> PyResult<Vec<Document>> {
+    let method: KeywordMethod = method.parse()?;
+    let mut doc_vec = extract_documents(docs)?;
+
+    py.allow_threads(|| {
+        let keywords: Vec<Vec<(String, f64)>> = match method {
+            KeywordMethod::Rake => doc_vec
+                .par_iter()
+                .map(|document| document.extract_keywords(top_k, method))
+                .collect(),
+            KeywordMethod::TfIdf => {
+                let counts: Vec<HashMap<String, usize>> = doc_vec
+                    .par_iter()
+                    .map(|document| term_counts(&document.page_content))
+                    .collect();
+                let document_frequency = counts
+                    .par_iter()
+                    .fold(
+                        HashMap::new,
+                        |mut frequency: HashMap<&str, usize>, counts| {
+                            for term in counts.keys() {
+                                *frequency.entry(term).or_insert(0) += 1;
+                            }
+                            frequency
+                        },
+                    )
+                    .reduce(HashMap::new, |mut a, b| {
+                        for (term, count) in b {
+                            *a.entry(term).or_insert(0) += count;
+                        }
+                        a
+                    });
+                // Smoothed so that a term found in every document still counts.
+                let documents = counts.len() as f64;
+                let idf = |term: &str| {
+                    ((1.0 + documents) / (1.0 + document_frequency[term] as f64)).ln() + 1.0
+                };
+                counts
+                    .par_iter()
+                    .map(|counts| tfidf_keywords(counts, top_k, idf))
+                    .collect()
+            }
+        };
+
+        for (document, keywords) in doc_vec.iter_mut().zip(keywords) {
+            let keywords: Vec<String> = keywords.into_iter().map(|(word, _)| word).collect();
+            document
+                .metadata
+                .insert(metadata_key.to_string(), keywords.join(", "));
+        }
+    });
+    Ok(doc_vec)
+}
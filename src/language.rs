@@ -0,0 +1,93 @@
+use crate::{extract_documents, Document};
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+
+/// The ISO 639-3 code for text whose language couldn't be determined.
+const UNDETERMINED: &str = "und";
+
+/// Detects the language of `text`, returning its ISO 639-3 code.
+///
+/// Detections that whatlang doesn't consider reliable, or whose confidence is below
+/// `min_confidence`, come back as `"und"`.
+fn detect_language(text: &str, min_confidence: f64) -> &'static str {
+    match whatlang::detect(text) {
+        Some(info) if info.is_reliable() && info.confidence() >= min_confidence => {
+            info.lang().code()
+        }
+        _ => UNDETERMINED,
+    }
+}
+
+/// Tags every document with the language of its page_content, in parallel.
+///
+/// # Arguments
+///
+/// * `docs` - The documents to tag.
+/// * `metadata_key` - The metadata key the ISO 639-3 language code (`"eng"`, `"deu"`,
+///   `"cmn"`, ...) is stored under.
+/// * `min_confidence` - The lowest detection confidence, from 0 to 1, that is accepted.
+///   Documents below it, or too short to detect reliably, are tagged `"und"`.
+///
+/// # Returns
+///
+/// Copies of the documents with the language metadata added.
+#[pyfunction]
+#[pyo3(signature = (docs, metadata_key = "language", min_confidence = 0.0))]
+pub fn detect_languages(
+    py: Python,
+    docs: &PyAny,
+    metadata_key: &str,
+    min_confidence: f64,
+) -> PyResult<Vec<Document>> {
+    let mut doc_vec: Vec<Document> = extract_documents(docs)?;
+    py.allow_threads(|| {
+        doc_vec.par_iter_mut().for_each(|document| {
+            let language = detect_language(&document.page_content, min_confidence);
+            document
+                .metadata
+                .insert(metadata_key.to_string(), language.to_string());
+        })
+    });
+    Ok(doc_vec)
+}
+
+/// Groups documents by language, so each language can go through its own pipeline.
+///
+/// Documents that already have a `metadata_key` value (from `detect_languages`, or a
+/// loader that knows the language) are grouped by it, and the rest are detected in
+/// parallel and tagged the same way.
+///
+/// # Returns
+///
+/// A dictionary from language code to the documents in that language, in their original
+/// order.
+#[pyfunction]
+#[pyo3(signature = (docs, metadata_key = "language", min_confidence = 0.0))]
+pub fn partition_by_language(
+    py: Python,
+    docs: &PyAny,
+    metadata_key: &str,
+    min_confidence: f64,
+) -> PyResult<BTreeMap<String, Vec<Document>>> {
+    let mut doc_vec: Vec<Document> = extract_documents(docs)?;
+    let partitions = py.allow_threads(|| {
+        doc_vec.par_iter_mut().for_each(|document| {
+            if !document.metadata.contains_key(metadata_key) {
+                let language = detect_language(&document.page_content, min_confidence);
+                document
+                    .metadata
+                    .insert(metadata_key.to_string(), language.to_string());
+            }
+        });
+        let mut partitions: BTreeMap<String, Vec<Document>> = BTreeMap::new();
+        for document in doc_vec {
+            partitions
+                .entry(document.metadata[metadata_key].clone())
+                .or_default()
+                .push(document);
+        }
+        partitions
+    });
+    Ok(partitions)
+}
@@ -0,0 +1,391 @@
+use crate::aio::{spawn_awaitable, Work};
+use crate::{Document, Error};
+use chardetng::EncodingDetector;
+use encoding_rs::{Decoder, DecoderResult, Encoding};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
+
+/// What the file loaders do with a file that isn't valid in its encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DecodeErrors {
+    /// Guess the file's real encoding from its bytes and decode it with that.
+    Detect,
+    /// Replace the invalid bytes with U+FFFD, the replacement character.
+    Replace,
+    /// Raise a `ValueError`.
+    Error,
+}
+
+impl FromStr for DecodeErrors {
+    type Err = Error;
+
+    fn from_str(errors: &str) -> Result<Self, Self::Err> {
+        match errors {
+            "detect" => Ok(DecodeErrors::Detect),
+            "replace" => Ok(DecodeErrors::Replace),
+            "error" => Ok(DecodeErrors::Error),
+            _ => Err(Error::InvalidArgument(format!(
+                "Unknown errors \"{}\", expected \"detect\", \"replace\" or \"error\"",
+                errors
+            ))),
+        }
+    }
+}
+
+/// Guesses the encoding of `bytes`, which aren't valid in the encoding they were
+/// supposed to be in.
+///
+/// The guess comes from the frequencies of bytes and byte pairs typical of each legacy
+/// encoding, as browsers guess the encoding of pages that don't declare one, and falls
+/// back to windows-1252, which decodes any bytes.
+fn detect_encoding(bytes: &[u8]) -> &'static Encoding {
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    detector.guess(None, true)
+}
+
+/// Looks up the encoding named by `encoding`, defaulting to UTF-8.
+fn encoding_for_label(encoding: Option<&str>) -> PyResult<&'static Encoding> {
+    match encoding {
+        Some(label) => Encoding::for_label(label.as_bytes())
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown encoding \"{}\"", label))),
+        None => Ok(encoding_rs::UTF_8),
+    }
+}
+
+/// Decodes `bytes` with the encoding named by `encoding`, defaulting to UTF-8, and
+/// handles bytes that aren't valid in it as `errors` says.
+///
+/// Encoding names are the WHATWG labels understood by browsers, e.g. `"utf-8"`,
+/// `"latin1"`, `"windows-1252"` or `"utf-16le"`. A byte order mark at the start of the
+/// file takes precedence over `encoding`, and is removed, so UTF-16 files with one
+/// are decoded without naming their encoding.
+pub(crate) fn decode(
+    bytes: &[u8],
+    encoding: Option<&str>,
+    errors: DecodeErrors,
+    path: &Path,
+) -> PyResult<String> {
+    let encoding = encoding_for_label(encoding)?;
+    let (encoding, bytes) = match Encoding::for_bom(bytes) {
+        Some((bom_encoding, bom_length)) => (bom_encoding, &bytes[bom_length..]),
+        None => (encoding, bytes),
+    };
+    if let Some(text) = encoding.decode_without_bom_handling_and_without_replacement(bytes) {
+        return Ok(text.into_owned());
+    }
+    let encoding = match errors {
+        DecodeErrors::Error => {
+            return Err(PyValueError::new_err(format!(
+                "{} is not valid {}",
+                path.display(),
+                encoding.name()
+            )))
+        }
+        DecodeErrors::Replace => {
+            tracing::warn!(path = %path.display(), encoding = encoding.name(), "replaced invalid bytes");
+            encoding
+        }
+        DecodeErrors::Detect => {
+            let detected = detect_encoding(bytes);
+            tracing::debug!(path = %path.display(), encoding = detected.name(), "detected encoding");
+            detected
+        }
+    };
+    Ok(encoding.decode_without_bom_handling(bytes).0.into_owned())
+}
+
+/// The number of bytes at the start of a stream that a `DecodingReader` detects the
+/// encoding from.
+const DETECT_SIZE: usize = 1024 * 1024;
+
+/// The number of bytes a `DecodingReader` reads at a time after the first ones.
+const READ_SIZE: usize = 64 * 1024;
+
+/// Reads text in any encoding from `reader` as UTF-8, for loaders that stream a file
+/// rather than decode it in one go with `decode`.
+///
+/// The encoding and byte order mark are handled as in `decode`. Encodings are detected
+/// from the first megabyte only, so with `DecodeErrors::Detect` invalid bytes after it
+/// are replaced with U+FFFD. With `DecodeErrors::Error` invalid bytes fail the read
+/// with an `InvalidData` error, which `decode_read_error` turns into a `ValueError`.
+pub(crate) struct DecodingReader<R> {
+    reader: R,
+    decoder: Decoder,
+    errors: DecodeErrors,
+    name: String,
+    /// Bytes read but not decoded yet.
+    input: Vec<u8>,
+    /// Decoded text that hasn't been returned yet.
+    output: Vec<u8>,
+    /// Whether the whole of `reader` has been read into `input`.
+    done: bool,
+    /// Whether the decoder has been given its last bytes.
+    flushed: bool,
+    warned: bool,
+}
+
+impl<R: Read> DecodingReader<R> {
+    /// Returns a reader decoding `reader`, whose `name` is used in error messages.
+    pub(crate) fn new(
+        mut reader: R,
+        encoding: Option<&str>,
+        errors: DecodeErrors,
+        name: String,
+    ) -> PyResult<Self> {
+        let encoding = encoding_for_label(encoding)?;
+        let mut input = Vec::new();
+        let done = read_up_to(&mut reader, &mut input, DETECT_SIZE)? < DETECT_SIZE;
+        let encoding = match Encoding::for_bom(&input) {
+            Some((bom_encoding, bom_length)) => {
+                input.drain(..bom_length);
+                bom_encoding
+            }
+            None if errors == DecodeErrors::Detect && !is_valid(encoding, &input, done) => {
+                let mut detector = EncodingDetector::new();
+                detector.feed(&input, done);
+                let detected = detector.guess(None, true);
+                tracing::debug!(source = %name, encoding = detected.name(), "detected encoding");
+                detected
+            }
+            None => encoding,
+        };
+        Ok(DecodingReader {
+            reader,
+            decoder: encoding.new_decoder_without_bom_handling(),
+            errors,
+            name,
+            input,
+            output: Vec::new(),
+            done,
+            flushed: false,
+            warned: false,
+        })
+    }
+
+    /// Decodes everything in `input` into `output`.
+    fn decode_input(&mut self) -> io::Result<()> {
+        let last = self.done;
+        let read = if self.errors == DecodeErrors::Error {
+            let length = self
+                .decoder
+                .max_utf8_buffer_length_without_replacement(self.input.len())
+                .ok_or_else(|| io::Error::other("the decoded text is too long"))?;
+            let start = self.output.len();
+            self.output.resize(start + length, 0);
+            let (result, read, written) = self.decoder.decode_to_utf8_without_replacement(
+                &self.input,
+                &mut self.output[start..],
+                last,
+            );
+            self.output.truncate(start + written);
+            if let DecoderResult::Malformed(..) = result {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{} is not valid {}",
+                        self.name,
+                        self.decoder.encoding().name()
+                    ),
+                ));
+            }
+            read
+        } else {
+            let length = self
+                .decoder
+                .max_utf8_buffer_length(self.input.len())
+                .ok_or_else(|| io::Error::other("the decoded text is too long"))?;
+            let start = self.output.len();
+            self.output.resize(start + length, 0);
+            let (_, read, written, replaced) =
+                self.decoder
+                    .decode_to_utf8(&self.input, &mut self.output[start..], last);
+            self.output.truncate(start + written);
+            if replaced && !self.warned {
+                self.warned = true;
+                tracing::warn!(source = %self.name, encoding = self.decoder.encoding().name(), "replaced invalid bytes");
+            }
+            read
+        };
+        self.input.drain(..read);
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecodingReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        while self.output.is_empty() && !self.flushed {
+            if self.input.is_empty() && !self.done {
+                self.done = read_up_to(&mut self.reader, &mut self.input, READ_SIZE)? == 0;
+            }
+            self.decode_input()?;
+            self.flushed = self.done;
+        }
+        let read = self.output.len().min(buffer.len());
+        buffer[..read].copy_from_slice(&self.output[..read]);
+        self.output.drain(..read);
+        Ok(read)
+    }
+}
+
+/// Appends up to `limit` bytes from `reader` to `buffer`, stopping early only at the end
+/// of the reader, and returns how many were read.
+fn read_up_to<R: Read>(reader: &mut R, buffer: &mut Vec<u8>, limit: usize) -> io::Result<usize> {
+    reader.take(limit as u64).read_to_end(buffer)
+}
+
+/// Returns whether `bytes` are valid in `encoding`, allowing a sequence cut off at the
+/// end unless `last` says nothing follows them.
+fn is_valid(encoding: &'static Encoding, bytes: &[u8], last: bool) -> bool {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let length = decoder
+        .max_utf8_buffer_length_without_replacement(bytes.len())
+        .unwrap_or(0);
+    let mut output = vec![0; length];
+    !matches!(
+        decoder
+            .decode_to_utf8_without_replacement(bytes, &mut output, last)
+            .0,
+        DecoderResult::Malformed(..)
+    )
+}
+
+/// Converts an error reading from a `DecodingReader` into a Python exception, raising
+/// a `ValueError` for text that isn't valid in its encoding and an `OSError` otherwise.
+pub(crate) fn decode_read_error(error: io::Error) -> PyErr {
+    if error.kind() == io::ErrorKind::InvalidData {
+        PyValueError::new_err(error.to_string())
+    } else {
+        error.into()
+    }
+}
+
+/// Reads a text file, detecting its encoding when it isn't UTF-8.
+pub(crate) fn read_text_file(path: &Path) -> PyResult<String> {
+    decode(&fs::read(path)?, None, DecodeErrors::Detect, path)
+}
+
+/// The metadata keys the tabular loaders add to every document.
+pub(crate) const LOADER_KEYS: [&str; 2] = ["source", "row"];
+
+/// Raises a `ValueError` when one of the `names` that `loader` copies into metadata is
+/// one of the `LOADER_KEYS`, so that it doesn't silently overwrite them.
+///
+/// `kind` is what the names are, like `"Column"`, and `fix` tells the caller how to
+/// leave them out.
+pub(crate) fn check_loader_keys<'a>(
+    loader: &str,
+    kind: &str,
+    names: impl IntoIterator<Item = &'a str>,
+    fix: &str,
+) -> PyResult<()> {
+    match names.into_iter().find(|name| LOADER_KEYS.contains(name)) {
+        Some(name) => Err(PyValueError::new_err(format!(
+            "{} \"{}\" would overwrite the \"{}\" metadata that {} adds; {}",
+            kind, name, name, loader, fix
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Converts a JSON value into a metadata string.
+///
+/// Strings are used as is, nulls become empty strings, and every other value
+/// (numbers, booleans, arrays, objects) is rendered as JSON.
+pub(crate) fn metadata_value(value: serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(string) => string,
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Builds the `source`, `size` and `mtime` metadata for a file.
+///
+/// `mtime` is the last modification time in whole seconds since the Unix epoch.
+pub(crate) fn file_metadata(path: &Path) -> PyResult<HashMap<String, String>> {
+    let file_metadata = fs::metadata(path)?;
+    let mut metadata = HashMap::new();
+    metadata.insert("source".to_string(), path.display().to_string());
+    metadata.insert("size".to_string(), file_metadata.len().to_string());
+    if let Ok(modified) = file_metadata.modified() {
+        if let Ok(since_epoch) = modified.duration_since(UNIX_EPOCH) {
+            metadata.insert("mtime".to_string(), since_epoch.as_secs().to_string());
+        }
+    }
+    Ok(metadata)
+}
+
+/// Reads a text file into a Document.
+pub(crate) fn load_text_file(
+    path: &Path,
+    encoding: Option<&str>,
+    errors: DecodeErrors,
+) -> PyResult<Document> {
+    let bytes = fs::read(path)?;
+    Ok(Document {
+        page_content: decode(&bytes, encoding, errors, path)?,
+        metadata: file_metadata(path)?,
+    })
+}
+
+/// Reads many text files into Documents in parallel.
+///
+/// Each document has `source`, `size` and `mtime` metadata, like `Document.from_file`.
+///
+/// # Arguments
+///
+/// * `paths` - The paths of the files to read.
+/// * `encoding` - The encoding of the files. Defaults to UTF-8.
+/// * `errors` - What to do with a file that isn't valid in `encoding`: `"detect"` its
+///   encoding (latin-1, windows-1252, Shift_JIS, ...) and decode it with that,
+///   `"replace"` the invalid bytes with U+FFFD, or raise an `"error"`.
+#[pyfunction]
+#[pyo3(signature = (paths, encoding = None, errors = "detect"))]
+pub fn load_text_files(
+    py: Python,
+    paths: Vec<PathBuf>,
+    encoding: Option<&str>,
+    errors: &str,
+) -> PyResult<Vec<Document>> {
+    let errors = errors.parse::<DecodeErrors>()?;
+    py.allow_threads(|| {
+        paths
+            .par_iter()
+            .map(|path| load_text_file(path, encoding, errors))
+            .collect()
+    })
+}
+
+/// An awaitable version of `load_text_files`, with the same arguments.
+///
+/// The files are read on the rayon pool while the event loop keeps running. Awaiting
+/// the result gives the Documents in the same order as `paths`.
+#[pyfunction]
+#[pyo3(signature = (paths, encoding = None, errors = "detect"))]
+pub fn load_text_files_async<'py>(
+    py: Python<'py>,
+    paths: Vec<PathBuf>,
+    encoding: Option<String>,
+    errors: &str,
+) -> PyResult<&'py PyAny> {
+    let errors = errors.parse::<DecodeErrors>()?;
+    spawn_awaitable(
+        py,
+        Work::Compute,
+        move || {
+            paths
+                .par_iter()
+                .map(|path| load_text_file(path, encoding.as_deref(), errors))
+                .collect::<PyResult<Vec<Document>>>()
+        },
+        |py, documents| Ok(documents.into_py(py)),
+    )
+}
@@ -0,0 +1,309 @@
+use crate::Error;
+use pyo3::exceptions::PyRuntimeWarning;
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+/// The environment variable read when the module is imported, with the same values as
+/// `set_log_level`.
+const LOG_ENV_VAR: &str = "RS_DOCUMENT_LOG";
+
+/// The most verbose level that is forwarded to Python, as `LogLevel as u8`.
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Off as u8);
+
+/// The most records that wait for the GIL, after which new ones are dropped.
+const MAX_PENDING: usize = 10_000;
+
+/// Records made on threads that don't hold the GIL, like rayon's worker threads, waiting
+/// for the next record made on a thread that does.
+static PENDING: Mutex<Vec<(i32, String)>> = Mutex::new(Vec::new());
+
+/// The number of records dropped since the last ones were passed to Python because
+/// `PENDING` was full.
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+/// The `rs_document` Python logger.
+static LOGGER: GILOnceCell<PyObject> = GILOnceCell::new();
+
+/// How much of the tracing output is forwarded to Python's `logging`, from nothing to
+/// everything.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum LogLevel {
+    Off,
+    Error,
+    Warning,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl FromStr for LogLevel {
+    type Err = Error;
+
+    fn from_str(level: &str) -> Result<Self, Self::Err> {
+        match level.to_ascii_lowercase().as_str() {
+            "off" | "" => Ok(LogLevel::Off),
+            "error" => Ok(LogLevel::Error),
+            "warn" | "warning" => Ok(LogLevel::Warning),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            _ => Err(Error::InvalidArgument(format!(
+                "Unknown log level \"{}\", expected \"off\", \"error\", \"warning\", \"info\", \
+                 \"debug\" or \"trace\"",
+                level
+            ))),
+        }
+    }
+}
+
+impl LogLevel {
+    fn current() -> Self {
+        match LOG_LEVEL.load(Ordering::Relaxed) {
+            1 => LogLevel::Error,
+            2 => LogLevel::Warning,
+            3 => LogLevel::Info,
+            4 => LogLevel::Debug,
+            5 => LogLevel::Trace,
+            _ => LogLevel::Off,
+        }
+    }
+
+    fn of(level: &Level) -> Self {
+        match *level {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN => LogLevel::Warning,
+            Level::INFO => LogLevel::Info,
+            Level::DEBUG => LogLevel::Debug,
+            Level::TRACE => LogLevel::Trace,
+        }
+    }
+
+    /// The matching level number of Python's `logging`, with 5 for trace, which it has
+    /// no name for.
+    fn python_level(self) -> i32 {
+        match self {
+            LogLevel::Off | LogLevel::Error => 40,
+            LogLevel::Warning => 30,
+            LogLevel::Info => 20,
+            LogLevel::Debug => 10,
+            LogLevel::Trace => 5,
+        }
+    }
+}
+
+fn enabled(metadata: &Metadata) -> bool {
+    LogLevel::of(metadata.level()) <= LogLevel::current()
+}
+
+/// The message and the other fields of a span or event, as ` key=value` pairs.
+#[derive(Default)]
+struct Fields {
+    message: String,
+    pairs: String,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{}", value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.pairs, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// When a span was created and its fields, kept in the span's extensions until it closes.
+struct SpanTiming {
+    start: Instant,
+    fields: Fields,
+}
+
+/// Passes a record to the `rs_document` Python logger, after the records that were
+/// waiting for the GIL.
+///
+/// Taking the GIL on a thread that doesn't hold it could deadlock with a thread that holds
+/// it while waiting for this one, so those records wait until a thread that already
+/// holds the GIL logs something or calls `flush`. Every batch function logs when its
+/// span closes, after its parallel section is done. At most `MAX_PENDING` records wait,
+/// so that trace logging from large batches doesn't use up memory, and the rest are
+/// dropped and counted in a warning.
+fn emit(level: i32, message: String) {
+    // SAFETY: The bridge is only installed once the module is imported, so the
+    // interpreter is running.
+    if unsafe { pyo3::ffi::PyGILState_Check() } == 0 {
+        let mut pending = PENDING.lock().unwrap();
+        if pending.len() < MAX_PENDING {
+            pending.push((level, message));
+        } else {
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+        return;
+    }
+    Python::with_gil(|py| log(py, Some((level, message))));
+}
+
+/// Passes the records that are waiting for the GIL to the `rs_document` Python logger.
+pub(crate) fn flush(py: Python) {
+    log(py, None);
+}
+
+fn log(py: Python, record: Option<(i32, String)>) {
+    let mut pending = std::mem::take(&mut *PENDING.lock().unwrap());
+    let dropped = DROPPED.swap(0, Ordering::Relaxed);
+    if dropped > 0 {
+        pending.push((
+            LogLevel::Warning.python_level(),
+            format!(
+                "dropped {} records logged while the GIL was released, after the first {}",
+                dropped, MAX_PENDING
+            ),
+        ));
+    }
+    if pending.is_empty() && record.is_none() {
+        return;
+    }
+    let logger = LOGGER.get_or_try_init(py, || -> PyResult<PyObject> {
+        let logging = py.import("logging")?;
+        Ok(logging.call_method1("getLogger", ("rs_document",))?.into())
+    });
+    let Ok(logger) = logger else { return };
+    for (level, message) in pending.into_iter().chain(record) {
+        // A failing handler shouldn't fail the call that logged.
+        let _ = logger.call_method1(py, "log", (level, message));
+    }
+}
+
+/// A tracing layer that forwards events, and the duration of spans when they close, to
+/// Python's `logging`.
+struct PythonLogging;
+
+impl<S> Layer<S> for PythonLogging
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if enabled(metadata) {
+            Interest::always()
+        } else {
+            Interest::never()
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+        enabled(metadata)
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(SpanTiming {
+            start: Instant::now(),
+            fields,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut extensions = span.extensions_mut();
+        if let Some(timing) = extensions.get_mut::<SpanTiming>() {
+            values.record(&mut timing.fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let scope = ctx
+            .event_scope(event)
+            .map(|scope| {
+                let names: Vec<&str> = scope.from_root().map(|span| span.name()).collect();
+                format!("{}: ", names.join(":"))
+            })
+            .unwrap_or_default();
+        let level = LogLevel::of(event.metadata().level()).python_level();
+        emit(
+            level,
+            format!("{}{}{}", scope, fields.message, fields.pairs),
+        );
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let extensions = span.extensions();
+        let Some(timing) = extensions.get::<SpanTiming>() else {
+            return;
+        };
+        let level = LogLevel::of(span.metadata().level()).python_level();
+        emit(
+            level,
+            format!(
+                "{} took {:.3} ms{}",
+                span.name(),
+                timing.start.elapsed().as_secs_f64() * 1000.0,
+                timing.fields.pairs
+            ),
+        );
+    }
+}
+
+/// Sets the level from `RS_DOCUMENT_LOG` and installs the bridge to Python's `logging`
+/// as the global tracing subscriber, unless another one is installed already.
+///
+/// An unknown level in `RS_DOCUMENT_LOG` only raises a `RuntimeWarning` and leaves
+/// logging off, so that a typo doesn't stop the module from being imported.
+pub fn install(py: Python) {
+    if let Ok(level) = std::env::var(LOG_ENV_VAR) {
+        match level.parse::<LogLevel>() {
+            Ok(level) => LOG_LEVEL.store(level as u8, Ordering::Relaxed),
+            Err(error) => {
+                let message = format!("Ignoring {}: {}", LOG_ENV_VAR, error);
+                // With warnings turned into errors, the import still goes ahead.
+                let _ = PyErr::warn(py, py.get_type::<PyRuntimeWarning>(), &message, 1);
+            }
+        }
+    }
+    let subscriber = tracing_subscriber::registry().with(PythonLogging);
+    let _ = tracing::subscriber::set_global_default(subscriber);
+}
+
+/// Sets how much of the module's tracing output goes to the `rs_document` logger of
+/// Python's `logging`.
+///
+/// At `"info"`, every batch function logs how long it took and how many documents it
+/// handled. `"debug"` adds the stages of the batch functions, and `"trace"` adds every
+/// cleaner and splitter run on every document, which is slow for large batches. Trace
+/// records use level 5, which `logging` has no name for. Logging is off by default, or
+/// set by the `RS_DOCUMENT_LOG` environment variable when the module is imported.
+///
+/// Records only show up once the logger's level and handlers let them through, as with
+/// `logging.basicConfig(level=5)`.
+///
+/// # Arguments
+///
+/// * `level` - `"off"`, `"error"`, `"warning"`, `"info"`, `"debug"` or `"trace"`. `None`
+///   turns logging off.
+#[pyfunction]
+#[pyo3(signature = (level))]
+pub fn set_log_level(level: Option<&str>) -> PyResult<()> {
+    let level = level.map_or(Ok(LogLevel::Off), str::parse::<LogLevel>)?;
+    LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+    // Call sites cache whether they are enabled, so they have to be asked again.
+    tracing::callsite::rebuild_interest_cache();
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use crate::loaders::{file_metadata, metadata_value, read_text_file};
+use crate::Document;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Parses YAML frontmatter into metadata.
+fn parse_yaml(frontmatter: &str) -> PyResult<HashMap<String, String>> {
+    let to_error =
+        |error: String| PyValueError::new_err(format!("Invalid YAML frontmatter: {}", error));
+    let value: serde_yaml::Value =
+        serde_yaml::from_str(frontmatter).map_err(|error| to_error(error.to_string()))?;
+    if value.is_null() {
+        return Ok(HashMap::new());
+    }
+    let mapping: serde_json::Map<String, serde_json::Value> = serde_json::from_value(
+        serde_json::to_value(value).map_err(|error| to_error(error.to_string()))?,
+    )
+    .map_err(|_| to_error("frontmatter must be a mapping".to_string()))?;
+    Ok(mapping
+        .into_iter()
+        .map(|(key, value)| (key, metadata_value(value)))
+        .collect())
+}
+
+/// Parses TOML frontmatter into metadata.
+fn parse_toml(frontmatter: &str) -> PyResult<HashMap<String, String>> {
+    let table: toml::Table = frontmatter
+        .parse()
+        .map_err(|error| PyValueError::new_err(format!("Invalid TOML frontmatter: {}", error)))?;
+    Ok(table
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                toml::Value::Datetime(datetime) => datetime.to_string(),
+                other => serde_json::to_value(other)
+                    .map(metadata_value)
+                    .unwrap_or_default(),
+            };
+            (key, value)
+        })
+        .collect())
+}
+
+/// Splits a markdown document into its frontmatter metadata and its body.
+///
+/// YAML frontmatter is fenced by `---` lines and TOML frontmatter by `+++` lines, and
+/// either must start on the first line of the document. Documents without frontmatter
+/// are returned unchanged with empty metadata.
+fn split_frontmatter(text: &str) -> PyResult<(HashMap<String, String>, String)> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut lines = text.split_inclusive('\n');
+    let fence = match lines.next().map(str::trim_end) {
+        Some("---") => "---",
+        Some("+++") => "+++",
+        _ => return Ok((HashMap::new(), text.to_string())),
+    };
+
+    let mut frontmatter = String::new();
+    let mut closed = false;
+    for line in lines.by_ref() {
+        let trimmed = line.trim_end();
+        if trimmed == fence || (fence == "---" && trimmed == "...") {
+            closed = true;
+            break;
+        }
+        frontmatter.push_str(line);
+    }
+    if !closed {
+        return Ok((HashMap::new(), text.to_string()));
+    }
+
+    let metadata = if fence == "---" {
+        parse_yaml(&frontmatter)?
+    } else {
+        parse_toml(&frontmatter)?
+    };
+    let body: String = lines.collect();
+    Ok((metadata, body.trim_start_matches(['\r', '\n']).to_string()))
+}
+
+/// Loads a markdown file into a Document, moving its frontmatter into metadata.
+pub(crate) fn load_markdown_file(path: &Path) -> PyResult<Document> {
+    let text = read_text_file(path)?;
+    let (frontmatter, body) = split_frontmatter(&text)?;
+    let mut metadata = file_metadata(path)?;
+    metadata.extend(frontmatter);
+    Ok(Document {
+        page_content: body,
+        metadata,
+    })
+}
+
+/// Loads a markdown file, parsing its YAML (`---`) or TOML (`+++`) frontmatter into
+/// metadata and using the rest of the file as page_content.
+///
+/// Frontmatter values that aren't strings are stored as JSON. The document also gets
+/// `source`, `size` and `mtime` metadata, which frontmatter keys take precedence over.
+///
+/// # Arguments
+///
+/// * `path` - The path of the markdown file.
+#[pyfunction]
+pub fn load_markdown(py: Python, path: PathBuf) -> PyResult<Document> {
+    py.allow_threads(|| load_markdown_file(&path))
+}
@@ -0,0 +1,87 @@
+use crate::document::render_template;
+use crate::{extract_documents, Document};
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// The changes `transform_metadata` makes to the metadata of every document.
+struct MetadataTransform {
+    rename: HashMap<String, String>,
+    add: HashMap<String, String>,
+    drop: Vec<String>,
+    compute: HashMap<String, String>,
+}
+
+impl MetadataTransform {
+    /// Renames keys, adds the constant values, renders the computed values and drops
+    /// keys, in that order.
+    ///
+    /// Every computed value is rendered from the metadata as it is after renaming and
+    /// adding, so computed keys can't refer to each other.
+    fn apply(&self, document: &mut Document) {
+        let metadata = &mut document.metadata;
+        // Take every renamed value out first, so that renames like a -> b and b -> a
+        // swap the values instead of depending on the order they run in.
+        let renamed: Vec<(String, String)> = self
+            .rename
+            .iter()
+            .filter_map(|(from, to)| Some((to.clone(), metadata.remove(from)?)))
+            .collect();
+        metadata.extend(renamed);
+        for (key, value) in &self.add {
+            metadata.insert(key.clone(), value.clone());
+        }
+        let computed: Vec<(String, String)> = self
+            .compute
+            .iter()
+            .map(|(key, template)| (key.clone(), render_template(template, metadata)))
+            .collect();
+        metadata.extend(computed);
+        for key in &self.drop {
+            metadata.remove(key);
+        }
+    }
+}
+
+/// Rewrites the metadata of every document, in parallel.
+///
+/// The steps run in this order, so a computed key can use a renamed or added key, and a
+/// key can be used by a template and then dropped.
+///
+/// # Arguments
+///
+/// * `docs` - The documents whose metadata to rewrite.
+/// * `rename` - A dictionary from old key to new key. Missing keys are skipped.
+/// * `add` - Constant values to set on every document, replacing existing values.
+/// * `compute` - A dictionary from key to a template such as `"{source}#{page}"`, with
+///   `{key}` placeholders rendered like `Document.add_context_header`.
+/// * `drop` - Keys to remove.
+///
+/// # Returns
+///
+/// Copies of the documents with the new metadata.
+#[pyfunction]
+#[pyo3(signature = (docs, rename = None, add = None, drop = None, compute = None))]
+pub fn transform_metadata(
+    py: Python,
+    docs: &PyAny,
+    rename: Option<HashMap<String, String>>,
+    add: Option<HashMap<String, String>>,
+    drop: Option<Vec<String>>,
+    compute: Option<HashMap<String, String>>,
+) -> PyResult<Vec<Document>> {
+    let transform = MetadataTransform {
+        rename: rename.unwrap_or_default(),
+        add: add.unwrap_or_default(),
+        drop: drop.unwrap_or_default(),
+        compute: compute.unwrap_or_default(),
+    };
+    let mut doc_vec: Vec<Document> = extract_documents(docs)?;
+    let _span = tracing::info_span!("transform_metadata", docs = doc_vec.len()).entered();
+    py.allow_threads(|| {
+        doc_vec
+            .par_iter_mut()
+            .for_each(|document| transform.apply(document))
+    });
+    Ok(doc_vec)
+}
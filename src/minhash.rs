@@ -0,0 +1,245 @@
+use crate::{extract_documents, Document};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// The Mersenne prime 2^61 - 1 used as the modulus for the permutation hashes.
+const MERSENNE_PRIME: u64 = (1 << 61) - 1;
+
+/// A deterministic `splitmix64` step, used to derive permutation coefficients so that
+/// signatures are reproducible between runs.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Builds the set of hashed word shingles for `text`.
+///
+/// Text is lowercased and split on whitespace. Documents with fewer words than
+/// `shingle_size` produce a single shingle made of all of their words.
+fn shingles(text: &str, shingle_size: usize) -> HashSet<u64> {
+    let lowered = text.to_lowercase();
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    let hash_words = |window: &[&str]| {
+        let mut hasher = DefaultHasher::new();
+        window.hash(&mut hasher);
+        hasher.finish() & MERSENNE_PRIME
+    };
+
+    if words.len() <= shingle_size {
+        let mut set = HashSet::new();
+        set.insert(hash_words(&words));
+        return set;
+    }
+    words.windows(shingle_size).map(hash_words).collect()
+}
+
+/// Computes MinHash signatures and groups them into LSH bands.
+struct MinHasher {
+    permutations: Vec<(u64, u64)>,
+    bands: usize,
+    rows: usize,
+}
+
+impl MinHasher {
+    fn new(num_perm: usize, threshold: f64) -> Self {
+        let mut state = 0x5EED_u64;
+        let permutations = (0..num_perm)
+            .map(|_| {
+                let a = splitmix64(&mut state) % (MERSENNE_PRIME - 1) + 1;
+                let b = splitmix64(&mut state) % MERSENNE_PRIME;
+                (a, b)
+            })
+            .collect();
+        let (bands, rows) = MinHasher::band_layout(num_perm, threshold);
+        MinHasher {
+            permutations,
+            bands,
+            rows,
+        }
+    }
+
+    /// Picks the number of bands and rows per band whose LSH threshold,
+    /// `(1 / bands) ^ (1 / rows)`, is closest to the requested similarity threshold.
+    fn band_layout(num_perm: usize, threshold: f64) -> (usize, usize) {
+        let mut best = (1, num_perm);
+        let mut best_error = f64::MAX;
+        for bands in 1..=num_perm {
+            for rows in 1..=(num_perm / bands) {
+                let lsh_threshold = (1.0 / bands as f64).powf(1.0 / rows as f64);
+                let error = (lsh_threshold - threshold).abs();
+                if error < best_error {
+                    best = (bands, rows);
+                    best_error = error;
+                }
+            }
+        }
+        best
+    }
+
+    fn signature(&self, shingles: &HashSet<u64>) -> Vec<u64> {
+        self.permutations
+            .iter()
+            .map(|&(a, b)| {
+                shingles
+                    .iter()
+                    .map(|&shingle| {
+                        ((a as u128 * shingle as u128 + b as u128) % MERSENNE_PRIME as u128) as u64
+                    })
+                    .min()
+                    .unwrap_or(u64::MAX)
+            })
+            .collect()
+    }
+
+    /// Hashes each band of a signature so that documents sharing a band land in the
+    /// same bucket.
+    fn band_hashes(&self, signature: &[u64]) -> Vec<u64> {
+        (0..self.bands)
+            .map(|band| {
+                let mut hasher = DefaultHasher::new();
+                band.hash(&mut hasher);
+                signature[band * self.rows..(band + 1) * self.rows].hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect()
+    }
+}
+
+/// Estimates the Jaccard similarity of two documents from their signatures.
+fn estimated_similarity(a: &[u64], b: &[u64]) -> f64 {
+    let matching = a.iter().zip(b).filter(|(x, y)| x == y).count();
+    matching as f64 / a.len() as f64
+}
+
+/// Finds the indices of documents that are near duplicates of an earlier document.
+///
+/// Candidate pairs are found with MinHash LSH and then confirmed by comparing the
+/// full signatures against `threshold`. A document is only dropped when it is similar
+/// to an earlier document that was itself kept, so long chains of slightly different
+/// documents do not collapse into a single survivor.
+fn near_duplicate_indices(
+    texts: &[&str],
+    threshold: f64,
+    num_perm: usize,
+    shingle_size: usize,
+) -> Vec<usize> {
+    let hasher = MinHasher::new(num_perm, threshold);
+    let signatures: Vec<Vec<u64>> = texts
+        .par_iter()
+        .map(|text| hasher.signature(&shingles(text, shingle_size)))
+        .collect();
+    let band_hashes: Vec<Vec<u64>> = signatures
+        .par_iter()
+        .map(|signature| hasher.band_hashes(signature))
+        .collect();
+
+    let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+    for (index, hashes) in band_hashes.iter().enumerate() {
+        for (band, hash) in hashes.iter().enumerate() {
+            buckets.entry((band, *hash)).or_default().push(index);
+        }
+    }
+
+    let mut candidates: HashSet<(usize, usize)> = HashSet::new();
+    for bucket in buckets.values() {
+        for (position, &later) in bucket.iter().enumerate() {
+            for &earlier in &bucket[..position] {
+                candidates.insert((earlier, later));
+            }
+        }
+    }
+
+    let mut confirmed: Vec<(usize, usize)> = candidates
+        .into_par_iter()
+        .filter(|&(earlier, later)| {
+            estimated_similarity(&signatures[earlier], &signatures[later]) >= threshold
+        })
+        .collect();
+    confirmed.sort_unstable_by_key(|&(earlier, later)| (later, earlier));
+
+    let mut dropped = vec![false; texts.len()];
+    for (earlier, later) in confirmed {
+        if !dropped[earlier] {
+            dropped[later] = true;
+        }
+    }
+    dropped
+        .iter()
+        .enumerate()
+        .filter(|(_, &is_dropped)| is_dropped)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Removes near duplicate documents from a list using MinHash locality sensitive hashing.
+///
+/// Each document's page_content is broken into word shingles, and documents whose
+/// estimated Jaccard similarity is at or above `threshold` are treated as duplicates.
+/// The first occurrence is always kept.
+///
+/// # Arguments
+///
+/// * `docs` - The documents to deduplicate.
+/// * `threshold` - The Jaccard similarity, between 0 and 1, at which documents are
+///   considered duplicates.
+/// * `num_perm` - The number of permutations in each MinHash signature. More
+///   permutations are more accurate but slower.
+/// * `shingle_size` - The number of words in each shingle.
+///
+/// # Returns
+///
+/// A tuple of the surviving documents (in their original order) and the indices of
+/// the documents that were dropped.
+#[pyfunction]
+#[pyo3(signature = (docs, threshold = 0.9, num_perm = 128, shingle_size = 3))]
+pub fn near_dedupe_docs(
+    py: Python,
+    docs: &PyAny,
+    threshold: f64,
+    num_perm: usize,
+    shingle_size: usize,
+) -> PyResult<(Vec<Document>, Vec<usize>)> {
+    if !(threshold > 0.0 && threshold <= 1.0) {
+        return Err(PyValueError::new_err(
+            "threshold must be greater than 0 and at most 1",
+        ));
+    }
+    if num_perm == 0 || shingle_size == 0 {
+        return Err(PyValueError::new_err(
+            "num_perm and shingle_size must be greater than 0",
+        ));
+    }
+    let doc_vec: Vec<Document> = extract_documents(docs)?;
+    let span = tracing::info_span!(
+        "near_dedupe_docs",
+        docs = doc_vec.len(),
+        dropped = tracing::field::Empty
+    )
+    .entered();
+
+    let result = py.allow_threads(|| {
+        let texts: Vec<&str> = doc_vec
+            .iter()
+            .map(|doc| doc.page_content.as_str())
+            .collect();
+        let dropped = near_duplicate_indices(&texts, threshold, num_perm, shingle_size);
+        let dropped_set: HashSet<usize> = dropped.iter().copied().collect();
+        let survivors: Vec<Document> = doc_vec
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !dropped_set.contains(index))
+            .map(|(_, document)| document.clone())
+            .collect();
+        (survivors, dropped)
+    });
+
+    span.record("dropped", result.1.len());
+    Ok(result)
+}
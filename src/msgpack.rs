@@ -0,0 +1,41 @@
+use crate::{extract_documents, Document};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Serializes a value to MessagePack, using maps with field names so that the output can
+/// be read by MessagePack libraries in other languages.
+pub(crate) fn encode<T: Serialize + ?Sized>(value: &T) -> PyResult<Vec<u8>> {
+    rmp_serde::to_vec_named(value).map_err(|error| PyValueError::new_err(error.to_string()))
+}
+
+/// Deserializes a value from MessagePack.
+pub(crate) fn decode<T: DeserializeOwned>(data: &[u8]) -> PyResult<T> {
+    rmp_serde::from_slice(data)
+        .map_err(|error| PyValueError::new_err(format!("Invalid MessagePack data: {}", error)))
+}
+
+/// Serializes documents to MessagePack bytes, as an array of
+/// `{"page_content": ..., "metadata": {...}}` maps.
+///
+/// # Arguments
+///
+/// * `docs` - The documents to serialize.
+#[pyfunction]
+pub fn docs_to_msgpack(py: Python, docs: &PyAny) -> PyResult<PyObject> {
+    let doc_vec: Vec<Document> = extract_documents(docs)?;
+    let data = py.allow_threads(|| encode(&doc_vec))?;
+    Ok(PyBytes::new(py, &data).into())
+}
+
+/// Deserializes documents from MessagePack bytes written by `docs_to_msgpack`.
+///
+/// # Arguments
+///
+/// * `data` - The MessagePack bytes.
+#[pyfunction]
+pub fn docs_from_msgpack(py: Python, data: &[u8]) -> PyResult<Vec<Document>> {
+    py.allow_threads(|| decode(data))
+}
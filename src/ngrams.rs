@@ -0,0 +1,106 @@
+use crate::keywords::tokenize;
+use crate::{extract_documents, Document};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// The n-gram sizes to count: a single `n`, or an inclusive `(min_n, max_n)` range.
+#[derive(FromPyObject)]
+pub enum NgramSizes {
+    Single(usize),
+    Range(usize, usize),
+}
+
+impl NgramSizes {
+    fn bounds(&self) -> PyResult<(usize, usize)> {
+        let (min_n, max_n) = match *self {
+            NgramSizes::Single(n) => (n, n),
+            NgramSizes::Range(min_n, max_n) => (min_n, max_n),
+        };
+        if min_n == 0 || min_n > max_n {
+            return Err(PyValueError::new_err(format!(
+                "Invalid n-gram size {:?}, expected n >= 1 or (min_n, max_n) with 1 <= min_n <= max_n",
+                (min_n, max_n)
+            )));
+        }
+        Ok((min_n, max_n))
+    }
+}
+
+/// Counts the n-grams of `min_n` to `max_n` lowercase words in a document.
+///
+/// N-grams never span punctuation, so the last words of one sentence and the first words
+/// of the next aren't counted as a phrase.
+fn document_ngrams(
+    document: &Document,
+    min_n: usize,
+    max_n: usize,
+    per_document: bool,
+) -> HashMap<String, usize> {
+    let tokens = tokenize(&document.page_content);
+    let mut counts = HashMap::new();
+    let mut seen = HashSet::new();
+    for phrase in tokens.split(Option::is_none) {
+        let words: Vec<&str> = phrase.iter().flatten().map(String::as_str).collect();
+        for n in min_n..=max_n.min(words.len()) {
+            for window in words.windows(n) {
+                let ngram = window.join(" ");
+                if per_document && !seen.insert(ngram.clone()) {
+                    continue;
+                }
+                *counts.entry(ngram).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Counts the most frequent words and phrases across a corpus, in parallel.
+///
+/// Stopwords are counted like any other word, which makes this useful for building
+/// stopword lists, and with `per_document=True` for spotting boilerplate that repeats
+/// across pages.
+///
+/// # Arguments
+///
+/// * `docs` - The documents to count.
+/// * `n` - The number of words per n-gram, or an inclusive `(min_n, max_n)` range.
+/// * `top_k` - How many of the most frequent n-grams to return.
+/// * `per_document` - Count the number of documents each n-gram appears in, rather
+///   than every occurrence.
+///
+/// # Returns
+///
+/// A list of `(ngram, count)` tuples, most frequent first, with ties broken
+/// alphabetically.
+#[pyfunction]
+#[pyo3(signature = (docs, n = NgramSizes::Single(1), top_k = 100, per_document = false))]
+pub fn ngram_counts(
+    py: Python,
+    docs: &PyAny,
+    n: NgramSizes,
+    top_k: usize,
+    per_document: bool,
+) -> PyResult<Vec<(String, usize)>> {
+    let (min_n, max_n) = n.bounds()?;
+    let doc_vec: Vec<Document> = extract_documents(docs)?;
+
+    let result = py.allow_threads(|| {
+        let counts = doc_vec
+            .par_iter()
+            .map(|document| document_ngrams(document, min_n, max_n, per_document))
+            .reduce(HashMap::new, |a, b| {
+                let (mut a, b) = if a.len() < b.len() { (b, a) } else { (a, b) };
+                for (ngram, count) in b {
+                    *a.entry(ngram).or_insert(0) += count;
+                }
+                a
+            });
+        let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts.truncate(top_k);
+        counts
+    });
+    Ok(result)
+}
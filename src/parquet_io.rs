@@ -0,0 +1,231 @@
+use crate::arrow_io::documents_to_record_batch;
+use crate::loaders::check_loader_keys;
+use crate::{extract_documents, Document};
+use arrow::array::{Array, ArrayRef, StringArray};
+use arrow::compute::{can_cast_types, cast};
+use arrow::datatypes::DataType;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::{ArrowWriter, ProjectionMask};
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// Converts an Arrow or Parquet error into a Python `ValueError`.
+fn to_py_error<E: std::fmt::Display>(error: E) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+/// Casts any Arrow column to strings so that it can be stored in a Document.
+fn string_column(column: &ArrayRef) -> PyResult<StringArray> {
+    let strings = cast(column, &DataType::Utf8).map_err(to_py_error)?;
+    Ok(strings
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .expect("casting to Utf8 produces a StringArray")
+        .clone())
+}
+
+/// Reads a single row group of a Parquet file into Documents.
+///
+/// `first_row` is the number of rows in the row groups before this one, so that the
+/// `row` metadata is the position of the row in the whole file.
+fn read_row_group(
+    path: &Path,
+    row_group: usize,
+    first_row: usize,
+    content_column: &str,
+    metadata_columns: &[String],
+) -> PyResult<Vec<Document>> {
+    let builder =
+        ParquetRecordBatchReaderBuilder::try_new(File::open(path)?).map_err(to_py_error)?;
+    let wanted: Vec<usize> = builder
+        .schema()
+        .fields()
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| {
+            let name = field.name();
+            name == content_column || metadata_columns.contains(name)
+        })
+        .map(|(index, _)| index)
+        .collect();
+    let projection = ProjectionMask::roots(builder.parquet_schema(), wanted);
+    let reader = builder
+        .with_row_groups(vec![row_group])
+        .with_projection(projection)
+        .build()
+        .map_err(to_py_error)?;
+
+    let source = path.display().to_string();
+    let mut documents = Vec::new();
+    for batch in reader {
+        let batch = batch.map_err(to_py_error)?;
+        let content = match batch.column_by_name(content_column) {
+            Some(column) => string_column(column)?,
+            None => {
+                return Err(PyValueError::new_err(format!(
+                    "Column \"{}\" is not in the Parquet file",
+                    content_column
+                )))
+            }
+        };
+        let metadata: Vec<(&String, StringArray)> = metadata_columns
+            .iter()
+            .map(|name| match batch.column_by_name(name) {
+                Some(column) => Ok((name, string_column(column)?)),
+                None => Err(PyValueError::new_err(format!(
+                    "Column \"{}\" is not in the Parquet file",
+                    name
+                ))),
+            })
+            .collect::<PyResult<_>>()?;
+
+        for row in 0..batch.num_rows() {
+            let page_content = if content.is_null(row) {
+                String::new()
+            } else {
+                content.value(row).to_string()
+            };
+            let mut row_metadata: HashMap<String, String> = metadata
+                .iter()
+                .filter(|(_, values)| !values.is_null(row))
+                .map(|(name, values)| (name.to_string(), values.value(row).to_string()))
+                .collect();
+            row_metadata.insert("source".to_string(), source.clone());
+            row_metadata.insert("row".to_string(), (first_row + documents.len()).to_string());
+            documents.push(Document {
+                page_content,
+                metadata: row_metadata,
+            });
+        }
+    }
+    Ok(documents)
+}
+
+/// Loads a Parquet file, turning every row into a Document.
+///
+/// Row groups are read in parallel. Columns that aren't strings are cast to strings, and
+/// null metadata values are left out of the metadata. Columns that can't be cast, like
+/// structs and maps, are left out unless they are in `metadata_columns`, where they are
+/// an error. Each document also gets the `source` path and the zero based `row` number,
+/// and a metadata column with either name is an error rather than being overwritten.
+///
+/// # Arguments
+///
+/// * `path` - The path of the Parquet file.
+/// * `content_column` - The column that becomes the page_content.
+/// * `metadata_columns` - The columns copied into metadata. Defaults to every column
+///   other than `content_column`.
+#[pyfunction]
+#[pyo3(signature = (path, content_column = "text", metadata_columns = None))]
+pub fn load_parquet(
+    py: Python,
+    path: PathBuf,
+    content_column: &str,
+    metadata_columns: Option<Vec<String>>,
+) -> PyResult<Vec<Document>> {
+    py.allow_threads(|| {
+        let builder =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(&path)?).map_err(to_py_error)?;
+        let schema = builder.schema();
+        let metadata_columns = match metadata_columns {
+            Some(names) => names,
+            // Columns like structs and maps can't be cast to strings, so they are only
+            // read when they are asked for.
+            None => schema
+                .fields()
+                .iter()
+                .filter(|field| field.name() != content_column)
+                .filter(|field| {
+                    let castable = can_cast_types(field.data_type(), &DataType::Utf8);
+                    if !castable {
+                        tracing::debug!(column = %field.name(), "skipping column that isn't castable to a string");
+                    }
+                    castable
+                })
+                .map(|field| field.name().clone())
+                .collect(),
+        };
+        check_loader_keys(
+            "load_parquet",
+            "Column",
+            metadata_columns.iter().map(String::as_str),
+            "leave it out of metadata_columns or rename it",
+        )?;
+        for name in metadata_columns.iter().map(String::as_str).chain([content_column]) {
+            if let Ok(field) = schema.field_with_name(name) {
+                if !can_cast_types(field.data_type(), &DataType::Utf8) {
+                    return Err(PyValueError::new_err(format!(
+                        "Column \"{}\" has type {}, which can't be stored as a string",
+                        name,
+                        field.data_type()
+                    )));
+                }
+            }
+        }
+        let row_counts: Vec<usize> = builder
+            .metadata()
+            .row_groups()
+            .iter()
+            .map(|row_group| row_group.num_rows() as usize)
+            .collect();
+        let first_rows: Vec<usize> = row_counts
+            .iter()
+            .scan(0, |total, &count| {
+                let first_row = *total;
+                *total += count;
+                Some(first_row)
+            })
+            .collect();
+
+        let row_groups: Vec<Vec<Document>> = first_rows
+            .par_iter()
+            .enumerate()
+            .map(|(row_group, &first_row)| {
+                read_row_group(
+                    &path,
+                    row_group,
+                    first_row,
+                    content_column,
+                    &metadata_columns,
+                )
+            })
+            .collect::<PyResult<_>>()?;
+        Ok(row_groups.into_iter().flatten().collect())
+    })
+}
+
+/// Writes documents to a Parquet file.
+///
+/// The file has a `page_content` column followed by one string column per metadata key,
+/// with nulls where a document doesn't have that key. Columns are zstd compressed.
+///
+/// # Arguments
+///
+/// * `docs` - The documents to write.
+/// * `path` - The path of the file to write.
+///
+/// # Returns
+///
+/// The number of documents written.
+#[pyfunction]
+pub fn write_parquet(py: Python, docs: &PyAny, path: PathBuf) -> PyResult<usize> {
+    let doc_vec: Vec<Document> = extract_documents(docs)?;
+    py.allow_threads(|| {
+        let batch = documents_to_record_batch(&doc_vec)?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        let mut writer =
+            ArrowWriter::try_new(File::create(&path)?, batch.schema(), Some(properties))
+                .map_err(to_py_error)?;
+        writer.write(&batch).map_err(to_py_error)?;
+        writer.close().map_err(to_py_error)?;
+        Ok(doc_vec.len())
+    })
+}
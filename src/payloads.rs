@@ -0,0 +1,241 @@
+use crate::{extract_documents, Document, Error};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// How the id of each payload is chosen.
+#[derive(Clone, Copy)]
+enum IdStrategy {
+    /// A UUID derived from the page_content and metadata.
+    Hash,
+    /// The metadata value at `id_key`.
+    Metadata,
+    /// The position of the document in the input.
+    Index,
+}
+
+impl FromStr for IdStrategy {
+    type Err = Error;
+
+    fn from_str(strategy: &str) -> Result<Self, Self::Err> {
+        match strategy {
+            "hash" => Ok(IdStrategy::Hash),
+            "metadata" => Ok(IdStrategy::Metadata),
+            "index" => Ok(IdStrategy::Index),
+            _ => Err(Error::InvalidArgument(format!(
+                "Unknown id strategy \"{}\", expected \"hash\", \"metadata\" or \"index\"",
+                strategy
+            ))),
+        }
+    }
+}
+
+/// A metadata value in a shape every vector store accepts.
+pub enum PayloadValue {
+    Text(String),
+    List(Vec<String>),
+}
+
+impl IntoPy<PyObject> for PayloadValue {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            PayloadValue::Text(text) => text.into_py(py),
+            PayloadValue::List(list) => list.into_py(py),
+        }
+    }
+}
+
+/// The id of a payload: a string, or an integer for `IdStrategy::Index`, since Qdrant
+/// only accepts UUIDs and unsigned integers.
+pub enum PayloadId {
+    Text(String),
+    Number(usize),
+}
+
+impl IntoPy<PyObject> for PayloadId {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            PayloadId::Text(text) => text.into_py(py),
+            PayloadId::Number(number) => number.into_py(py),
+        }
+    }
+}
+
+/// An `(id, text, metadata)` triple ready to be upserted into a vector store.
+pub type Payload = (PayloadId, String, BTreeMap<String, PayloadValue>);
+
+/// Builds a deterministic UUID from the page_content and sorted metadata of a document.
+///
+/// The first 16 bytes of a SHA-256 digest are formatted as a UUID with the version and
+/// variant bits set, so that stores which only accept UUIDs (Qdrant, Weaviate) take it.
+fn hash_id(document: &Document) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(document.page_content.as_bytes());
+    let pairs: BTreeMap<&String, &String> = document.metadata.iter().collect();
+    for (key, value) in pairs {
+        hasher.update([0]);
+        hasher.update(key.as_bytes());
+        hasher.update([0]);
+        hasher.update(value.as_bytes());
+    }
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&hasher.finalize()[..16]);
+    bytes[6] = (bytes[6] & 0x0f) | 0x50;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Replaces every character that isn't an ASCII letter, digit or underscore with an
+/// underscore, and prefixes keys starting with a digit, which Weaviate rejects.
+fn sanitize_key(key: &str) -> String {
+    let mut sanitized: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        sanitized.insert(0, '_');
+    }
+    sanitized
+}
+
+/// Renders a scalar JSON value as a string, returning `None` for nulls.
+fn scalar_string(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(string) => Some(string.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Adds a JSON value to the payload, flattening objects into `parent_child` keys.
+///
+/// Returns the key when the payload already has it, because two metadata keys were
+/// sanitized or flattened into the same one.
+fn flatten_value(
+    key: String,
+    value: serde_json::Value,
+    payload: &mut BTreeMap<String, PayloadValue>,
+) -> Result<(), String> {
+    let value = match value {
+        serde_json::Value::Object(object) => {
+            for (child_key, child_value) in object {
+                let child_key = format!("{}_{}", key, sanitize_key(&child_key));
+                flatten_value(child_key, child_value, payload)?;
+            }
+            return Ok(());
+        }
+        serde_json::Value::Array(items) => {
+            PayloadValue::List(items.iter().filter_map(scalar_string).collect())
+        }
+        other => match scalar_string(&other).filter(|text| !text.is_empty()) {
+            Some(text) => PayloadValue::Text(text),
+            None => return Ok(()),
+        },
+    };
+    if payload.contains_key(&key) {
+        return Err(key);
+    }
+    payload.insert(key, value);
+    Ok(())
+}
+
+/// Flattens and sanitizes metadata into something Qdrant, Pinecone and Weaviate all accept.
+///
+/// Keys are sanitized with `sanitize_key`. Values holding a JSON object (as loaders store
+/// nested frontmatter) are flattened into one key per leaf, JSON arrays become lists of
+/// strings, and empty values are dropped because Pinecone doesn't allow nulls.
+///
+/// Returns an error naming the document by `index` when two keys end up the same, like
+/// `source file` and `source_file`, rather than dropping one of the values.
+fn sanitize_metadata(
+    index: usize,
+    document: &Document,
+) -> PyResult<BTreeMap<String, PayloadValue>> {
+    let mut payload = BTreeMap::new();
+    let pairs: BTreeMap<&String, &String> = document.metadata.iter().collect();
+    for (original, value) in pairs {
+        let key = sanitize_key(original);
+        let trimmed = value.trim_start();
+        let json = if trimmed.starts_with('{') || trimmed.starts_with('[') {
+            serde_json::from_str(value).ok()
+        } else {
+            None
+        };
+        let value = json.unwrap_or_else(|| serde_json::Value::String(value.clone()));
+        flatten_value(key, value, &mut payload).map_err(|key| {
+            PyValueError::new_err(format!(
+                "Document {} has more than one metadata key that becomes \"{}\", \
+                 including \"{}\"",
+                index, key, original
+            ))
+        })?;
+    }
+    Ok(payload)
+}
+
+/// Converts documents into `(id, text, metadata)` triples for vector store upserts.
+///
+/// The metadata is flattened and sanitized so that Qdrant, Pinecone and Weaviate accept
+/// it as is: keys only contain letters, digits and underscores, nested JSON objects are
+/// flattened into `parent_child` keys, JSON arrays become lists of strings and empty
+/// values are left out. Metadata keys that become the same key are an error.
+///
+/// # Arguments
+///
+/// * `docs` - The documents to convert.
+/// * `id_strategy` - How ids are chosen. `"hash"` derives a deterministic UUID from the
+///   page_content and metadata, so re-upserting the same chunk overwrites it.
+///   `"metadata"` uses the metadata value at `id_key`, and `"index"` uses the position
+///   of the document, as an integer.
+/// * `id_key` - The metadata key holding the id when `id_strategy` is `"metadata"`.
+#[pyfunction]
+#[pyo3(signature = (docs, id_strategy = "hash", id_key = "id"))]
+pub fn to_vector_payloads(
+    py: Python,
+    docs: &PyAny,
+    id_strategy: &str,
+    id_key: &str,
+) -> PyResult<Vec<Payload>> {
+    let strategy: IdStrategy = id_strategy.parse()?;
+    let doc_vec: Vec<Document> = extract_documents(docs)?;
+    py.allow_threads(|| {
+        doc_vec
+            .into_par_iter()
+            .enumerate()
+            .map(|(index, document)| {
+                let id = match strategy {
+                    IdStrategy::Hash => PayloadId::Text(hash_id(&document)),
+                    IdStrategy::Index => PayloadId::Number(index),
+                    IdStrategy::Metadata => match document.metadata.get(id_key) {
+                        Some(id) => PayloadId::Text(id.clone()),
+                        None => {
+                            return Err(PyValueError::new_err(format!(
+                                "Document {} has no \"{}\" metadata to use as its id",
+                                index, id_key
+                            )))
+                        }
+                    },
+                };
+                let metadata = sanitize_metadata(index, &document)?;
+                Ok((id, document.page_content, metadata))
+            })
+            .collect()
+    })
+}
@@ -0,0 +1,493 @@
+#[cfg(feature = "python")]
+use crate::{extract_documents, SizeUnit};
+use crate::{Document, Error, FootnoteMode, SplitterChain};
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use rayon::prelude::*;
+#[cfg(feature = "python")]
+use serde::de::{self, Deserializer, MapAccess, Visitor};
+#[cfg(feature = "python")]
+use serde::Deserialize;
+use std::collections::HashMap;
+#[cfg(feature = "python")]
+use std::fmt;
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// A cleaner run by a `Pipeline`, named after the `Document` method it calls.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cleaner {
+    /// `Document::clean`, every cleaner in turn.
+    Clean,
+    ReflowColumns,
+    CleanExtraWhitespace,
+    CleanLigatures,
+    CleanBullets,
+    CleanNonAsciiChars,
+    GroupBrokenParagraphs,
+    NewLineGrouper,
+    AutoParagraphGrouper,
+    CleanFootnotes(FootnoteMode),
+}
+
+impl Cleaner {
+    /// Looks up a cleaner by its method name, with `options` for the cleaners that
+    /// take arguments: `mode` for `clean_footnotes`, which defaults to `"strip"`.
+    pub fn with_options(name: &str, options: &HashMap<String, String>) -> Result<Self, Error> {
+        let cleaner = match name {
+            "clean" => Cleaner::Clean,
+            "reflow_columns" => Cleaner::ReflowColumns,
+            "clean_extra_whitespace" => Cleaner::CleanExtraWhitespace,
+            "clean_ligatures" => Cleaner::CleanLigatures,
+            "clean_bullets" => Cleaner::CleanBullets,
+            "clean_non_ascii_chars" => Cleaner::CleanNonAsciiChars,
+            "group_broken_paragraphs" => Cleaner::GroupBrokenParagraphs,
+            "new_line_grouper" => Cleaner::NewLineGrouper,
+            "auto_paragraph_grouper" => Cleaner::AutoParagraphGrouper,
+            "clean_footnotes" => Cleaner::CleanFootnotes(match options.get("mode") {
+                Some(mode) => mode.parse()?,
+                None => FootnoteMode::Strip,
+            }),
+            _ => {
+                return Err(Error::InvalidArgument(format!(
+                    "Unknown cleaner \"{}\", expected one of {}",
+                    name,
+                    CLEANER_NAMES.join(", ")
+                )))
+            }
+        };
+        let allowed: &[&str] = match cleaner {
+            Cleaner::CleanFootnotes(_) => &["mode"],
+            _ => &[],
+        };
+        if let Some(option) = options.keys().find(|key| !allowed.contains(&key.as_str())) {
+            return Err(Error::InvalidArgument(format!(
+                "Unknown option \"{}\" for cleaner \"{}\"",
+                option, name
+            )));
+        }
+        Ok(cleaner)
+    }
+
+    /// Runs the cleaner on `document`.
+    pub fn apply(self, document: &mut Document) {
+        match self {
+            Cleaner::Clean => document.clean(),
+            Cleaner::ReflowColumns => document.reflow_columns(),
+            Cleaner::CleanExtraWhitespace => document.clean_extra_whitespace(),
+            Cleaner::CleanLigatures => document.clean_ligatures(),
+            Cleaner::CleanBullets => document.clean_bullets(),
+            Cleaner::CleanNonAsciiChars => document.clean_non_ascii_chars(),
+            Cleaner::GroupBrokenParagraphs => document.group_broken_paragraphs(),
+            Cleaner::NewLineGrouper => document.new_line_grouper(),
+            Cleaner::AutoParagraphGrouper => document.auto_paragraph_grouper(),
+            Cleaner::CleanFootnotes(mode) => document.clean_footnotes(mode),
+        }
+    }
+}
+
+/// The names `Cleaner` accepts, for error messages.
+const CLEANER_NAMES: &[&str] = &[
+    "clean",
+    "reflow_columns",
+    "clean_extra_whitespace",
+    "clean_ligatures",
+    "clean_bullets",
+    "clean_non_ascii_chars",
+    "group_broken_paragraphs",
+    "new_line_grouper",
+    "auto_paragraph_grouper",
+    "clean_footnotes",
+];
+
+impl FromStr for Cleaner {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Cleaner::with_options(name, &HashMap::new())
+    }
+}
+
+/// Cleaners followed by a `SplitterChain`, run over each document, with the options
+/// for the chunks the chain produces.
+///
+/// ```
+/// use rs_document::{Cleaner, Document, Pipeline, SplitterChain};
+/// use std::collections::HashMap;
+///
+/// let pipeline = Pipeline::new(SplitterChain::new().recursive(10))
+///     .cleaner(Cleaner::CleanExtraWhitespace)
+///     .provenance(true, "id");
+/// let document = Document::new("Some   text   to split", HashMap::new());
+/// let chunks = pipeline.run(&document).unwrap();
+/// assert_eq!(chunks[0].page_content, "Some text");
+/// assert_eq!(chunks[0].metadata["total_chunks"], "3");
+/// ```
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pipeline {
+    cleaners: Vec<Cleaner>,
+    splitter: SplitterChain,
+    provenance: bool,
+    id_key: String,
+    context_template: Option<String>,
+}
+
+impl Pipeline {
+    /// Returns a pipeline that splits with `splitter`, without cleaners, provenance or
+    /// context headers.
+    pub fn new(splitter: SplitterChain) -> Self {
+        Pipeline {
+            cleaners: Vec::new(),
+            splitter,
+            provenance: false,
+            id_key: "id".to_string(),
+            context_template: None,
+        }
+    }
+
+    /// Adds a cleaner, run after the ones added before it.
+    pub fn cleaner(mut self, cleaner: Cleaner) -> Self {
+        self.cleaners.push(cleaner);
+        self
+    }
+
+    /// Sets whether chunks get `chunk_index`, `total_chunks` and `parent_id` metadata,
+    /// with the parent's id read from `id_key`.
+    pub fn provenance(mut self, provenance: bool, id_key: &str) -> Self {
+        self.provenance = provenance;
+        self.id_key = id_key.to_string();
+        self
+    }
+
+    /// Sets the template of the context header added to every chunk, as in
+    /// `Document::add_context_header`.
+    pub fn context_template(mut self, template: &str) -> Self {
+        self.context_template = Some(template.to_string());
+        self
+    }
+
+    /// Cleans a copy of `document` and splits it.
+    ///
+    /// Returns `Error::InvalidArgument` when the arguments of a splitter step are out of
+    /// range.
+    #[tracing::instrument(level = "trace", skip_all, fields(bytes = document.page_content.len()))]
+    pub fn run(&self, document: &Document) -> Result<Vec<Document>, Error> {
+        let parent_id = self.provenance.then(|| document.parent_id(&self.id_key));
+        let mut document = document.clone();
+        for cleaner in &self.cleaners {
+            cleaner.apply(&mut document);
+        }
+        let mut chunks = self.splitter.split(
+            &document,
+            self.provenance,
+            &self.id_key,
+            self.context_template.as_deref(),
+        )?;
+        if let Some(parent_id) = parent_id {
+            Document::set_parent_id(&mut chunks, &parent_id);
+        }
+        Ok(chunks)
+    }
+}
+
+/// A cleaner in a pipeline config: its name, or a map from its name to its options,
+/// like `{"clean_footnotes": {"mode": "inline"}}`.
+#[cfg(feature = "python")]
+struct CleanerConfig(Cleaner);
+
+#[cfg(feature = "python")]
+impl<'de> Deserialize<'de> for CleanerConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct CleanerVisitor;
+
+        impl<'de> Visitor<'de> for CleanerVisitor {
+            type Value = CleanerConfig;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a cleaner name or a map from a cleaner name to its options")
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<Self::Value, E> {
+                name.parse().map(CleanerConfig).map_err(E::custom)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let Some((name, options)) = map.next_entry::<String, HashMap<String, String>>()?
+                else {
+                    return Err(de::Error::custom(
+                        "expected a cleaner name, got an empty map",
+                    ));
+                };
+                if map.next_key::<String>()?.is_some() {
+                    return Err(de::Error::custom(
+                        "expected one cleaner per entry, got a map with several",
+                    ));
+                }
+                Cleaner::with_options(&name, &options)
+                    .map(CleanerConfig)
+                    .map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_any(CleanerVisitor)
+    }
+}
+
+/// A step of the splitter chain in a pipeline config, with the same arguments and
+/// defaults as the `SplitterChain` methods in Python.
+#[cfg(feature = "python")]
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum StepConfig {
+    MarkdownHeaders {
+        #[serde(default = "default_max_level")]
+        max_level: usize,
+    },
+    Recursive {
+        chunk_size: usize,
+    },
+    Markdown {
+        chunk_size: usize,
+    },
+    Sentences {
+        chunk_size: usize,
+        #[serde(default = "default_overlap_sentences")]
+        overlap_sentences: usize,
+    },
+    Characters {
+        num_characters: u32,
+        #[serde(default)]
+        overlap: u32,
+        #[serde(default = "default_unit")]
+        unit: String,
+    },
+    MergeSmall {
+        min_size: usize,
+        max_size: usize,
+    },
+    Filter {
+        #[serde(default)]
+        min_chunk_size: usize,
+        #[serde(default = "default_drop_empty")]
+        drop_empty: bool,
+    },
+}
+
+#[cfg(feature = "python")]
+fn default_max_level() -> usize {
+    6
+}
+
+#[cfg(feature = "python")]
+fn default_overlap_sentences() -> usize {
+    1
+}
+
+#[cfg(feature = "python")]
+fn default_unit() -> String {
+    "graphemes".to_string()
+}
+
+#[cfg(feature = "python")]
+fn default_drop_empty() -> bool {
+    true
+}
+
+#[cfg(feature = "python")]
+fn default_cleaners() -> Vec<CleanerConfig> {
+    vec![CleanerConfig(Cleaner::Clean)]
+}
+
+#[cfg(feature = "python")]
+fn default_id_key() -> String {
+    "id".to_string()
+}
+
+#[cfg(feature = "python")]
+impl StepConfig {
+    fn add_to(self, chain: SplitterChain) -> Result<SplitterChain, Error> {
+        Ok(match self {
+            StepConfig::MarkdownHeaders { max_level } => chain.markdown_headers(max_level),
+            StepConfig::Recursive { chunk_size } => chain.recursive(chunk_size),
+            StepConfig::Markdown { chunk_size } => chain.markdown(chunk_size),
+            StepConfig::Sentences {
+                chunk_size,
+                overlap_sentences,
+            } => chain.sentences(chunk_size, overlap_sentences),
+            StepConfig::Characters {
+                num_characters,
+                overlap,
+                unit,
+            } => chain.characters(num_characters, overlap, unit.parse::<SizeUnit>()?),
+            StepConfig::MergeSmall { min_size, max_size } => chain.merge_small(min_size, max_size),
+            StepConfig::Filter {
+                min_chunk_size,
+                drop_empty,
+            } => chain.filter(min_chunk_size, drop_empty),
+        })
+    }
+}
+
+/// The contents of a pipeline config file.
+#[cfg(feature = "python")]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PipelineConfig {
+    #[serde(default = "default_cleaners")]
+    cleaners: Vec<CleanerConfig>,
+    // Steps are written as one-key maps, like in JSON, rather than as YAML tags.
+    #[serde(
+        default,
+        deserialize_with = "serde_yaml::with::singleton_map_recursive::deserialize"
+    )]
+    splitter: Vec<StepConfig>,
+    #[serde(default)]
+    provenance: bool,
+    #[serde(default = "default_id_key")]
+    id_key: String,
+    #[serde(default)]
+    context_template: Option<String>,
+}
+
+#[cfg(feature = "python")]
+impl PipelineConfig {
+    fn into_pipeline(self) -> Result<Pipeline, Error> {
+        let mut splitter = SplitterChain::new();
+        for step in self.splitter {
+            splitter = step.add_to(splitter)?;
+        }
+        splitter.check()?;
+        Ok(Pipeline {
+            cleaners: self.cleaners.into_iter().map(|cleaner| cleaner.0).collect(),
+            splitter,
+            provenance: self.provenance,
+            id_key: self.id_key,
+            context_template: self.context_template,
+        })
+    }
+}
+
+/// Converts the `cleaners` argument of `Pipeline` from Python: names, or dictionaries
+/// from a name to its options.
+#[cfg(feature = "python")]
+fn extract_cleaner(cleaner: &PyAny) -> PyResult<Cleaner> {
+    if let Ok(name) = cleaner.extract::<&str>() {
+        return Ok(name.parse()?);
+    }
+    let entries: HashMap<String, HashMap<String, String>> = cleaner.extract()?;
+    match entries.iter().next() {
+        Some((name, options)) if entries.len() == 1 => Ok(Cleaner::with_options(name, options)?),
+        _ => Err(PyValueError::new_err(
+            "Expected a cleaner name or a dict with one cleaner name",
+        )),
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Pipeline {
+    /// Returns a pipeline that runs `cleaners`, in order, and then splits with
+    /// `splitter`. Without a splitter, documents are only cleaned.
+    ///
+    /// Cleaners are named after the `Document` methods, like `"reflow_columns"` or
+    /// `"clean"` for all of them. Cleaners with options are given as a dict, like
+    /// `{"clean_footnotes": {"mode": "inline"}}`.
+    #[new]
+    #[pyo3(signature = (
+        cleaners = None,
+        splitter = None,
+        provenance = false,
+        id_key = "id",
+        context_template = None
+    ))]
+    fn py_new(
+        cleaners: Option<Vec<&PyAny>>,
+        splitter: Option<SplitterChain>,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<String>,
+    ) -> PyResult<Self> {
+        let cleaners = match cleaners {
+            Some(cleaners) => cleaners
+                .into_iter()
+                .map(extract_cleaner)
+                .collect::<PyResult<Vec<Cleaner>>>()?,
+            None => vec![Cleaner::Clean],
+        };
+        Ok(Pipeline {
+            cleaners,
+            splitter: splitter.unwrap_or_default(),
+            provenance,
+            id_key: id_key.to_string(),
+            context_template,
+        })
+    }
+
+    /// Loads a pipeline from a YAML (`.yaml`, `.yml`) or JSON (`.json`) config file.
+    ///
+    /// The config has `cleaners`, a list like the `cleaners` argument, `splitter`, a
+    /// list of `SplitterChain` steps with their arguments, like
+    /// `{"recursive": {"chunk_size": 1000}}`, and the `provenance`, `id_key` and
+    /// `context_template` options. Every key is optional, with the same defaults as the
+    /// arguments of `Pipeline`. Unknown keys, cleaners and splitter steps, and arguments
+    /// that are out of range, raise `ValueError` with where in the file they are.
+    #[staticmethod]
+    #[pyo3(name = "from_config")]
+    fn py_from_config(path: PathBuf) -> PyResult<Self> {
+        let text = std::fs::read_to_string(&path)?;
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        let config: Result<PipelineConfig, String> = match extension {
+            Some("yaml" | "yml") => serde_yaml::from_str(&text).map_err(|error| error.to_string()),
+            Some("json") => serde_json::from_str(&text).map_err(|error| error.to_string()),
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown config format for {}, expected a .yaml, .yml or .json file",
+                    path.display()
+                )))
+            }
+        };
+        config
+            .and_then(|config| config.into_pipeline().map_err(|error| error.to_string()))
+            .map_err(|error| {
+                PyValueError::new_err(format!(
+                    "Invalid pipeline config {}: {}",
+                    path.display(),
+                    error
+                ))
+            })
+    }
+
+    /// Cleans a copy of `document` and splits it.
+    #[pyo3(name = "run")]
+    fn py_run(&self, py: Python, document: Document) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.run(&document))?)
+    }
+
+    /// Cleans and splits each document in parallel, returning the chunks of all of them
+    /// in order.
+    #[pyo3(name = "run_docs")]
+    fn py_run_docs(&self, py: Python, docs: &PyAny) -> PyResult<Vec<Document>> {
+        let doc_vec = extract_documents(docs)?;
+        let span = tracing::info_span!(
+            "run_docs",
+            docs = doc_vec.len(),
+            chunks = tracing::field::Empty
+        )
+        .entered();
+        let chunks = py
+            .allow_threads(|| {
+                doc_vec
+                    .par_iter()
+                    .map(|document| self.run(document))
+                    .collect::<Result<Vec<_>, Error>>()
+            })?
+            .concat();
+        span.record("chunks", chunks.len());
+        Ok(chunks)
+    }
+}
@@ -0,0 +1,883 @@
+//! The Python bindings, a thin layer over the core `Document` API.
+
+use crate::splitters::char_count;
+use crate::{
+    aio, arrow_io, coalesce, csv_loader, dedupe, entities, html, incremental, jsonl, keywords,
+    language, loaders, logging, markdown, metadata, minhash, msgpack, ngrams, parquet_io, payloads,
+    quality, readability, sqlite, stats, stream, tokens, web, Document, Pipeline, SizeUnit,
+    SplitterChain,
+};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tiktoken_rs::CoreBPE;
+
+#[pymethods]
+impl Document {
+    /// Returns a new document with page content and metadata.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_content` - A string that holds the content of the document.
+    /// * `metadata` - A python dictionary of metadata for the document.
+    #[new]
+    fn py_new(page_content: String, metadata: HashMap<String, String>) -> Self {
+        Document::new(page_content, metadata)
+    }
+    /// The text of the document.
+    #[getter(page_content)]
+    fn py_page_content(&self) -> String {
+        self.page_content.clone()
+    }
+    #[setter(page_content)]
+    fn py_set_page_content(&mut self, page_content: String) {
+        self.page_content = page_content;
+    }
+    /// Metadata for the document. Currently only supports strings as keys *and* values.
+    #[getter(metadata)]
+    fn py_metadata(&self) -> HashMap<String, String> {
+        self.metadata.clone()
+    }
+    #[setter(metadata)]
+    fn py_set_metadata(&mut self, metadata: HashMap<String, String>) {
+        self.metadata = metadata;
+    }
+    /// Returns a new document with the contents of a text file.
+    ///
+    /// The file is read on the Rust side, and the document gets `source`, `size` and
+    /// `mtime` metadata.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file to read.
+    /// * `encoding` - The encoding of the file. Defaults to UTF-8.
+    /// * `errors` - What to do when the file isn't valid in `encoding`, as in
+    ///   `load_text_files`.
+    #[staticmethod]
+    #[pyo3(signature = (path, encoding = None, errors = "detect"))]
+    fn from_file(path: PathBuf, encoding: Option<&str>, errors: &str) -> PyResult<Self> {
+        loaders::load_text_file(&path, encoding, errors.parse()?)
+    }
+    /// Returns a new document with the readable text of an HTML page.
+    ///
+    /// The main content of the page is used when it can be found, and navigation,
+    /// scripts and similar boilerplate are skipped. The page's `title`, `description`
+    /// and `canonical_url` are added to the metadata when present.
+    ///
+    /// # Arguments
+    ///
+    /// * `html_or_path` - Either a string of HTML or the path of an HTML file. Strings
+    ///   containing a `<` are treated as HTML.
+    #[staticmethod]
+    fn from_html(html_or_path: &PyAny) -> PyResult<Self> {
+        if let Ok(html) = html_or_path.extract::<&str>() {
+            if html.contains('<') {
+                return Ok(html::parse_html(html));
+            }
+        }
+        html::load_html_file(&html_or_path.extract::<PathBuf>()?)
+    }
+    /// Create a human readable repr(Document) and str(Document).
+    fn __repr__(&self) -> String {
+        format!(
+            "Document(page_content=\"{}\", metadata={:?})",
+            self.page_content, self.metadata
+        )
+    }
+
+    /// Create a human readable repr(Document) and str(Document).
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    /// Serializes the document to MessagePack bytes, as a
+    /// `{"page_content": ..., "metadata": {...}}` map.
+    pub fn to_msgpack(&self, py: Python) -> PyResult<PyObject> {
+        Ok(PyBytes::new(py, &msgpack::encode(self)?).into())
+    }
+
+    /// Returns a new document from MessagePack bytes written by `to_msgpack`.
+    #[staticmethod]
+    fn from_msgpack(data: &[u8]) -> PyResult<Self> {
+        msgpack::decode(data)
+    }
+
+    /// Returns a copy of this document as a LangChain `Document`.
+    ///
+    /// Requires `langchain_core` to be installed.
+    pub fn to_langchain(&self, py: Python) -> PyResult<PyObject> {
+        let langchain_document = py.import("langchain_core.documents")?.getattr("Document")?;
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("page_content", &self.page_content)?;
+        kwargs.set_item("metadata", self.metadata.clone())?;
+        Ok(langchain_document.call((), Some(kwargs))?.into())
+    }
+
+    /// Remove Non Ascii characters from document's page_content.
+    #[pyo3(name = "clean_non_ascii_chars")]
+    fn py_clean_non_ascii_chars(&mut self) {
+        self.clean_non_ascii_chars()
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "_unicode_bullets_pattern")]
+    fn py_unicode_bullets_pattern() -> String {
+        Document::_unicode_bullets_pattern()
+    }
+
+    /// Remove bullets from page_content using a regular expression pattern.
+    #[pyo3(name = "clean_bullets")]
+    fn py_clean_bullets(&mut self) {
+        self.clean_bullets()
+    }
+
+    /// Replace common ligatures like æ in page_content.
+    #[pyo3(name = "clean_ligatures")]
+    fn py_clean_ligatures(&mut self) {
+        self.clean_ligatures()
+    }
+
+    /// Remove extraneous whitespace from page_content
+    #[pyo3(name = "clean_extra_whitespace")]
+    fn py_clean_extra_whitespace(&mut self) {
+        self.clean_extra_whitespace()
+    }
+
+    /// Groups paragraphs that have bullets and line breaks
+    #[staticmethod]
+    #[pyo3(name = "_group_bullet_paragraph")]
+    fn py_group_bullet_paragraph(paragraph: &str) -> Vec<String> {
+        Document::_group_bullet_paragraph(paragraph)
+    }
+
+    /// Groups paragraphs in page_content that have line breaks.
+    #[pyo3(name = "group_broken_paragraphs")]
+    fn py_group_broken_paragraphs(&mut self) {
+        self.group_broken_paragraphs()
+    }
+
+    /// Concatenates page_content that has one-line paragraph break pattern.
+    #[pyo3(name = "new_line_grouper")]
+    fn py_new_line_grouper(&mut self) {
+        self.new_line_grouper()
+    }
+
+    /// Groups page_content with `new_line_grouper` or `group_broken_paragraphs`,
+    /// depending on how many of its lines are blank.
+    #[pyo3(name = "auto_paragraph_grouper")]
+    fn py_auto_paragraph_grouper(&mut self) {
+        self.auto_paragraph_grouper()
+    }
+
+    /// Reflows text laid out in two or three columns side by side into reading order,
+    /// one column after the other.
+    #[pyo3(name = "reflow_columns")]
+    fn py_reflow_columns(&mut self) {
+        self.reflow_columns()
+    }
+
+    /// Strips footnote markers and footnotes, or moves the footnotes inline or into
+    /// metadata.
+    ///
+    /// `mode` is one of "strip", "inline" or "metadata".
+    #[pyo3(name = "clean_footnotes", signature = (mode = "strip"))]
+    fn py_clean_footnotes(&mut self, mode: &str) -> PyResult<()> {
+        self.clean_footnotes(mode.parse()?);
+        Ok(())
+    }
+
+    /// A helper function that calls all of the cleaning functions at once
+    #[pyo3(name = "clean")]
+    fn py_clean(&mut self) {
+        self.clean()
+    }
+
+    /// An opinionated splitter based on LangChain's RecursiveCharacterTextSplitter.
+    ///
+    /// This splitter will always default to 1/3 of the chunk_size as the chunk_overlap.
+    /// Pass `overlap_sentences` to pack whole sentences into each chunk instead and
+    /// overlap neighbouring chunks by that many sentences.
+    ///
+    /// `unit` is `"characters"` to measure chunk_size in characters, or `"tokens"` to
+    /// measure it in tokens of `encoding` (see `count_tokens`). Chunks shorter than
+    /// `min_chunk_size`, in the same unit, are left out, and so are chunks that are only
+    /// whitespace when `drop_empty` is true.
+    #[pyo3(
+        name = "recursive_character_splitter",
+        signature = (
+            chunk_size,
+            provenance = false,
+            id_key = "id",
+            context_template = None,
+            overlap_sentences = None,
+            unit = "characters",
+            encoding = "cl100k_base",
+            min_chunk_size = 0,
+            drop_empty = false
+        )
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn py_recursive_character_splitter(
+        &self,
+        py: Python,
+        chunk_size: usize,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+        overlap_sentences: Option<usize>,
+        unit: &str,
+        encoding: &str,
+        min_chunk_size: usize,
+        drop_empty: bool,
+    ) -> PyResult<Vec<Document>> {
+        let bpe = unit_encoding(unit, encoding)?;
+        let options = ChunkOptions {
+            min_chunk_size,
+            drop_empty,
+            provenance,
+            id_key,
+            context_template,
+        };
+        Ok(py.allow_threads(|| {
+            split_with_overlap(self, chunk_size, overlap_sentences, bpe, &options)
+        }))
+    }
+
+    /// A variant of recursive_character_splitter for markdown that never splits inside a
+    /// fenced code block or a table, unless the block alone exceeds chunk_size.
+    ///
+    /// Tables that are too large are split into groups of rows with the header repeated,
+    /// and chunks that contain a table get `has_table` metadata. `min_chunk_size` and
+    /// `drop_empty` leave out chunks like in `recursive_character_splitter`.
+    #[pyo3(
+        name = "markdown_splitter",
+        signature = (
+            chunk_size,
+            provenance = false,
+            id_key = "id",
+            context_template = None,
+            min_chunk_size = 0,
+            drop_empty = false
+        )
+    )]
+    fn py_markdown_splitter(
+        &self,
+        chunk_size: usize,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+        min_chunk_size: usize,
+        drop_empty: bool,
+    ) -> Vec<Document> {
+        let options = ChunkOptions {
+            min_chunk_size,
+            drop_empty,
+            provenance,
+            id_key,
+            context_template,
+        };
+        let chunks = self.markdown_splitter(chunk_size, false, id_key, None);
+        options.finish(self, chunks, &char_count)
+    }
+
+    /// A basic splitter to split on a number of characters, without breaking up grapheme
+    /// clusters.
+    ///
+    /// `unit` is one of "graphemes", "characters" or "bytes", and sets what
+    /// `num_characters`, `overlap` and `min_chunk_size` count. Each chunk starts with the
+    /// last `overlap` units of the chunk before it.
+    #[pyo3(
+        name = "split_on_num_characters",
+        signature = (
+            num_characters,
+            provenance = false,
+            id_key = "id",
+            overlap = 0,
+            unit = "graphemes",
+            min_chunk_size = 0,
+            drop_empty = false
+        )
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn py_split_on_num_characters(
+        &self,
+        num_characters: u32,
+        provenance: bool,
+        id_key: &str,
+        overlap: u32,
+        unit: &str,
+        min_chunk_size: usize,
+        drop_empty: bool,
+    ) -> PyResult<Vec<Document>> {
+        let unit: SizeUnit = unit.parse()?;
+        let options = ChunkOptions {
+            min_chunk_size,
+            drop_empty,
+            provenance,
+            id_key,
+            context_template: None,
+        };
+        let chunks = self.split_on_num_characters(num_characters, overlap, unit, false, id_key)?;
+        Ok(options.finish(self, chunks, &|text: &str| unit.measure(text)))
+    }
+
+    /// Splits the document into one chunk per group of `sentences_per_chunk` sentences,
+    /// with up to `window_size` neighbouring sentences on each side stored in the
+    /// `window_before` and `window_after` metadata, for sentence-window retrieval.
+    #[pyo3(
+        name = "sentence_window_splitter",
+        signature = (
+            sentences_per_chunk = 1,
+            window_size = 3,
+            provenance = false,
+            id_key = "id",
+            context_template = None
+        )
+    )]
+    fn py_sentence_window_splitter(
+        &self,
+        sentences_per_chunk: usize,
+        window_size: usize,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+    ) -> PyResult<Vec<Document>> {
+        Ok(self.sentence_window_splitter(
+            sentences_per_chunk,
+            window_size,
+            provenance,
+            id_key,
+            context_template,
+        )?)
+    }
+
+    /// Prepends a context header, rendered from the document's metadata, to page_content.
+    #[pyo3(name = "add_context_header")]
+    fn py_add_context_header(&mut self, template: &str) {
+        self.add_context_header(template)
+    }
+
+    /// Splits the document into large parent chunks and small child chunks, for the
+    /// parent document retriever pattern.
+    #[pyo3(
+        name = "hierarchical_split",
+        signature = (parent_size, child_size, id_key = "id")
+    )]
+    fn py_hierarchical_split(
+        &self,
+        parent_size: usize,
+        child_size: usize,
+        id_key: &str,
+    ) -> PyResult<(Vec<Document>, Vec<Document>)> {
+        Ok(self.hierarchical_split(parent_size, child_size, id_key)?)
+    }
+
+    /// Returns the `top_k` keywords of page_content with their scores, highest first.
+    ///
+    /// `method` is `"rake"` for key phrases or `"tfidf"` for single words.
+    #[pyo3(name = "extract_keywords", signature = (top_k = 10, method = "rake"))]
+    fn py_extract_keywords(&self, top_k: usize, method: &str) -> PyResult<Vec<(String, f64)>> {
+        Ok(self.extract_keywords(top_k, method.parse()?))
+    }
+
+    /// Returns readability scores for page_content, as a dictionary with the
+    /// `flesch_kincaid_grade`, `flesch_reading_ease`, `smog_index`,
+    /// `average_sentence_length`, `sentences` and `words` keys.
+    #[pyo3(name = "readability")]
+    fn py_readability(&self) -> crate::Readability {
+        self.readability()
+    }
+
+    /// Scores how much page_content looks like natural language, from 0 (OCR garbage,
+    /// binary data) to 1 (clean prose).
+    ///
+    /// The score combines the share of letters and digits, the density of unusual
+    /// symbols, runs of repeated characters, the average word length and the spread of
+    /// line lengths.
+    #[pyo3(name = "quality_score")]
+    fn py_quality_score(&self) -> f64 {
+        self.quality_score()
+    }
+
+    /// Splits page_content into sentences, trimmed of surrounding whitespace.
+    #[pyo3(name = "sentences")]
+    fn py_sentences(&self) -> Vec<String> {
+        self.sentences()
+    }
+
+    /// Returns the `(start, end)` character offsets of each sentence in page_content.
+    #[pyo3(name = "sentence_spans")]
+    fn py_sentence_spans(&self) -> Vec<(usize, usize)> {
+        self.sentence_spans()
+    }
+
+    /// Runs named regular expressions over page_content and stores their matches in the
+    /// metadata.
+    ///
+    /// Each key of `patterns` becomes a metadata key holding a JSON array of the unique
+    /// matches of its pattern, in order of first appearance. When a pattern has capture
+    /// groups the first group is stored instead of the whole match, and patterns without
+    /// matches leave the metadata alone.
+    ///
+    /// For example:
+    ///
+    /// {"tickets": r"[A-Z]+-\d+"} on "Fixed in OPS-12, see OPS-7."
+    ///     -> metadata["tickets"] == '["OPS-12","OPS-7"]'
+    fn extract_entities(&mut self, patterns: HashMap<String, String>) -> PyResult<()> {
+        entities::EntityPatterns::new(patterns)?.apply(self);
+        Ok(())
+    }
+
+    /// Counts the tokens in page_content with a byte pair encoding.
+    ///
+    /// `encoding` is an encoding name such as `"cl100k_base"` or `"o200k_base"`, or an
+    /// OpenAI model name such as `"gpt-4o"`.
+    #[pyo3(signature = (encoding = "cl100k_base"))]
+    fn count_tokens(&self, py: Python, encoding: &str) -> PyResult<usize> {
+        let bpe = tokens::encoding(encoding)?;
+        Ok(py.allow_threads(|| tokens::count_tokens(bpe, &self.page_content)))
+    }
+}
+
+/// A Python module implemented in Rust.
+#[pymodule]
+fn rs_document(py: Python, m: &PyModule) -> PyResult<()> {
+    logging::install(py);
+    m.add_class::<Document>()?;
+    m.add_class::<arrow_io::ArrowTable>()?;
+    m.add_class::<jsonl::JsonlIterator>()?;
+    m.add_class::<stream::ChunkIterator>()?;
+    m.add_class::<SplitterChain>()?;
+    m.add_class::<Pipeline>()?;
+    m.add_class::<incremental::IncrementalProcessor>()?;
+    m.add_function(wrap_pyfunction!(clean_and_split_docs, m)?)?;
+    m.add_function(wrap_pyfunction!(clean_and_split_docs_async, m)?)?;
+    m.add_function(wrap_pyfunction!(arrow_io::to_arrow, m)?)?;
+    m.add_function(wrap_pyfunction!(arrow_io::docs_to_arrow_table, m)?)?;
+    m.add_function(wrap_pyfunction!(coalesce::coalesce_chunks, m)?)?;
+    m.add_function(wrap_pyfunction!(csv_loader::load_csv, m)?)?;
+    m.add_function(wrap_pyfunction!(dedupe::dedupe_docs, m)?)?;
+    m.add_function(wrap_pyfunction!(jsonl::load_jsonl, m)?)?;
+    m.add_function(wrap_pyfunction!(jsonl::write_jsonl, m)?)?;
+    m.add_function(wrap_pyfunction!(entities::extract_entities_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(keywords::extract_keywords_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(language::detect_languages, m)?)?;
+    m.add_function(wrap_pyfunction!(language::partition_by_language, m)?)?;
+    m.add_function(wrap_pyfunction!(loaders::load_text_files, m)?)?;
+    m.add_function(wrap_pyfunction!(loaders::load_text_files_async, m)?)?;
+    m.add_function(wrap_pyfunction!(logging::set_log_level, m)?)?;
+    m.add_function(wrap_pyfunction!(markdown::load_markdown, m)?)?;
+    m.add_function(wrap_pyfunction!(metadata::transform_metadata, m)?)?;
+    m.add_function(wrap_pyfunction!(minhash::near_dedupe_docs, m)?)?;
+    m.add_function(wrap_pyfunction!(msgpack::docs_from_msgpack, m)?)?;
+    m.add_function(wrap_pyfunction!(msgpack::docs_to_msgpack, m)?)?;
+    m.add_function(wrap_pyfunction!(ngrams::ngram_counts, m)?)?;
+    m.add_function(wrap_pyfunction!(parquet_io::load_parquet, m)?)?;
+    m.add_function(wrap_pyfunction!(parquet_io::write_parquet, m)?)?;
+    m.add_function(wrap_pyfunction!(payloads::to_vector_payloads, m)?)?;
+    m.add_function(wrap_pyfunction!(quality::filter_by_quality, m)?)?;
+    m.add_function(wrap_pyfunction!(readability::readability_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(sqlite::load_sqlite, m)?)?;
+    m.add_function(wrap_pyfunction!(stats::corpus_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(stream::split_stream, m)?)?;
+    m.add_function(wrap_pyfunction!(tokens::count_tokens_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(web::load_url, m)?)?;
+    m.add_function(wrap_pyfunction!(web::load_urls, m)?)?;
+    m.add_function(wrap_pyfunction!(web::load_url_async, m)?)?;
+    m.add_function(wrap_pyfunction!(web::load_urls_async, m)?)?;
+    Ok(())
+}
+
+/// Converts a Python metadata mapping into string metadata.
+///
+/// `None` becomes empty metadata. Keys and values that aren't strings are converted with
+/// Python's `str()`, since loaders (LangChain's included) often store page numbers and
+/// similar values as other types.
+fn extract_metadata(metadata: Option<&PyAny>) -> PyResult<HashMap<String, String>> {
+    let metadata = match metadata {
+        Some(metadata) if !metadata.is_none() => metadata.downcast::<PyDict>()?,
+        _ => return Ok(HashMap::new()),
+    };
+    let to_string = |value: &PyAny| match value.extract::<String>() {
+        Ok(string) => Ok(string),
+        Err(_) => value.str()?.extract::<String>(),
+    };
+    metadata
+        .iter()
+        .map(|(key, value)| Ok((to_string(key)?, to_string(value)?)))
+        .collect()
+}
+
+/// Converts any Python iterable into a vector of documents.
+///
+/// Items can be `Document` instances, dictionaries in LangChain's shape (with a
+/// `page_content` key and an optional `metadata` key), or any object with
+/// `page_content` and `metadata` attributes, such as LangChain's own `Document`. The
+/// iterable is consumed incrementally, so generators and lazy loader outputs never need
+/// to be listed first.
+pub(crate) fn extract_documents(docs: &PyAny) -> PyResult<Vec<Document>> {
+    let mut result = Vec::new();
+    for item in docs.iter()? {
+        let item = item?;
+        if let Ok(document) = item.extract::<Document>() {
+            result.push(document);
+        } else if let Ok(dict) = item.downcast::<PyDict>() {
+            let page_content = match dict.get_item("page_content") {
+                Some(value) => value.extract::<String>()?,
+                None => {
+                    return Err(PyTypeError::new_err(
+                        "Document dictionaries must have a \"page_content\" key",
+                    ))
+                }
+            };
+            result.push(Document {
+                page_content,
+                metadata: extract_metadata(dict.get_item("metadata"))?,
+            });
+        } else if item.hasattr("page_content")? {
+            let metadata = if item.hasattr("metadata")? {
+                Some(item.getattr("metadata")?)
+            } else {
+                None
+            };
+            result.push(Document {
+                page_content: item.getattr("page_content")?.extract::<String>()?,
+                metadata: extract_metadata(metadata)?,
+            });
+        } else {
+            return Err(PyTypeError::new_err(format!(
+                "Expected a Document, a dict or an object with a page_content attribute, got {}",
+                item.get_type().name()?
+            )));
+        }
+    }
+    Ok(result)
+}
+
+/// Returns the encoding chunk sizes are counted in for the `unit` argument of the
+/// splitters: `None` for `"characters"`, or `encoding` for `"tokens"`.
+fn unit_encoding(unit: &str, encoding: &str) -> PyResult<Option<&'static CoreBPE>> {
+    match unit {
+        "characters" => Ok(None),
+        "tokens" => Ok(Some(tokens::encoding(encoding)?)),
+        other => Err(PyValueError::new_err(format!(
+            "Unknown unit \"{}\", expected \"characters\" or \"tokens\"",
+            other
+        ))),
+    }
+}
+
+/// The size of `text` in characters, or in tokens of `bpe`.
+fn unit_length(bpe: Option<&CoreBPE>, text: &str) -> usize {
+    bpe.map_or_else(|| char_count(text), |bpe| tokens::count_tokens(bpe, text))
+}
+
+/// The options the Python splitters share for what to do with the chunks they split.
+struct ChunkOptions<'a> {
+    min_chunk_size: usize,
+    drop_empty: bool,
+    provenance: bool,
+    id_key: &'a str,
+    context_template: Option<&'a str>,
+}
+
+impl ChunkOptions<'_> {
+    /// Leaves out small and empty chunks of `document`, measured by `length`, and then
+    /// adds provenance and context headers to the rest.
+    ///
+    /// The chunks must have been split without provenance or context headers.
+    fn finish(
+        &self,
+        document: &Document,
+        mut chunks: Vec<Document>,
+        length: &dyn Fn(&str) -> usize,
+    ) -> Vec<Document> {
+        Document::filter_chunks(&mut chunks, self.min_chunk_size, self.drop_empty, length);
+        document.finish_chunks(
+            &mut chunks,
+            self.provenance,
+            self.id_key,
+            self.context_template,
+        );
+        chunks
+    }
+}
+
+/// Splits with `recursive_character_splitter`, or with `sentence_overlap_splitter` when
+/// `overlap_sentences` is given, counting chunk_size in tokens of `bpe` when it is given
+/// and in characters otherwise.
+fn split_with_overlap(
+    document: &Document,
+    chunk_size: usize,
+    overlap_sentences: Option<usize>,
+    bpe: Option<&CoreBPE>,
+    options: &ChunkOptions,
+) -> Vec<Document> {
+    let id_key = options.id_key;
+    let Some(bpe) = bpe else {
+        let chunks = match overlap_sentences {
+            Some(overlap_sentences) => document.sentence_overlap_splitter(
+                chunk_size,
+                overlap_sentences,
+                false,
+                id_key,
+                None,
+            ),
+            None => document.recursive_character_splitter(chunk_size, false, id_key, None),
+        };
+        return options.finish(document, chunks, &char_count);
+    };
+    let length = |text: &str| tokens::count_tokens(bpe, text);
+    let chunks = match overlap_sentences {
+        Some(overlap_sentences) => document.sentence_overlap_splitter_with_length(
+            chunk_size,
+            overlap_sentences,
+            &length,
+            false,
+            id_key,
+            None,
+        ),
+        None => document
+            .recursive_character_splitter_with_length(chunk_size, &length, false, id_key, None),
+    };
+    options.finish(document, chunks, &length)
+}
+
+/// The arguments of `clean_and_split_docs` besides the documents, checked and owned, so
+/// that `clean_and_split_docs_async` can use them on another thread.
+pub(crate) struct CleanAndSplit {
+    chunk_size: usize,
+    overlap_sentences: Option<usize>,
+    bpe: Option<&'static CoreBPE>,
+    min_chunk_size: usize,
+    drop_empty: bool,
+    provenance: bool,
+    id_key: String,
+    context_template: Option<String>,
+    langchain: bool,
+}
+
+impl CleanAndSplit {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        chunk_size: usize,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+        output_type: &str,
+        overlap_sentences: Option<usize>,
+        unit: &str,
+        encoding: &str,
+        min_chunk_size: usize,
+        drop_empty: bool,
+    ) -> PyResult<Self> {
+        if output_type != "rs_document" && output_type != "langchain" {
+            return Err(PyValueError::new_err(format!(
+                "Unknown output_type \"{}\", expected \"rs_document\" or \"langchain\"",
+                output_type
+            )));
+        }
+        Ok(CleanAndSplit {
+            chunk_size,
+            overlap_sentences,
+            bpe: unit_encoding(unit, encoding)?,
+            min_chunk_size,
+            drop_empty,
+            provenance,
+            id_key: id_key.to_string(),
+            context_template: context_template.map(str::to_string),
+            langchain: output_type == "langchain",
+        })
+    }
+
+    /// Cleans copies of `docs` and splits them, in parallel.
+    pub(crate) fn run(&self, docs: &[Document]) -> Vec<Document> {
+        docs.par_iter()
+            .map(|document| self.split(document))
+            .flatten()
+            .collect()
+    }
+
+    /// Cleans a copy of `document` and splits it.
+    pub(crate) fn split(&self, document: &Document) -> Vec<Document> {
+        let options = ChunkOptions {
+            min_chunk_size: self.min_chunk_size,
+            drop_empty: self.drop_empty,
+            provenance: self.provenance,
+            id_key: &self.id_key,
+            context_template: self.context_template.as_deref(),
+        };
+        let parent_id = self.provenance.then(|| document.parent_id(&self.id_key));
+        let mut document = document.clone();
+        document.clean();
+        let mut chunks = split_with_overlap(
+            &document,
+            self.chunk_size,
+            self.overlap_sentences,
+            self.bpe,
+            &options,
+        );
+        if let Some(parent_id) = parent_id {
+            Document::set_parent_id(&mut chunks, &parent_id);
+        }
+        chunks
+    }
+
+    /// The size of `text` in the unit `chunk_size` is measured in.
+    pub(crate) fn measure(&self, text: &str) -> usize {
+        unit_length(self.bpe, text)
+    }
+
+    /// Converts the chunks into the documents of the requested `output_type`.
+    fn to_python(&self, py: Python, chunks: Vec<Document>) -> PyResult<Vec<PyObject>> {
+        chunks
+            .into_iter()
+            .map(|document| {
+                if self.langchain {
+                    document.to_langchain(py)
+                } else {
+                    Ok(document.into_py(py))
+                }
+            })
+            .collect()
+    }
+}
+
+/// Cleans and splits an iterable of documents in parallel.
+///
+/// The GIL is released while the documents are processed so that other Python threads
+/// (web servers, event loops) are not blocked by a long running batch.
+///
+/// `output_type` selects what the chunks are returned as: `"rs_document"` for this
+/// module's `Document`, or `"langchain"` for LangChain's `Document`. `overlap_sentences`,
+/// `unit`, `encoding`, `min_chunk_size` and `drop_empty` work the same way as in
+/// `Document.recursive_character_splitter`.
+#[pyfunction]
+#[pyo3(signature = (
+    docs,
+    chunk_size,
+    provenance = false,
+    id_key = "id",
+    context_template = None,
+    output_type = "rs_document",
+    overlap_sentences = None,
+    unit = "characters",
+    encoding = "cl100k_base",
+    min_chunk_size = 0,
+    drop_empty = false
+))]
+#[allow(clippy::too_many_arguments)]
+fn clean_and_split_docs(
+    py: Python,
+    docs: &PyAny,
+    chunk_size: usize,
+    provenance: bool,
+    id_key: &str,
+    context_template: Option<&str>,
+    output_type: &str,
+    overlap_sentences: Option<usize>,
+    unit: &str,
+    encoding: &str,
+    min_chunk_size: usize,
+    drop_empty: bool,
+) -> PyResult<Vec<PyObject>> {
+    let task = CleanAndSplit::new(
+        chunk_size,
+        provenance,
+        id_key,
+        context_template,
+        output_type,
+        overlap_sentences,
+        unit,
+        encoding,
+        min_chunk_size,
+        drop_empty,
+    )?;
+    let span = tracing::info_span!(
+        "clean_and_split_docs",
+        docs = tracing::field::Empty,
+        chunks = tracing::field::Empty
+    )
+    .entered();
+    let doc_vec: Vec<Document> = {
+        let _stage = tracing::debug_span!("extract_documents").entered();
+        extract_documents(docs)?
+    };
+    span.record("docs", doc_vec.len());
+
+    let result: Vec<Document> = {
+        let _stage = tracing::debug_span!("clean_and_split").entered();
+        py.allow_threads(|| task.run(&doc_vec))
+    };
+    span.record("chunks", result.len());
+
+    let _stage = tracing::debug_span!("convert", output_type).entered();
+    task.to_python(py, result)
+}
+
+/// An awaitable version of `clean_and_split_docs`, with the same arguments.
+///
+/// The documents are read before this returns, and cleaned and split on the rayon pool
+/// while the event loop keeps running. Awaiting the result gives the list of chunks.
+#[pyfunction]
+#[pyo3(signature = (
+    docs,
+    chunk_size,
+    provenance = false,
+    id_key = "id",
+    context_template = None,
+    output_type = "rs_document",
+    overlap_sentences = None,
+    unit = "characters",
+    encoding = "cl100k_base",
+    min_chunk_size = 0,
+    drop_empty = false
+))]
+#[allow(clippy::too_many_arguments)]
+fn clean_and_split_docs_async<'py>(
+    py: Python<'py>,
+    docs: &PyAny,
+    chunk_size: usize,
+    provenance: bool,
+    id_key: &str,
+    context_template: Option<&str>,
+    output_type: &str,
+    overlap_sentences: Option<usize>,
+    unit: &str,
+    encoding: &str,
+    min_chunk_size: usize,
+    drop_empty: bool,
+) -> PyResult<&'py PyAny> {
+    let task = CleanAndSplit::new(
+        chunk_size,
+        provenance,
+        id_key,
+        context_template,
+        output_type,
+        overlap_sentences,
+        unit,
+        encoding,
+        min_chunk_size,
+        drop_empty,
+    )?;
+    let doc_vec: Vec<Document> = extract_documents(docs)?;
+    aio::spawn_awaitable(
+        py,
+        aio::Work::Compute,
+        move || {
+            let span = tracing::info_span!(
+                "clean_and_split_docs_async",
+                docs = doc_vec.len(),
+                chunks = tracing::field::Empty
+            )
+            .entered();
+            let chunks = task.run(&doc_vec);
+            span.record("chunks", chunks.len());
+            Ok((task, chunks))
+        },
+        |py, (task, chunks)| Ok(task.to_python(py, chunks)?.into_py(py)),
+    )
+}
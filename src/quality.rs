@@ -0,0 +1,200 @@
+#[cfg(feature = "python")]
+use crate::extract_documents;
+use crate::Document;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// How much each heuristic counts towards `Document::quality_score`.
+const ALPHANUMERIC_WEIGHT: f64 = 0.3;
+const SYMBOL_WEIGHT: f64 = 0.2;
+const REPEAT_WEIGHT: f64 = 0.2;
+const WORD_LENGTH_WEIGHT: f64 = 0.15;
+const LINE_ENTROPY_WEIGHT: f64 = 0.15;
+
+/// The lowest value a single heuristic contributes, so that one failing heuristic pulls
+/// the score down hard without zeroing it.
+const SCORE_FLOOR: f64 = 0.01;
+
+/// The shortest run of one character that counts as a repeated-character run.
+const MIN_REPEAT_RUN: usize = 4;
+
+/// Punctuation that is normal in prose, including full-width CJK punctuation, and isn't
+/// counted as a symbol.
+fn is_common_punctuation(character: char) -> bool {
+    ".,;:!?'\"()-‘’“”…。，、；：！？「」『』（）".contains(character)
+}
+
+/// Chinese, Japanese and Korean characters, which are written without spaces between words.
+fn is_cjk(character: char) -> bool {
+    matches!(
+        character,
+        '\u{3040}'..='\u{30ff}' | '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' | '\u{ac00}'..='\u{d7af}'
+    )
+}
+
+/// Maps `value` to 1 at or beyond `good` and to 0 at or beyond `bad`, linearly between.
+fn scale(value: f64, bad: f64, good: f64) -> f64 {
+    ((value - bad) / (good - bad)).clamp(0.0, 1.0)
+}
+
+/// Scores the spread of line lengths, which is close to 0 for fixed width dumps such as
+/// base64 or hex where every line has the same length.
+///
+/// Text with fewer than five lines doesn't have enough lines to judge and scores 1.
+fn line_entropy_score(text: &str) -> f64 {
+    let lengths: Vec<usize> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.chars().count())
+        .collect();
+    if lengths.len() < 5 {
+        return 1.0;
+    }
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+    for &length in &lengths {
+        *counts.entry(length).or_insert(0) += 1;
+    }
+    let total = lengths.len() as f64;
+    let entropy: f64 = counts
+        .values()
+        .map(|&count| {
+            let probability = count as f64 / total;
+            -probability * probability.log2()
+        })
+        .sum();
+    // One bit, as many lines of one length as of another, is already a healthy spread.
+    scale(entropy, 0.0, 1.0)
+}
+
+impl Document {
+    /// Scores how much page_content looks like natural language, from 0 (OCR garbage,
+    /// binary data) to 1 (clean prose).
+    ///
+    /// The score is a weighted geometric mean of five heuristics, so text that fails any
+    /// one of them badly scores low:
+    ///
+    /// * The share of non-whitespace characters that are letters or digits.
+    /// * The density of symbols other than common punctuation, which includes control
+    ///   characters and the U+FFFD left behind by undecodable bytes.
+    /// * The share of characters in runs of four or more of the same character.
+    /// * The average word length, which is ignored for Chinese, Japanese and Korean text.
+    /// * The spread of line lengths, to catch fixed width dumps like base64.
+    ///
+    /// Text without any visible characters scores 0.
+    pub fn quality_score(&self) -> f64 {
+        let text = &self.page_content;
+        let mut visible = 0;
+        let mut alphanumeric = 0;
+        let mut symbols = 0;
+        let mut cjk = 0;
+        let mut repeated = 0;
+        let mut run: Option<(char, usize)> = None;
+        for character in text.chars() {
+            let continues_run = matches!(run, Some((previous, _)) if previous == character);
+            if !continues_run {
+                if let Some((_, length)) =
+                    run.take().filter(|&(_, length)| length >= MIN_REPEAT_RUN)
+                {
+                    repeated += length;
+                }
+            }
+            if character.is_whitespace() {
+                continue;
+            }
+            run = match run {
+                Some((previous, length)) if continues_run => Some((previous, length + 1)),
+                _ => Some((character, 1)),
+            };
+            visible += 1;
+            if character.is_alphanumeric() {
+                alphanumeric += 1;
+                if is_cjk(character) {
+                    cjk += 1;
+                }
+            } else if !is_common_punctuation(character) {
+                symbols += 1;
+            }
+        }
+        if let Some((_, length)) = run.filter(|&(_, length)| length >= MIN_REPEAT_RUN) {
+            repeated += length;
+        }
+        if visible == 0 {
+            return 0.0;
+        }
+
+        let visible = visible as f64;
+        let word_length_score = if cjk as f64 / visible > 0.3 {
+            1.0
+        } else {
+            let words = text.split_whitespace().count() as f64;
+            let average_word_length = visible / words;
+            scale(average_word_length, 1.0, 3.0).min(scale(average_word_length, 20.0, 10.0))
+        };
+
+        [
+            (
+                ALPHANUMERIC_WEIGHT,
+                scale(alphanumeric as f64 / visible, 0.5, 0.8),
+            ),
+            (SYMBOL_WEIGHT, scale(symbols as f64 / visible, 0.15, 0.0)),
+            (REPEAT_WEIGHT, scale(repeated as f64 / visible, 0.3, 0.0)),
+            (WORD_LENGTH_WEIGHT, word_length_score),
+            (LINE_ENTROPY_WEIGHT, line_entropy_score(text)),
+        ]
+        .iter()
+        .map(|&(weight, score)| score.max(SCORE_FLOOR).powf(weight))
+        .product()
+    }
+}
+
+/// Drops documents whose `quality_score` is below `min_score`, scoring them in parallel.
+///
+/// # Arguments
+///
+/// * `docs` - The documents to filter.
+/// * `min_score` - The lowest score, from 0 to 1, a document needs to be kept.
+/// * `metadata_key` - When given, the score of every kept document is stored under this
+///   metadata key.
+///
+/// # Returns
+///
+/// A tuple of the kept documents (in their original order) and the indices of the
+/// documents that were dropped.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (docs, min_score = 0.5, metadata_key = None))]
+pub fn filter_by_quality(
+    py: Python,
+    docs: &PyAny,
+    min_score: f64,
+    metadata_key: Option<&str>,
+) -> PyResult<(Vec<Document>, Vec<usize>)> {
+    let doc_vec: Vec<Document> = extract_documents(docs)?;
+    let span = tracing::info_span!(
+        "filter_by_quality",
+        docs = doc_vec.len(),
+        dropped = tracing::field::Empty
+    )
+    .entered();
+    let result = py.allow_threads(|| {
+        let scores: Vec<f64> = doc_vec.par_iter().map(Document::quality_score).collect();
+        let mut kept = Vec::new();
+        let mut dropped = Vec::new();
+        for (index, (mut document, score)) in doc_vec.into_iter().zip(scores).enumerate() {
+            if score < min_score {
+                dropped.push(index);
+                continue;
+            }
+            if let Some(key) = metadata_key {
+                document.metadata.insert(key.to_string(), score.to_string());
+            }
+            kept.push(document);
+        }
+        (kept, dropped)
+    });
+    span.record("dropped", result.1.len());
+    Ok(result)
+}
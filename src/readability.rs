@@ -0,0 +1,136 @@
+#[cfg(feature = "python")]
+use crate::extract_documents;
+use crate::sentences::sentence_ranges;
+use crate::Document;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::IntoPyDict;
+#[cfg(feature = "python")]
+use rayon::prelude::*;
+
+/// Readability scores of a document's text, from `Document::readability`.
+///
+/// Every score is 0 for text without any words.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Readability {
+    /// The Flesch-Kincaid grade level: roughly the US school grade needed to follow the
+    /// text.
+    pub flesch_kincaid_grade: f64,
+    /// The Flesch reading ease, from about 0 (very hard) to 100 (very easy).
+    pub flesch_reading_ease: f64,
+    /// The SMOG grade, estimated from the number of words with three or more syllables.
+    pub smog_index: f64,
+    /// The average number of words per sentence.
+    pub average_sentence_length: f64,
+    /// The number of sentences.
+    pub sentences: usize,
+    /// The number of words.
+    pub words: usize,
+}
+
+/// Estimates the syllables in an English word by counting groups of vowels.
+///
+/// A silent final "e" is not counted ("make"), unless it follows an "l" ("table"), and
+/// every word has at least one syllable.
+fn syllables(word: &str) -> usize {
+    let word = word.to_lowercase();
+    let letters: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    let is_vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+    let mut count: usize = 0;
+    let mut previous_vowel = false;
+    for &letter in &letters {
+        let vowel = is_vowel(letter);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+    if let [.., second_last, last] = letters[..] {
+        if last == 'e' && second_last != 'l' && !is_vowel(second_last) {
+            count = count.saturating_sub(1);
+        }
+    }
+    count.max(1)
+}
+
+impl Document {
+    /// Returns readability scores for page_content.
+    ///
+    /// Sentences are found with `Document::sentences`. Words are the runs of letters, digits and apostrophes that contain at least
+    /// one letter, and syllables are estimated from groups of vowels, so the scores are
+    /// meant for English text.
+    pub fn readability(&self) -> Readability {
+        let mut sentences = 0;
+        let mut words = 0;
+        let mut syllable_count = 0;
+        let mut polysyllables = 0;
+        for range in sentence_ranges(&self.page_content) {
+            let sentence = &self.page_content[range];
+            let mut sentence_words = 0;
+            for word in sentence.split(|c: char| !(c.is_alphanumeric() || c == '\'')) {
+                if !word.chars().any(char::is_alphabetic) {
+                    continue;
+                }
+                let count = syllables(word);
+                sentence_words += 1;
+                syllable_count += count;
+                if count >= 3 {
+                    polysyllables += 1;
+                }
+            }
+            if sentence_words > 0 {
+                sentences += 1;
+                words += sentence_words;
+            }
+        }
+        if words == 0 {
+            return Readability::default();
+        }
+
+        let words_per_sentence = words as f64 / sentences as f64;
+        let syllables_per_word = syllable_count as f64 / words as f64;
+        Readability {
+            flesch_kincaid_grade: 0.39 * words_per_sentence + 11.8 * syllables_per_word - 15.59,
+            flesch_reading_ease: 206.835 - 1.015 * words_per_sentence - 84.6 * syllables_per_word,
+            smog_index: 1.043 * (polysyllables as f64 * 30.0 / sentences as f64).sqrt() + 3.1291,
+            average_sentence_length: words_per_sentence,
+            sentences,
+            words,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl IntoPy<PyObject> for Readability {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        let items: [(&str, PyObject); 6] = [
+            (
+                "flesch_kincaid_grade",
+                self.flesch_kincaid_grade.into_py(py),
+            ),
+            ("flesch_reading_ease", self.flesch_reading_ease.into_py(py)),
+            ("smog_index", self.smog_index.into_py(py)),
+            (
+                "average_sentence_length",
+                self.average_sentence_length.into_py(py),
+            ),
+            ("sentences", self.sentences.into_py(py)),
+            ("words", self.words.into_py(py)),
+        ];
+        items.into_py_dict(py).into()
+    }
+}
+
+/// Computes the readability scores of every document, in parallel.
+///
+/// # Returns
+///
+/// A list with a dictionary of scores for each document, in the same order as `docs`.
+/// See `Document.readability` for the keys.
+#[cfg(feature = "python")]
+#[pyfunction]
+pub fn readability_batch(py: Python, docs: &PyAny) -> PyResult<Vec<Readability>> {
+    let doc_vec: Vec<Document> = extract_documents(docs)?;
+    Ok(py.allow_threads(|| doc_vec.par_iter().map(Document::readability).collect()))
+}
@@ -0,0 +1,149 @@
+use crate::Document;
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// Abbreviations that end in a period without ending the sentence, in lowercase and
+/// without their final period, separated by whitespace.
+const ABBREVIATIONS: &str = "
+    mr mrs ms dr prof sr jr st mt vs etc e.g i.e cf al approx dept est fig figs inc ltd co
+    corp no nos vol vols p pp ed eds jan feb mar apr jun jul aug sep sept oct nov dec
+";
+
+static ABBREVIATION_SET: Lazy<HashSet<&'static str>> =
+    Lazy::new(|| ABBREVIATIONS.split_whitespace().collect());
+
+fn is_terminator(character: char) -> bool {
+    matches!(character, '.' | '!' | '?' | '…') || is_full_width_terminator(character)
+}
+
+/// Chinese and Japanese sentence endings, which aren't followed by a space.
+fn is_full_width_terminator(character: char) -> bool {
+    matches!(character, '。' | '！' | '？')
+}
+
+/// Quotes and brackets that may follow a terminator and still belong to the sentence.
+fn is_closing(character: char) -> bool {
+    matches!(
+        character,
+        '"' | '\'' | ')' | ']' | '”' | '’' | '»' | '」' | '』' | '）'
+    )
+}
+
+/// Whether the period ending at `end` belongs to an abbreviation or an initial, judging
+/// by the word before it.
+fn is_abbreviation(text: &str, end: usize) -> bool {
+    let start = text[..end]
+        .rfind(|character: char| character.is_whitespace() || character == '(')
+        .map_or(0, |index| index + 1);
+    let word = text[start..end].to_lowercase();
+    let mut letters = word.chars();
+    let is_initial =
+        matches!((letters.next(), letters.next()), (Some(letter), None) if letter.is_alphabetic());
+    is_initial || ABBREVIATION_SET.contains(word.as_str())
+}
+
+/// Returns the byte ranges of the sentences in `text`, trimmed of surrounding whitespace.
+///
+/// A sentence ends at a run of `.`, `!`, `?` or `…` (and any closing quotes or brackets
+/// after it) that is followed by whitespace, unless the next word starts in lowercase or
+/// the period belongs to a known abbreviation or an initial. The full width `。`, `！`
+/// and `？` end a sentence without any whitespace. A blank line always ends a
+/// sentence, so headings and list items without punctuation stand on their own.
+pub(crate) fn sentence_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut push = |range: Range<usize>| {
+        let sentence = &text[range.clone()];
+        let trimmed = sentence.trim_start();
+        let start = range.start + sentence.len() - trimmed.len();
+        let end = start + trimmed.trim_end().len();
+        if start < end {
+            ranges.push(start..end);
+        }
+    };
+
+    let mut start = 0;
+    let mut characters = text.char_indices().peekable();
+    while let Some((index, character)) = characters.next() {
+        if character == '\n' {
+            // A blank line, possibly holding other whitespace, ends the sentence.
+            let rest = &text[index + 1..];
+            let blank = rest.trim_start_matches([' ', '\t', '\r']);
+            if blank.starts_with('\n') {
+                push(start..index);
+                start = index + 1;
+            }
+            continue;
+        }
+        if !is_terminator(character) {
+            continue;
+        }
+        let mut end = index + character.len_utf8();
+        let mut single_period = character == '.';
+        while let Some(&(next_index, next)) = characters.peek() {
+            if is_terminator(next) || is_closing(next) {
+                single_period &= !is_terminator(next);
+                end = next_index + next.len_utf8();
+                characters.next();
+            } else {
+                break;
+            }
+        }
+        let rest = &text[end..];
+        if !rest.is_empty()
+            && !rest.starts_with(char::is_whitespace)
+            && !is_full_width_terminator(character)
+        {
+            continue;
+        }
+        let next_word_is_lowercase = rest
+            .trim_start()
+            .chars()
+            .next()
+            .is_some_and(char::is_lowercase);
+        if next_word_is_lowercase || (single_period && is_abbreviation(text, index)) {
+            continue;
+        }
+        push(start..end);
+        start = end;
+    }
+    push(start..text.len());
+    ranges
+}
+
+impl Document {
+    /// Splits page_content into sentences, trimmed of surrounding whitespace.
+    ///
+    /// A sentence ends at `.`, `!`, `?` or `…` (along with any closing quotes or brackets)
+    /// followed by whitespace and a word that doesn't start in lowercase. Periods after
+    /// common abbreviations ("Dr.", "e.g.") and initials don't end a sentence, and a blank
+    /// line always does, so headings and list items stand on their own. The full width
+    /// `。`, `！` and `？` of Chinese and Japanese text end a sentence on their own.
+    ///
+    /// For example:
+    ///
+    /// "Dr. Smith arrived. \"Hello!\" she said.\n\nNext steps"
+    ///     -> ["Dr. Smith arrived.", "\"Hello!\" she said.", "Next steps"]
+    pub fn sentences(&self) -> Vec<String> {
+        sentence_ranges(&self.page_content)
+            .into_iter()
+            .map(|range| self.page_content[range].to_string())
+            .collect()
+    }
+
+    /// Returns the `(start, end)` character offsets of each sentence in page_content,
+    /// matching `Document::sentences`, for highlighting sentences in the original text.
+    pub fn sentence_spans(&self) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        let mut characters = 0;
+        let mut bytes = 0;
+        for range in sentence_ranges(&self.page_content) {
+            characters += self.page_content[bytes..range.start].chars().count();
+            let start = characters;
+            characters += self.page_content[range.clone()].chars().count();
+            spans.push((start, characters));
+            bytes = range.end;
+        }
+        spans
+    }
+}
@@ -0,0 +1,910 @@
+use crate::blocks::{find_blocks, BlockKind};
+use crate::document::content_hash;
+use crate::sentences::sentence_ranges;
+use crate::{Document, Error};
+use std::ops::Range;
+use std::str::FromStr;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The separators `recursive_character_splitter` splits by, from coarsest to finest.
+pub(crate) const SEPARATORS: &[&str] = &["\n\n", "\n", " ", ""];
+
+/// What `Document::split_on_num_characters` counts towards its chunk size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SizeUnit {
+    /// User-perceived characters, so that an emoji with modifiers or a letter with
+    /// combining accents counts once.
+    Graphemes,
+    /// Unicode scalar values, what Rust calls `char`s.
+    Characters,
+    /// Bytes of UTF-8.
+    Bytes,
+}
+
+impl FromStr for SizeUnit {
+    type Err = Error;
+
+    fn from_str(unit: &str) -> Result<Self, Self::Err> {
+        match unit {
+            "graphemes" => Ok(SizeUnit::Graphemes),
+            "characters" => Ok(SizeUnit::Characters),
+            "bytes" => Ok(SizeUnit::Bytes),
+            _ => Err(Error::InvalidArgument(format!(
+                "Unknown unit \"{}\", expected \"graphemes\", \"characters\" or \"bytes\"",
+                unit
+            ))),
+        }
+    }
+}
+
+impl SizeUnit {
+    /// The size of `text` in this unit.
+    pub fn measure(self, text: &str) -> usize {
+        match self {
+            SizeUnit::Graphemes => text.graphemes(true).count(),
+            SizeUnit::Characters => text.chars().count(),
+            SizeUnit::Bytes => text.len(),
+        }
+    }
+
+    /// The size of a single grapheme cluster in this unit.
+    fn size(self, grapheme: &str) -> usize {
+        match self {
+            SizeUnit::Graphemes => 1,
+            SizeUnit::Characters => grapheme.chars().count(),
+            SizeUnit::Bytes => grapheme.len(),
+        }
+    }
+}
+
+/// Measures byte ranges of `text` in characters, the unit `chunk_size` is in unless a
+/// splitter is given a length function.
+///
+/// The internal splitting functions measure byte ranges of the text they split rather
+/// than string slices. In ASCII text every character is a byte, so measuring ranges of
+/// it costs nothing.
+pub(crate) fn char_length(text: &str) -> impl Fn(Range<usize>) -> usize + '_ {
+    let ascii = text.is_ascii();
+    move |range: Range<usize>| {
+        if ascii {
+            range.len()
+        } else {
+            text[range].chars().count()
+        }
+    }
+}
+
+/// The number of characters in `text`, the unit chunk sizes are in by default.
+pub(crate) fn char_count(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Collects the byte ranges of the pieces of `text[range]` that are no longer than
+/// `chunk_size`, as measured by `length`, splitting by `separators` in order until each
+/// piece fits.
+///
+/// Empty pieces are skipped, and a piece that still doesn't fit once the separators run
+/// out (a single character with a tiny `chunk_size`) is kept as it is.
+fn collect_pieces<L: Fn(Range<usize>) -> usize>(
+    text: &str,
+    range: Range<usize>,
+    chunk_size: usize,
+    separators: &[&str],
+    length: &L,
+    pieces: &mut Vec<Range<usize>>,
+) {
+    let Some((&separator, remaining_separators)) = separators.split_first() else {
+        pieces.push(range);
+        return;
+    };
+    let mut add_piece = |piece: Range<usize>| {
+        if piece.is_empty() {
+            return;
+        }
+        if length(piece.clone()) <= chunk_size {
+            pieces.push(piece);
+        } else {
+            collect_pieces(
+                text,
+                piece,
+                chunk_size,
+                remaining_separators,
+                length,
+                pieces,
+            );
+        }
+    };
+
+    let offset = range.start;
+    let slice = &text[range];
+    if separator.is_empty() {
+        for (index, character) in slice.char_indices() {
+            add_piece(offset + index..offset + index + character.len_utf8());
+        }
+    } else {
+        let mut piece_start = 0;
+        for (index, _) in slice.match_indices(separator) {
+            add_piece(offset + piece_start..offset + index);
+            piece_start = index + separator.len();
+        }
+        add_piece(offset + piece_start..offset + slice.len());
+    }
+}
+
+/// This is a function that splits text by `separators` until they are smaller than `chunk_size`.
+///
+/// The text is never copied: the result is the byte ranges of the splits within `text`.
+/// The smallest pieces are merged back together, along with the separators between
+/// them, for as long as the merged range stays within `chunk_size`.
+///
+/// # Arguments
+///
+/// * `text` - The string that you want to split.
+/// * `chunk_size` - The maximum size of the output splits.
+/// * `separators` - The characters to split the text by, in order of which separator to use.
+///
+/// # Returns
+///
+/// A Vector of byte ranges, each with a size smaller than chunk_size. The chunks will only
+/// be split until they are below the chunk_size threshold and then splitting not continue.
+fn split_text<L: Fn(Range<usize>) -> usize>(
+    text: &str,
+    chunk_size: usize,
+    separators: &[&str],
+    length: &L,
+) -> Vec<Range<usize>> {
+    let mut pieces = Vec::new();
+    collect_pieces(
+        text,
+        0..text.len(),
+        chunk_size,
+        separators,
+        length,
+        &mut pieces,
+    );
+    merge_pieces(pieces, chunk_size, length)
+}
+
+/// The lengths of a run of ranges in a text, for measuring spans of neighbouring ranges
+/// without measuring the same text over and over.
+///
+/// A span is as long as its first range plus each of the ranges after it along with the
+/// text before them. That is exact for characters and a close upper bound for tokens, and
+/// keeps splitting linear in the size of the text.
+struct RangeLengths {
+    /// The length of each range on its own.
+    alone: Vec<usize>,
+    /// The length of each range along with the text since the end of the previous range.
+    joined: Vec<usize>,
+}
+
+impl RangeLengths {
+    fn new<L: Fn(Range<usize>) -> usize>(ranges: &[Range<usize>], length: &L) -> Self {
+        let alone = ranges.iter().map(|range| length(range.clone())).collect();
+        let joined = ranges
+            .iter()
+            .enumerate()
+            .map(|(index, range)| match index {
+                0 => length(range.clone()),
+                _ => length(ranges[index - 1].end..range.end),
+            })
+            .collect();
+        RangeLengths { alone, joined }
+    }
+
+    /// The length of the span from range `first` to range `last`, inclusive.
+    fn span(&self, first: usize, last: usize) -> usize {
+        self.alone[first] + self.joined[first + 1..=last].iter().sum::<usize>()
+    }
+}
+
+/// Merges neighbouring pieces, along with the text between them, for as long as the
+/// merged range stays within `chunk_size`.
+///
+/// The length of a merged range is added up from its pieces, like `RangeLengths::span`.
+fn merge_pieces<L: Fn(Range<usize>) -> usize>(
+    pieces: Vec<Range<usize>>,
+    chunk_size: usize,
+    length: &L,
+) -> Vec<Range<usize>> {
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(pieces.len());
+    let mut merged_length = 0;
+    for piece in pieces {
+        if let Some(current) = merged.last_mut() {
+            let joined_length = length(current.end..piece.end);
+            if merged_length + joined_length <= chunk_size {
+                current.end = piece.end;
+                merged_length += joined_length;
+                continue;
+            }
+        }
+        merged_length = length(piece.clone());
+        merged.push(piece);
+    }
+    merged
+}
+
+/// This function will split text into chunks 1/3 the size of `chunk_size`, and then
+/// merge them back together such that there is a 1/3rd overlap in the returned chunks.
+///
+/// For example, if you get [A, B, C, D, E, F, G] back from `split_text` function, this function
+/// will merge chunks and return [ABC, CDE, EFG]. This provides an overlap that is useful
+/// for RAG projects. Individual chunks will never exceed `chunk_size` limit passed in:
+/// when the separators between three splits would push a chunk over the limit, it takes
+/// fewer splits instead.
+///
+/// # Arguments
+///
+/// * `text` - The string that you want to split.
+/// * `chunk_size` - The maximum size of the output splits.
+/// * `separators` - The characters to split the text by, in order of which separator to use.
+/// * `length` - Measures the size of a byte range of `text`, in the unit of `chunk_size`.
+///
+/// # Returns
+///
+/// A Vector of Strings, each with a size smaller than chunk_size. The chunks will only
+/// be split until they are below the chunk_size threshold and then splitting not continue.
+/// Each chunk will overlap with it's neighbor chunks by about 1/3 of the `chunk_size`.
+/// The chunks are the only strings allocated.
+pub(crate) fn split_and_merge<L: Fn(Range<usize>) -> usize>(
+    text: &str,
+    chunk_size: usize,
+    separators: &[&str],
+    length: &L,
+) -> Vec<String> {
+    let splits = split_text(text, chunk_size / 3, separators, length);
+    overlap_splits(&splits, chunk_size, length)
+        .into_iter()
+        .map(|chunk| text[chunk].to_string())
+        .collect()
+}
+
+/// Joins up to three neighbouring splits into each chunk, starting every chunk at the
+/// last split of the one before it, without letting a chunk grow past `chunk_size`.
+///
+/// A split that is larger than `chunk_size / 3` takes the place of several, and one
+/// larger than `chunk_size` becomes a chunk of its own.
+fn overlap_splits<L: Fn(Range<usize>) -> usize>(
+    splits: &[Range<usize>],
+    chunk_size: usize,
+    length: &L,
+) -> Vec<Range<usize>> {
+    if splits.len() < 2 {
+        // Nothing to merge, the text already fits in a single chunk.
+        return splits.to_vec();
+    }
+    let lengths = RangeLengths::new(splits, length);
+    let mut result = Vec::new();
+    let mut first = 0;
+    loop {
+        // Take up to three splits, then start the next chunk at the last one taken so
+        // that neighbouring chunks share a split.
+        let last = (first..splits.len().min(first + 3))
+            .rev()
+            .find(|&last| lengths.span(first, last) <= chunk_size)
+            .unwrap_or(first);
+        result.push(splits[first].start..splits[last].end);
+        if last == splits.len() - 1 {
+            break;
+        }
+        first = last.max(first + 1);
+    }
+    result
+}
+
+/// Packs whole sentences into chunks of up to `chunk_size`, starting every chunk with the
+/// last `overlap_sentences` sentences of the chunk before it.
+///
+/// A sentence that is larger than `chunk_size` is split by words and its pieces are
+/// packed like sentences. The overlap shrinks when repeating all of it would leave no room
+/// for the next sentence, so that every chunk adds something new.
+fn split_by_sentences<L: Fn(Range<usize>) -> usize>(
+    text: &str,
+    chunk_size: usize,
+    overlap_sentences: usize,
+    length: &L,
+) -> Vec<String> {
+    let mut sentences = Vec::new();
+    for sentence in sentence_ranges(text) {
+        if length(sentence.clone()) <= chunk_size {
+            sentences.push(sentence);
+        } else {
+            collect_pieces(
+                text,
+                sentence,
+                chunk_size,
+                &SEPARATORS[2..],
+                length,
+                &mut sentences,
+            );
+        }
+    }
+    let lengths = RangeLengths::new(&sentences, length);
+
+    let mut result = Vec::new();
+    let mut first = 0;
+    while first < sentences.len() {
+        let mut last = first;
+        while last + 1 < sentences.len() && lengths.span(first, last + 1) <= chunk_size {
+            last += 1;
+        }
+        result.push(text[sentences[first].start..sentences[last].end].to_string());
+        let next = last + 1;
+        if next == sentences.len() {
+            break;
+        }
+        first = (next.saturating_sub(overlap_sentences).max(first + 1)..next)
+            .find(|&candidate| lengths.span(candidate, next) <= chunk_size)
+            .unwrap_or(next);
+    }
+    result
+}
+
+/// Splits a table that doesn't fit in one chunk into groups of rows, repeating the
+/// `header` at the top of every group so that each chunk can be read on its own.
+///
+/// A row that doesn't fit under the header on its own is split like any other text.
+fn split_table(header: &str, body: &str, chunk_size: usize) -> Vec<String> {
+    let header = header.trim_end_matches(['\n', '\r']);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let header_size = char_count(header);
+    let mut current_size = 0;
+    for row in body.lines() {
+        let row_size = char_count(row);
+        if header_size + 1 + row_size > chunk_size {
+            if !current.is_empty() {
+                chunks.push(std::mem::take(&mut current));
+            }
+            chunks.extend(split_and_merge(
+                row,
+                chunk_size,
+                &SEPARATORS[2..],
+                &char_length(row),
+            ));
+            continue;
+        }
+        if !current.is_empty() && current_size + 1 + row_size > chunk_size {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if current.is_empty() {
+            current.push_str(header);
+            current_size = header_size;
+        }
+        current.push('\n');
+        current.push_str(row);
+        current_size += 1 + row_size;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Splits markdown like `split_and_merge`, but keeps every fenced code block and table
+/// whole, so that no chunk starts or ends inside one.
+///
+/// A table that is larger than `chunk_size` is split into groups of rows with its header
+/// repeated, see `split_table`. Any other block that is larger than `chunk_size` is split
+/// between lines, and only a line that is larger than `chunk_size` is split by words.
+///
+/// # Returns
+///
+/// Every chunk, along with whether it contains a table or part of one.
+pub(crate) fn split_markdown(text: &str, chunk_size: usize) -> Vec<(String, bool)> {
+    let intermediate_size = chunk_size / 3;
+    let length = char_length(text);
+    let blocks = find_blocks(text);
+    let tables: Vec<Range<usize>> = blocks
+        .iter()
+        .filter(|block| matches!(block.kind, BlockKind::Table { .. }))
+        .map(|block| block.range.clone())
+        .collect();
+    let mut chunks = Vec::new();
+    let mut pieces = Vec::new();
+    // Turns the pieces collected so far into overlapping chunks.
+    let flush = |pieces: &mut Vec<Range<usize>>, chunks: &mut Vec<(String, bool)>| {
+        let splits = merge_pieces(std::mem::take(pieces), intermediate_size, &length);
+        for chunk in overlap_splits(&splits, chunk_size, &length) {
+            let has_table = tables
+                .iter()
+                .any(|table| table.start < chunk.end && chunk.start < table.end);
+            chunks.push((text[chunk].to_string(), has_table));
+        }
+    };
+
+    let mut position = 0;
+    for block in &blocks {
+        let range = block.range.clone();
+        collect_pieces(
+            text,
+            position..range.start,
+            intermediate_size,
+            SEPARATORS,
+            &length,
+            &mut pieces,
+        );
+        match block.kind {
+            _ if length(range.clone()) <= chunk_size => pieces.push(range.clone()),
+            BlockKind::Table { body_start } if length(range.start..body_start) < chunk_size => {
+                flush(&mut pieces, &mut chunks);
+                let header = &text[range.start..body_start];
+                let body = &text[body_start..range.end];
+                chunks.extend(
+                    split_table(header, body, chunk_size)
+                        .into_iter()
+                        .map(|chunk| (chunk, true)),
+                );
+            }
+            // Lines of up to `chunk_size` stay whole, even in a block of many lines.
+            _ => collect_pieces(
+                text,
+                range.clone(),
+                chunk_size,
+                &SEPARATORS[1..],
+                &length,
+                &mut pieces,
+            ),
+        }
+        position = range.end;
+    }
+    collect_pieces(
+        text,
+        position..text.len(),
+        intermediate_size,
+        SEPARATORS,
+        &length,
+        &mut pieces,
+    );
+    flush(&mut pieces, &mut chunks);
+    chunks
+}
+
+impl Document {
+    /// An opinionated splitter based on LangChain's [RecursiveCharacterTextSplitter](https://api.python.langchain.com/en/latest/text_splitter/langchain.text_splitter.RecursiveCharacterTextSplitter.html).
+    ///
+    /// This splitter will always default to 1/3 of the chunk_size as the chunk_overlap.
+    ///
+    /// When `provenance` is true, every chunk gets `chunk_index`, `total_chunks` and
+    /// `parent_id` metadata. See `Document::add_provenance`.
+    ///
+    /// When `context_template` is given, it is rendered from each chunk's metadata and
+    /// prepended to the chunk. See `Document::add_context_header`.
+    #[tracing::instrument(level = "trace", skip_all, fields(bytes = self.page_content.len()))]
+    pub fn recursive_character_splitter(
+        &self,
+        chunk_size: usize,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+    ) -> Vec<Document> {
+        let length = char_length(&self.page_content);
+        let split_docs = split_and_merge(&self.page_content, chunk_size, SEPARATORS, &length);
+        self.chunks_from(split_docs, provenance, id_key, context_template)
+    }
+
+    /// `recursive_character_splitter` with `chunk_size` measured by `length_function`
+    /// instead of in characters, for example in tokens.
+    ///
+    /// ```
+    /// use rs_document::Document;
+    /// use std::collections::HashMap;
+    ///
+    /// let document = Document::new("one two three four five six", HashMap::new());
+    /// let words = |text: &str| text.split_whitespace().count();
+    /// let chunks = document.recursive_character_splitter_with_length(3, &words, false, "id", None);
+    /// assert_eq!(chunks[0].page_content, "one two three");
+    /// ```
+    #[tracing::instrument(level = "trace", skip_all, fields(bytes = self.page_content.len()))]
+    pub fn recursive_character_splitter_with_length(
+        &self,
+        chunk_size: usize,
+        length_function: &dyn Fn(&str) -> usize,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+    ) -> Vec<Document> {
+        let length = |range: Range<usize>| length_function(&self.page_content[range]);
+        let split_docs = split_and_merge(&self.page_content, chunk_size, SEPARATORS, &length);
+        self.chunks_from(split_docs, provenance, id_key, context_template)
+    }
+
+    /// A variant of `recursive_character_splitter` whose chunks hold whole sentences and
+    /// overlap by `overlap_sentences` sentences instead of by a third of `chunk_size`.
+    ///
+    /// Sentences are found with `Document::sentences` and packed into chunks of up to
+    /// `chunk_size`, and every chunk starts with the last `overlap_sentences` sentences of
+    /// the one before it, so no chunk starts mid-sentence. Fewer sentences are repeated
+    /// when all of them wouldn't leave room for a new one, and a sentence that is larger
+    /// than `chunk_size` on its own is split by words.
+    ///
+    /// `provenance`, `id_key` and `context_template` work the same way as in
+    /// `recursive_character_splitter`.
+    #[tracing::instrument(level = "trace", skip_all, fields(bytes = self.page_content.len()))]
+    pub fn sentence_overlap_splitter(
+        &self,
+        chunk_size: usize,
+        overlap_sentences: usize,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+    ) -> Vec<Document> {
+        let split_docs = split_by_sentences(
+            &self.page_content,
+            chunk_size,
+            overlap_sentences,
+            &char_length(&self.page_content),
+        );
+        self.chunks_from(split_docs, provenance, id_key, context_template)
+    }
+
+    /// `sentence_overlap_splitter` with `chunk_size` measured by `length_function`
+    /// instead of in characters, for example in tokens.
+    #[tracing::instrument(level = "trace", skip_all, fields(bytes = self.page_content.len()))]
+    pub fn sentence_overlap_splitter_with_length(
+        &self,
+        chunk_size: usize,
+        overlap_sentences: usize,
+        length_function: &dyn Fn(&str) -> usize,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+    ) -> Vec<Document> {
+        let length = |range: Range<usize>| length_function(&self.page_content[range]);
+        let split_docs =
+            split_by_sentences(&self.page_content, chunk_size, overlap_sentences, &length);
+        self.chunks_from(split_docs, provenance, id_key, context_template)
+    }
+
+    /// Splits the document into one chunk per group of `sentences_per_chunk` sentences,
+    /// for sentence-window retrieval: the small chunks are embedded, and the window
+    /// around a matching chunk is what gets returned.
+    ///
+    /// Sentences are found with `Document::sentences`. Every chunk gets `window_before`
+    /// and `window_after` metadata with up to `window_size` sentences before and after
+    /// it, as they appear in the document, which are empty at the start and the end.
+    ///
+    /// `provenance`, `id_key` and `context_template` work the same way as in
+    /// `recursive_character_splitter`.
+    ///
+    /// Returns `Error::InvalidArgument` when `sentences_per_chunk` is zero.
+    ///
+    /// ```
+    /// use rs_document::Document;
+    /// use std::collections::HashMap;
+    ///
+    /// let document = Document::new("One. Two. Three. Four.", HashMap::new());
+    /// let chunks = document.sentence_window_splitter(1, 2, false, "id", None).unwrap();
+    /// assert_eq!(chunks[1].page_content, "Two.");
+    /// assert_eq!(chunks[1].metadata["window_before"], "One.");
+    /// assert_eq!(chunks[1].metadata["window_after"], "Three. Four.");
+    /// ```
+    #[tracing::instrument(level = "trace", skip_all, fields(bytes = self.page_content.len()))]
+    pub fn sentence_window_splitter(
+        &self,
+        sentences_per_chunk: usize,
+        window_size: usize,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+    ) -> Result<Vec<Document>, Error> {
+        if sentences_per_chunk == 0 {
+            return Err(Error::InvalidArgument(
+                "sentences_per_chunk must be at least 1".to_string(),
+            ));
+        }
+        let text = &self.page_content;
+        let sentences = sentence_ranges(text);
+        let mut chunks: Vec<Document> = (0..sentences.len())
+            .step_by(sentences_per_chunk)
+            .map(|first| {
+                let last = (first + sentences_per_chunk).min(sentences.len()) - 1;
+                let before = first.saturating_sub(window_size);
+                let after = (last + window_size).min(sentences.len() - 1);
+                let mut metadata = self.metadata.clone();
+                metadata.insert(
+                    "window_before".to_string(),
+                    text[sentences[before].start..sentences[first].start]
+                        .trim_end()
+                        .to_string(),
+                );
+                metadata.insert(
+                    "window_after".to_string(),
+                    text[sentences[last].end..sentences[after].end]
+                        .trim_start()
+                        .to_string(),
+                );
+                Document {
+                    page_content: text[sentences[first].start..sentences[last].end].to_string(),
+                    metadata,
+                }
+            })
+            .collect();
+        self.finish_chunks(&mut chunks, provenance, id_key, context_template);
+        Ok(chunks)
+    }
+
+    /// A variant of `recursive_character_splitter` for markdown that never splits inside
+    /// a fenced code block or a table.
+    ///
+    /// Blocks are kept whole in a single chunk unless they alone exceed `chunk_size`. A
+    /// table that is too large is split into groups of rows, each under a copy of the
+    /// table's header, and any other block is split between lines rather than mid-line.
+    /// Everything else is split the same way as `recursive_character_splitter`, with the
+    /// same 1/3 overlap.
+    ///
+    /// Tables are pipe tables or columns of text aligned with spaces or tabs, and chunks
+    /// that contain one get `has_table` metadata set to `"true"`.
+    #[tracing::instrument(level = "trace", skip_all, fields(bytes = self.page_content.len()))]
+    pub fn markdown_splitter(
+        &self,
+        chunk_size: usize,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+    ) -> Vec<Document> {
+        let (split_docs, has_table): (Vec<String>, Vec<bool>) =
+            split_markdown(&self.page_content, chunk_size)
+                .into_iter()
+                .unzip();
+        let mut result = self.chunks_from(split_docs, provenance, id_key, context_template);
+        for (chunk, has_table) in result.iter_mut().zip(has_table) {
+            if has_table {
+                chunk
+                    .metadata
+                    .insert("has_table".to_string(), "true".to_string());
+            }
+        }
+        result
+    }
+
+    /// Turns the split texts into chunks carrying this document's metadata, with
+    /// provenance and context headers added when asked for.
+    fn chunks_from(
+        &self,
+        texts: Vec<String>,
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+    ) -> Vec<Document> {
+        let mut result: Vec<Document> = texts
+            .into_iter()
+            .map(|text| Document {
+                page_content: text,
+                metadata: self.metadata.clone(),
+            })
+            .collect();
+        self.finish_chunks(&mut result, provenance, id_key, context_template);
+        result
+    }
+
+    /// Adds provenance and context headers to chunks split from this document, when
+    /// asked for.
+    ///
+    /// The splitters do this themselves. It is for chunks that were split with
+    /// `provenance` off and no `context_template`, and then filtered with
+    /// `Document::filter_chunks`, so that `chunk_index` and `total_chunks` only count the
+    /// chunks that were kept.
+    pub fn finish_chunks(
+        &self,
+        chunks: &mut [Document],
+        provenance: bool,
+        id_key: &str,
+        context_template: Option<&str>,
+    ) {
+        if provenance {
+            self.add_provenance(chunks, id_key);
+        }
+        if let Some(template) = context_template {
+            for chunk in chunks.iter_mut() {
+                chunk.add_context_header(template);
+            }
+        }
+    }
+
+    /// Drops the chunks that are shorter than `min_chunk_size`, as measured by
+    /// `length_function`, and when `drop_empty` is true the chunks that are only
+    /// whitespace.
+    ///
+    /// ```
+    /// use rs_document::Document;
+    /// use std::collections::HashMap;
+    ///
+    /// let document = Document::new("A paragraph of text.\n\nTail", HashMap::new());
+    /// let mut chunks = document.recursive_character_splitter(24, false, "id", None);
+    /// Document::filter_chunks(&mut chunks, 15, true, &str::len);
+    /// document.finish_chunks(&mut chunks, true, "id", None);
+    /// assert_eq!(chunks.len(), 1);
+    /// assert_eq!(chunks[0].metadata["total_chunks"], "1");
+    /// ```
+    pub fn filter_chunks(
+        chunks: &mut Vec<Document>,
+        min_chunk_size: usize,
+        drop_empty: bool,
+        length_function: &dyn Fn(&str) -> usize,
+    ) {
+        chunks.retain(|chunk| {
+            !(drop_empty && chunk.page_content.trim().is_empty())
+                && (min_chunk_size == 0 || length_function(&chunk.page_content) >= min_chunk_size)
+        });
+    }
+
+    /// A basic splitter to split on a number of characters.
+    ///
+    /// Chunks never break up a grapheme cluster. `unit` chooses whether `num_characters`
+    /// and `overlap` count graphemes, chars or bytes; a grapheme larger than
+    /// `num_characters` on its own becomes a chunk by itself. Each chunk starts with the
+    /// last `overlap` units of the chunk before it, rounded down to whole graphemes.
+    ///
+    /// When `provenance` is true, every chunk gets `chunk_index`, `total_chunks` and
+    /// `parent_id` metadata. See `Document::add_provenance`.
+    ///
+    /// Returns `Error::InvalidArgument` when `num_characters` is zero or `overlap` isn't
+    /// smaller than it.
+    ///
+    /// ```
+    /// use rs_document::{Document, SizeUnit};
+    /// use std::collections::HashMap;
+    ///
+    /// let document = Document::new("abcdefgh", HashMap::new());
+    /// let chunks = document
+    ///     .split_on_num_characters(4, 1, SizeUnit::Graphemes, false, "id")
+    ///     .unwrap();
+    /// let contents: Vec<&str> = chunks.iter().map(|chunk| chunk.page_content.as_str()).collect();
+    /// assert_eq!(contents, ["abcd", "defg", "gh"]);
+    /// ```
+    #[tracing::instrument(level = "trace", skip_all, fields(bytes = self.page_content.len()))]
+    pub fn split_on_num_characters(
+        &self,
+        num_characters: u32,
+        overlap: u32,
+        unit: SizeUnit,
+        provenance: bool,
+        id_key: &str,
+    ) -> Result<Vec<Document>, Error> {
+        if num_characters == 0 {
+            return Err(Error::InvalidArgument(
+                "num_characters must be greater than 0".to_string(),
+            ));
+        }
+        if overlap >= num_characters {
+            return Err(Error::InvalidArgument(
+                "overlap must be smaller than num_characters".to_string(),
+            ));
+        }
+        let (num_characters, overlap) = (num_characters as usize, overlap as usize);
+        let graphemes: Vec<(usize, usize)> = self
+            .page_content
+            .grapheme_indices(true)
+            .map(|(start, grapheme)| (start, unit.size(grapheme)))
+            .collect();
+
+        let mut result = Vec::new();
+        let mut first = 0;
+        while first < graphemes.len() {
+            // Take at least one grapheme, then as many more as fit.
+            let mut end = first + 1;
+            let mut size = graphemes[first].1;
+            while end < graphemes.len() && size + graphemes[end].1 <= num_characters {
+                size += graphemes[end].1;
+                end += 1;
+            }
+            let end_byte = graphemes
+                .get(end)
+                .map_or(self.page_content.len(), |&(start, _)| start);
+            result.push(Document {
+                page_content: self.page_content[graphemes[first].0..end_byte].to_string(),
+                metadata: self.metadata.clone(),
+            });
+            if end == graphemes.len() {
+                break;
+            }
+            // Step back over as many graphemes as fit in the overlap, but always move on
+            // by at least one.
+            let mut next = end;
+            let mut overlap_size = 0;
+            while next - 1 > first && overlap_size + graphemes[next - 1].1 <= overlap {
+                overlap_size += graphemes[next - 1].1;
+                next -= 1;
+            }
+            first = next;
+        }
+        if provenance {
+            self.add_provenance(&mut result, id_key);
+        }
+        Ok(result)
+    }
+
+    /// Splits the document into large parent chunks and small child chunks, for the
+    /// parent document retriever pattern.
+    ///
+    /// Parent chunks are produced with `recursive_character_splitter(parent_size)` and
+    /// each parent is split again with `child_size`. Every chunk carries the provenance
+    /// metadata and its own id under `id_key`, so a child's `parent_id` is the id of the
+    /// parent chunk it came from.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the parent chunks and the child chunks.
+    #[tracing::instrument(level = "trace", skip_all, fields(bytes = self.page_content.len()))]
+    pub fn hierarchical_split(
+        &self,
+        parent_size: usize,
+        child_size: usize,
+        id_key: &str,
+    ) -> Result<(Vec<Document>, Vec<Document>), Error> {
+        if child_size >= parent_size {
+            return Err(Error::InvalidArgument(
+                "child_size must be smaller than parent_size".to_string(),
+            ));
+        }
+        let mut parents = self.recursive_character_splitter(parent_size, true, id_key, None);
+        Document::assign_chunk_ids(&mut parents, id_key);
+
+        let children = parents
+            .iter()
+            .flat_map(|parent| {
+                let mut children =
+                    parent.recursive_character_splitter(child_size, true, id_key, None);
+                Document::assign_chunk_ids(&mut children, id_key);
+                children
+            })
+            .collect();
+
+        Ok((parents, children))
+    }
+
+    /// Gives every chunk an id under `id_key` built from its `parent_id` and `chunk_index`.
+    ///
+    /// The chunks must already carry provenance metadata from `add_provenance`.
+    fn assign_chunk_ids(chunks: &mut [Document], id_key: &str) {
+        for chunk in chunks.iter_mut() {
+            let id = format!(
+                "{}-{}",
+                chunk.metadata["parent_id"], chunk.metadata["chunk_index"]
+            );
+            chunk.metadata.insert(id_key.to_string(), id);
+        }
+    }
+
+    /// The `parent_id` that `add_provenance` gives the chunks of this document: its
+    /// `id_key` metadata when present, and otherwise a hash of its page_content.
+    pub(crate) fn parent_id(&self, id_key: &str) -> String {
+        self.metadata
+            .get(id_key)
+            .cloned()
+            .unwrap_or_else(|| content_hash(&self.page_content))
+    }
+
+    /// Points every chunk's `parent_id` at `parent_id`, for chunks split from a copy of
+    /// a document whose page_content was cleaned first, so that the fallback id is the
+    /// hash of the text the caller passed in.
+    pub(crate) fn set_parent_id(chunks: &mut [Document], parent_id: &str) {
+        for chunk in chunks {
+            chunk
+                .metadata
+                .insert("parent_id".to_string(), parent_id.to_string());
+        }
+    }
+
+    /// Links chunks split from this document back to it through their metadata.
+    ///
+    /// Each chunk gets a `chunk_index` (its position among the chunks), `total_chunks`,
+    /// and a `parent_id`. The parent id is the value of this document's `id_key`
+    /// metadata when present, and otherwise a hash of its page_content.
+    fn add_provenance(&self, chunks: &mut [Document], id_key: &str) {
+        let parent_id = self.parent_id(id_key);
+        let total_chunks = chunks.len().to_string();
+        for (index, chunk) in chunks.iter_mut().enumerate() {
+            chunk
+                .metadata
+                .insert("chunk_index".to_string(), index.to_string());
+            chunk
+                .metadata
+                .insert("total_chunks".to_string(), total_chunks.clone());
+            chunk
+                .metadata
+                .insert("parent_id".to_string(), parent_id.clone());
+        }
+    }
+}
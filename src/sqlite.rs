@@ -0,0 +1,102 @@
+use crate::loaders::check_loader_keys;
+use crate::Document;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Converts a SQLite error into a Python `ValueError`.
+fn to_py_error(error: rusqlite::Error) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+/// Converts a SQLite value into a string, returning `None` for `NULL`.
+///
+/// Blobs are decoded as UTF-8, replacing any invalid sequences.
+fn value_to_string(value: ValueRef) -> Option<String> {
+    match value {
+        ValueRef::Null => None,
+        ValueRef::Integer(integer) => Some(integer.to_string()),
+        ValueRef::Real(real) => Some(real.to_string()),
+        ValueRef::Text(text) | ValueRef::Blob(text) => {
+            Some(String::from_utf8_lossy(text).into_owned())
+        }
+    }
+}
+
+/// Runs a query against a SQLite database, turning every row into a Document.
+///
+/// The database is opened read only. The `content_column` of each row becomes the
+/// page_content and every other column is copied into metadata, leaving out `NULL`s.
+/// Each document also gets the `source` path and the zero based `row` number, and a
+/// metadata column with either name is an error rather than being overwritten.
+///
+/// # Arguments
+///
+/// * `path` - The path of the SQLite database.
+/// * `query` - The `SELECT` statement to run.
+/// * `content_column` - The column of the query result that becomes the page_content.
+#[pyfunction]
+pub fn load_sqlite(
+    py: Python,
+    path: PathBuf,
+    query: &str,
+    content_column: &str,
+) -> PyResult<Vec<Document>> {
+    py.allow_threads(|| {
+        let connection = Connection::open_with_flags(
+            &path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(to_py_error)?;
+        let mut statement = connection.prepare(query).map_err(to_py_error)?;
+        let columns: Vec<String> = statement
+            .column_names()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let content_index = columns
+            .iter()
+            .position(|column| column == content_column)
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Column \"{}\" is not in the query result",
+                    content_column
+                ))
+            })?;
+        check_loader_keys(
+            "load_sqlite",
+            "Column",
+            columns
+                .iter()
+                .filter(|column| *column != content_column)
+                .map(String::as_str),
+            "leave it out of the query or rename it with AS",
+        )?;
+
+        let source = path.display().to_string();
+        let mut rows = statement.query([]).map_err(to_py_error)?;
+        let mut documents = Vec::new();
+        while let Some(row) = rows.next().map_err(to_py_error)? {
+            let mut page_content = String::new();
+            let mut metadata = HashMap::new();
+            for (index, column) in columns.iter().enumerate() {
+                let value = value_to_string(row.get_ref(index).map_err(to_py_error)?);
+                if index == content_index {
+                    page_content = value.unwrap_or_default();
+                } else if let Some(value) = value {
+                    metadata.insert(column.clone(), value);
+                }
+            }
+            metadata.insert("source".to_string(), source.clone());
+            metadata.insert("row".to_string(), documents.len().to_string());
+            documents.push(Document {
+                page_content,
+                metadata,
+            });
+        }
+        Ok(documents)
+    })
+}
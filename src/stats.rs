@@ -0,0 +1,281 @@
+use crate::python::CleanAndSplit;
+use crate::{extract_documents, tokens, Document};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::IntoPyDict;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// The percentiles reported for every length, as `(key, percent)`.
+const PERCENTILES: [(&str, usize); 4] = [("p50", 50), ("p90", 90), ("p95", 95), ("p99", 99)];
+
+/// The total, mean, extremes and percentiles of a list of lengths.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LengthStats {
+    pub total: usize,
+    pub mean: f64,
+    pub min: usize,
+    pub max: usize,
+    /// The lengths at each of `PERCENTILES`, by the nearest-rank method.
+    pub percentiles: [usize; 4],
+}
+
+impl LengthStats {
+    /// Summarizes `lengths`, sorting them in place. Every field is 0 when there are none.
+    fn new(lengths: &mut [usize]) -> Self {
+        if lengths.is_empty() {
+            return LengthStats::default();
+        }
+        lengths.par_sort_unstable();
+        let total: usize = lengths.iter().sum();
+        // The nearest rank of `percent` is the smallest length that at least `percent`
+        // of the lengths are no longer than.
+        let rank = |percent: usize| (percent * lengths.len()).div_ceil(100).max(1) - 1;
+        LengthStats {
+            total,
+            mean: total as f64 / lengths.len() as f64,
+            min: lengths[0],
+            max: lengths[lengths.len() - 1],
+            percentiles: PERCENTILES.map(|(_, percent)| lengths[rank(percent)]),
+        }
+    }
+}
+
+impl IntoPy<PyObject> for LengthStats {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        let mut items: Vec<(&str, PyObject)> = vec![
+            ("total", self.total.into_py(py)),
+            ("mean", self.mean.into_py(py)),
+            ("min", self.min.into_py(py)),
+            ("max", self.max.into_py(py)),
+        ];
+        for ((key, _), value) in PERCENTILES.iter().zip(self.percentiles) {
+            items.push((key, value.into_py(py)));
+        }
+        items.into_py_dict(py).into()
+    }
+}
+
+/// A range of chunk sizes, inclusive at both ends, and how many chunks fall into it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bucket {
+    pub min: usize,
+    pub max: usize,
+    pub count: usize,
+}
+
+impl IntoPy<PyObject> for Bucket {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        [("min", self.min), ("max", self.max), ("count", self.count)]
+            .into_py_dict(py)
+            .into()
+    }
+}
+
+/// Counts `sizes` in `bins` buckets of equal width from 0 to `chunk_size`, and in one
+/// more bucket for the sizes over `chunk_size`, if there are any.
+fn histogram(sizes: &[usize], chunk_size: usize, bins: usize) -> Vec<Bucket> {
+    let width = chunk_size.div_ceil(bins).max(1);
+    let mut counts = vec![0; bins + 1];
+    for &size in sizes {
+        let bucket = if size > chunk_size {
+            bins
+        } else {
+            (size.saturating_sub(1) / width).min(bins - 1)
+        };
+        counts[bucket] += 1;
+    }
+    let mut buckets: Vec<Bucket> = (0..bins)
+        .map(|bucket| Bucket {
+            min: if bucket == 0 { 0 } else { bucket * width + 1 },
+            max: ((bucket + 1) * width).min(chunk_size),
+            count: counts[bucket],
+        })
+        .filter(|bucket| bucket.min <= bucket.max)
+        .collect();
+    if counts[bins] > 0 {
+        buckets.push(Bucket {
+            min: chunk_size + 1,
+            max: sizes.iter().copied().max().unwrap_or(chunk_size),
+            count: counts[bins],
+        });
+    }
+    buckets
+}
+
+/// The chunks of the trial split in `corpus_stats`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChunkStats {
+    pub count: usize,
+    pub sizes: LengthStats,
+    pub histogram: Vec<Bucket>,
+}
+
+impl IntoPy<PyObject> for ChunkStats {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        let items: [(&str, PyObject); 3] = [
+            ("count", self.count.into_py(py)),
+            ("sizes", self.sizes.into_py(py)),
+            ("histogram", self.histogram.into_py(py)),
+        ];
+        items.into_py_dict(py).into()
+    }
+}
+
+/// The statistics of a corpus, from `corpus_stats`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CorpusStats {
+    pub documents: usize,
+    pub characters: LengthStats,
+    pub words: LengthStats,
+    pub tokens: LengthStats,
+    pub chunks: Option<ChunkStats>,
+    /// The share of the documents that have each metadata key.
+    pub metadata_coverage: HashMap<String, f64>,
+}
+
+impl IntoPy<PyObject> for CorpusStats {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        let items: [(&str, PyObject); 6] = [
+            ("documents", self.documents.into_py(py)),
+            ("characters", self.characters.into_py(py)),
+            ("words", self.words.into_py(py)),
+            ("tokens", self.tokens.into_py(py)),
+            ("chunks", self.chunks.into_py(py)),
+            ("metadata_coverage", self.metadata_coverage.into_py(py)),
+        ];
+        items.into_py_dict(py).into()
+    }
+}
+
+/// Computes the statistics that help pick a chunk size for a corpus, in parallel.
+///
+/// The lengths of the documents are counted in characters, in words separated by
+/// whitespace, and in tokens of `encoding` (see `count_tokens`). When `chunk_size` is
+/// given, the documents are also cleaned and split like `clean_and_split_docs` does with
+/// the same `chunk_size`, `unit`, `encoding` and `overlap_sentences`, and the sizes of the
+/// chunks are summarized and counted in a histogram. Chunk sizes are measured the way the
+/// splitter measures `chunk_size`, so the histogram shows how full the chunks are.
+///
+/// # Arguments
+///
+/// * `docs` - The documents to summarize.
+/// * `chunk_size` - The chunk size to try, or `None` to skip the trial split.
+/// * `unit` - `"characters"` or `"tokens"`, what `chunk_size` counts.
+/// * `encoding` - The encoding the tokens are counted in.
+/// * `overlap_sentences` - Split with overlapping sentences, as in `clean_and_split_docs`.
+/// * `bins` - How many buckets of equal width from 0 to `chunk_size` the histogram has.
+///   Chunks over `chunk_size` are counted in one more bucket at the end.
+///
+/// # Returns
+///
+/// A dictionary with the number of `documents`, the `characters`, `words` and `tokens`
+/// length statistics (`total`, `mean`, `min`, `max`, `p50`, `p90`, `p95` and `p99`),
+/// the `chunks` of the trial split (their `count`, their `sizes` and a `histogram` of
+/// `{"min", "max", "count"}` buckets) or `None`, and the `metadata_coverage`: the share
+/// of the documents that have each metadata key, from 0 to 1.
+#[pyfunction]
+#[pyo3(signature = (
+    docs,
+    chunk_size = None,
+    unit = "characters",
+    encoding = "cl100k_base",
+    overlap_sentences = None,
+    bins = 10
+))]
+pub fn corpus_stats(
+    py: Python,
+    docs: &PyAny,
+    chunk_size: Option<usize>,
+    unit: &str,
+    encoding: &str,
+    overlap_sentences: Option<usize>,
+    bins: usize,
+) -> PyResult<CorpusStats> {
+    if bins == 0 {
+        return Err(PyValueError::new_err("bins must be at least 1"));
+    }
+    let bpe = tokens::encoding(encoding)?;
+    let trial = chunk_size
+        .map(|chunk_size| {
+            CleanAndSplit::new(
+                chunk_size,
+                false,
+                "id",
+                None,
+                "rs_document",
+                overlap_sentences,
+                unit,
+                encoding,
+                0,
+                false,
+            )
+            .map(|task| (chunk_size, task))
+        })
+        .transpose()?;
+    let doc_vec: Vec<Document> = extract_documents(docs)?;
+    let span = tracing::info_span!(
+        "corpus_stats",
+        docs = doc_vec.len(),
+        chunks = tracing::field::Empty
+    )
+    .entered();
+
+    let stats = py.allow_threads(|| {
+        let (mut characters, (mut words, mut token_counts)): (Vec<usize>, (Vec<_>, Vec<_>)) =
+            doc_vec
+                .par_iter()
+                .map(|document| {
+                    let text = &document.page_content;
+                    (
+                        text.chars().count(),
+                        (
+                            text.split_whitespace().count(),
+                            tokens::count_tokens(bpe, text),
+                        ),
+                    )
+                })
+                .unzip();
+        let key_counts = doc_vec
+            .par_iter()
+            .fold(HashMap::new, |mut counts, document| {
+                for key in document.metadata.keys() {
+                    *counts.entry(key.as_str()).or_insert(0usize) += 1;
+                }
+                counts
+            })
+            .reduce(HashMap::new, |mut a, b| {
+                for (key, count) in b {
+                    *a.entry(key).or_insert(0) += count;
+                }
+                a
+            });
+        let chunks = trial.map(|(chunk_size, task)| {
+            let chunks = task.run(&doc_vec);
+            let mut sizes: Vec<usize> = chunks
+                .par_iter()
+                .map(|chunk| task.measure(&chunk.page_content))
+                .collect();
+            ChunkStats {
+                count: sizes.len(),
+                histogram: histogram(&sizes, chunk_size, bins),
+                sizes: LengthStats::new(&mut sizes),
+            }
+        });
+        CorpusStats {
+            documents: doc_vec.len(),
+            characters: LengthStats::new(&mut characters),
+            words: LengthStats::new(&mut words),
+            tokens: LengthStats::new(&mut token_counts),
+            chunks,
+            metadata_coverage: key_counts
+                .into_iter()
+                .map(|(key, count)| (key.to_string(), count as f64 / doc_vec.len() as f64))
+                .collect(),
+        }
+    });
+    if let Some(chunks) = &stats.chunks {
+        span.record("chunks", chunks.count);
+    }
+    Ok(stats)
+}
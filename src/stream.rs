@@ -0,0 +1,290 @@
+#[cfg(feature = "python")]
+use crate::loaders::{decode_read_error, DecodeErrors, DecodingReader};
+use crate::splitters::{char_length, split_and_merge, SEPARATORS};
+use crate::Document;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::{PyBytes, PyString};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read};
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+
+/// The number of bytes requested from the reader at a time.
+const READ_SIZE: usize = 64 * 1024;
+
+/// Splits text read from a reader into chunks, without ever holding the whole text.
+///
+/// The text is read in segments of at least 16 times `chunk_size`, and each segment is
+/// cut at the last paragraph break (or line break, or space) in its second half and
+/// split like `Document::recursive_character_splitter`. Chunks never span the cut
+/// between two segments, which is the only difference from splitting the whole text at
+/// once. Bytes that are not valid UTF-8 are replaced with U+FFFD.
+///
+/// ```
+/// use rs_document::StreamSplitter;
+/// use std::collections::HashMap;
+///
+/// let text = "First paragraph.\n\nSecond paragraph.".as_bytes();
+/// let chunks = StreamSplitter::new(text, 1000, HashMap::new())
+///     .collect::<std::io::Result<Vec<_>>>()
+///     .unwrap();
+/// assert_eq!(chunks[0].page_content, "First paragraph.\n\nSecond paragraph.");
+/// ```
+pub struct StreamSplitter<R> {
+    reader: R,
+    chunk_size: usize,
+    metadata: HashMap<String, String>,
+    /// Decoded text that hasn't been split yet.
+    text: String,
+    /// The start of a UTF-8 sequence that was cut off by the end of the last read.
+    undecoded: Vec<u8>,
+    chunks: VecDeque<String>,
+    finished: bool,
+}
+
+impl<R: Read> StreamSplitter<R> {
+    /// Returns a splitter over the text of `reader`.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Where the text is read from.
+    /// * `chunk_size` - The maximum size of the chunks, in characters.
+    /// * `metadata` - The metadata given to every chunk.
+    pub fn new(reader: R, chunk_size: usize, metadata: HashMap<String, String>) -> Self {
+        StreamSplitter {
+            reader,
+            chunk_size,
+            metadata,
+            text: String::new(),
+            undecoded: Vec::new(),
+            chunks: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    fn segment_size(&self) -> usize {
+        self.chunk_size.saturating_mul(16).max(READ_SIZE)
+    }
+
+    /// Reads until `text` holds a full segment, returning false once the reader is done.
+    fn fill_text(&mut self) -> io::Result<bool> {
+        let mut buffer = vec![0; READ_SIZE];
+        while self.text.len() < self.segment_size() {
+            let read = match self.reader.read(&mut buffer) {
+                Ok(0) => {
+                    if !self.undecoded.is_empty() {
+                        self.undecoded.clear();
+                        self.text.push(char::REPLACEMENT_CHARACTER);
+                    }
+                    return Ok(false);
+                }
+                Ok(read) => read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            };
+            self.undecoded.extend_from_slice(&buffer[..read]);
+            self.decode();
+        }
+        Ok(true)
+    }
+
+    /// Moves the valid UTF-8 in `undecoded` to `text`, leaving an incomplete trailing
+    /// sequence behind for the next read.
+    fn decode(&mut self) {
+        let mut start = 0;
+        loop {
+            match std::str::from_utf8(&self.undecoded[start..]) {
+                Ok(valid) => {
+                    self.text.push_str(valid);
+                    self.undecoded.clear();
+                    return;
+                }
+                Err(error) => {
+                    let valid_end = start + error.valid_up_to();
+                    let valid = std::str::from_utf8(&self.undecoded[start..valid_end])
+                        .expect("from_utf8 checked these bytes");
+                    self.text.push_str(valid);
+                    match error.error_len() {
+                        Some(invalid) => {
+                            self.text.push(char::REPLACEMENT_CHARACTER);
+                            start = valid_end + invalid;
+                        }
+                        None => {
+                            self.undecoded.drain(..valid_end);
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns where to cut `text` so the segment ends on the coarsest separator found in
+    /// its second half, and where the text after the separator starts.
+    fn cut_point(&self) -> (usize, usize) {
+        let mut half = self.text.len() / 2;
+        while !self.text.is_char_boundary(half) {
+            half += 1;
+        }
+        for separator in SEPARATORS.iter().filter(|separator| !separator.is_empty()) {
+            if let Some(index) = self.text[half..].rfind(separator) {
+                return (half + index, half + index + separator.len());
+            }
+        }
+        (half, half)
+    }
+
+    /// Reads and splits the next segment of text.
+    fn split_segment(&mut self) -> io::Result<()> {
+        let more = self.fill_text()?;
+        let (end, rest) = if more {
+            self.cut_point()
+        } else {
+            self.finished = true;
+            (self.text.len(), self.text.len())
+        };
+        let segment = &self.text[..end];
+        self.chunks.extend(split_and_merge(
+            segment,
+            self.chunk_size,
+            SEPARATORS,
+            &char_length(segment),
+        ));
+        self.text.drain(..rest);
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for StreamSplitter<R> {
+    type Item = io::Result<Document>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.chunks.is_empty() && !self.finished {
+            if let Err(error) = self.split_segment() {
+                self.finished = true;
+                return Some(Err(error));
+            }
+        }
+        let page_content = self.chunks.pop_front()?;
+        Some(Ok(Document::new(page_content, self.metadata.clone())))
+    }
+}
+
+/// Reads bytes from a Python file object through its `read` method.
+///
+/// Text files are read as strings and encoded as UTF-8.
+#[cfg(feature = "python")]
+struct PyReader {
+    file: PyObject,
+    /// Bytes of the last read that didn't fit in the caller's buffer.
+    pending: Vec<u8>,
+}
+
+#[cfg(feature = "python")]
+impl Read for PyReader {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            Python::with_gil(|py| -> PyResult<()> {
+                let data = self.file.call_method1(py, "read", (buffer.len(),))?;
+                let data = data.as_ref(py);
+                if let Ok(bytes) = data.downcast::<PyBytes>() {
+                    self.pending.extend_from_slice(bytes.as_bytes());
+                } else {
+                    let text = data.downcast::<PyString>()?.to_str()?;
+                    self.pending.extend_from_slice(text.as_bytes());
+                }
+                Ok(())
+            })
+            .map_err(|error| io::Error::other(error.to_string()))?;
+        }
+        let read = self.pending.len().min(buffer.len());
+        buffer[..read].copy_from_slice(&self.pending[..read]);
+        self.pending.drain(..read);
+        Ok(read)
+    }
+}
+
+/// An iterator over the chunks of a file, split as it is read.
+#[cfg(feature = "python")]
+#[pyclass]
+pub struct ChunkIterator {
+    splitter: StreamSplitter<Box<dyn Read + Send>>,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ChunkIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>, py: Python) -> PyResult<Option<Document>> {
+        let splitter = &mut slf.splitter;
+        let chunk = py.allow_threads(|| splitter.next());
+        chunk.transpose().map_err(decode_read_error)
+    }
+}
+
+/// Splits a file into chunks as it is read, for files too large to load as a Document.
+///
+/// The chunks match `Document.recursive_character_splitter`, except that chunks never
+/// span the paragraph breaks where the file is cut into segments of at least 16 times
+/// `chunk_size`.
+///
+/// # Arguments
+///
+/// * `source` - A path, or a file object opened in binary or text mode.
+/// * `chunk_size` - The maximum size of the chunks.
+/// * `metadata` - The metadata given to every chunk. Chunks of a path also get its
+///   `source`, unless the metadata already has one.
+/// * `encoding` - The encoding of a path or binary file. Defaults to UTF-8. Text files
+///   are already decoded, so it doesn't apply to them.
+/// * `errors` - What to do when the file isn't valid in `encoding`, as in
+///   `load_text_files`. The encoding is detected from the first megabyte of the file,
+///   and invalid bytes after it are replaced.
+///
+/// # Returns
+///
+/// An iterator of Documents, which can be passed straight to the batch functions.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (source, chunk_size, metadata = None, encoding = None, errors = "detect"))]
+pub fn split_stream(
+    source: &PyAny,
+    chunk_size: usize,
+    metadata: Option<HashMap<String, String>>,
+    encoding: Option<&str>,
+    errors: &str,
+) -> PyResult<ChunkIterator> {
+    let errors = errors.parse::<DecodeErrors>()?;
+    let mut metadata = metadata.unwrap_or_default();
+    let reader: Box<dyn Read + Send> = if source.hasattr("read")? {
+        let reader = PyReader {
+            file: source.into(),
+            pending: Vec::new(),
+        };
+        // Text files have an encoding of their own, and give back decoded strings.
+        if source.hasattr("encoding")? {
+            Box::new(reader)
+        } else {
+            let name = match source.getattr("name") {
+                Ok(name) => name.str()?.to_string(),
+                Err(_) => "the file".to_string(),
+            };
+            Box::new(DecodingReader::new(reader, encoding, errors, name)?)
+        }
+    } else {
+        let path: PathBuf = source.extract()?;
+        metadata
+            .entry("source".to_string())
+            .or_insert_with(|| path.display().to_string());
+        let file = std::fs::File::open(&path)?;
+        let name = path.display().to_string();
+        Box::new(DecodingReader::new(file, encoding, errors, name)?)
+    };
+    Ok(ChunkIterator {
+        splitter: StreamSplitter::new(reader, chunk_size, metadata),
+    })
+}
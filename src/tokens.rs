@@ -0,0 +1,64 @@
+use crate::{extract_documents, Document};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+use tiktoken_rs::CoreBPE;
+
+/// Returns the BPE for an encoding name (`"cl100k_base"`) or a model name (`"gpt-4o"`).
+///
+/// Each encoding is only built once, on first use.
+pub(crate) fn encoding(name: &str) -> PyResult<&'static CoreBPE> {
+    let tokenizer = match name {
+        "o200k_base" => Tokenizer::O200kBase,
+        "cl100k_base" => Tokenizer::Cl100kBase,
+        "p50k_base" => Tokenizer::P50kBase,
+        "p50k_edit" => Tokenizer::P50kEdit,
+        "r50k_base" | "gpt2" => Tokenizer::R50kBase,
+        model => get_tokenizer(model).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Unknown encoding or model \"{}\", expected one of o200k_base, cl100k_base, \
+                 p50k_base, p50k_edit, r50k_base or an OpenAI model name",
+                model
+            ))
+        })?,
+    };
+    Ok(match tokenizer {
+        Tokenizer::O200kBase => tiktoken_rs::o200k_base_singleton(),
+        Tokenizer::Cl100kBase => tiktoken_rs::cl100k_base_singleton(),
+        Tokenizer::P50kBase => tiktoken_rs::p50k_base_singleton(),
+        Tokenizer::P50kEdit => tiktoken_rs::p50k_edit_singleton(),
+        Tokenizer::R50kBase | Tokenizer::Gpt2 => tiktoken_rs::r50k_base_singleton(),
+    })
+}
+
+/// Counts the tokens in `text`.
+///
+/// Special tokens such as `<|endoftext|>` are counted as the plain text they are made of.
+pub(crate) fn count_tokens(bpe: &CoreBPE, text: &str) -> usize {
+    bpe.encode_ordinary(text).len()
+}
+
+/// Counts the tokens in the page_content of every document, in parallel.
+///
+/// # Arguments
+///
+/// * `docs` - The documents to count.
+/// * `encoding` - An encoding name such as `"cl100k_base"` or `"o200k_base"`, or an
+///   OpenAI model name such as `"gpt-4o"`.
+///
+/// # Returns
+///
+/// The number of tokens in each document, in the same order as `docs`.
+#[pyfunction]
+#[pyo3(signature = (docs, encoding = "cl100k_base"))]
+pub fn count_tokens_batch(py: Python, docs: &PyAny, encoding: &str) -> PyResult<Vec<usize>> {
+    let bpe = self::encoding(encoding)?;
+    let doc_vec: Vec<Document> = extract_documents(docs)?;
+    Ok(py.allow_threads(|| {
+        doc_vec
+            .par_iter()
+            .map(|document| count_tokens(bpe, &document.page_content))
+            .collect()
+    }))
+}
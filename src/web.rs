@@ -0,0 +1,172 @@
+use crate::aio::{spawn_awaitable, Work};
+use crate::html::parse_html;
+use crate::Document;
+use once_cell::sync::Lazy;
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Builds an HTTP agent that gives up on requests after `timeout` seconds.
+fn agent(timeout: f64) -> PyResult<ureq::Agent> {
+    if !(timeout > 0.0 && timeout.is_finite()) {
+        return Err(PyValueError::new_err("timeout must be a positive number"));
+    }
+    Ok(ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs_f64(timeout))
+        .build())
+}
+
+/// The thread pools built by `fetch_pool`, by their number of threads.
+static FETCH_POOLS: Lazy<Mutex<HashMap<usize, Arc<rayon::ThreadPool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Returns the thread pool that fetches `max_workers` pages at the same time, which is
+/// built the first time and reused by later calls with the same `max_workers`.
+fn fetch_pool(max_workers: usize) -> PyResult<Arc<rayon::ThreadPool>> {
+    let max_workers = max_workers.max(1);
+    let mut pools = FETCH_POOLS.lock().unwrap();
+    if let Some(pool) = pools.get(&max_workers) {
+        return Ok(Arc::clone(pool));
+    }
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(max_workers)
+        .thread_name(|index| format!("rs_document-fetch-{}", index))
+        .build()
+        .map_err(|error| PyOSError::new_err(error.to_string()))?;
+    let pool = Arc::new(pool);
+    pools.insert(max_workers, Arc::clone(&pool));
+    Ok(pool)
+}
+
+/// Fetches a single URL and turns the response into a Document.
+///
+/// HTML responses go through the HTML loader, and anything else is used as plain text.
+/// Responses with an error status are still returned so that callers can inspect the
+/// `status` metadata, but transport failures (DNS, connection, timeouts) raise.
+pub(crate) fn fetch_url(agent: &ureq::Agent, url: &str) -> PyResult<Document> {
+    let response = match agent.get(url).call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(_, response)) => response,
+        Err(ureq::Error::Transport(transport)) => {
+            return Err(PyOSError::new_err(format!(
+                "Failed to fetch {}: {}",
+                url, transport
+            )))
+        }
+    };
+    let fetched_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_secs())
+        .unwrap_or_default();
+
+    let status = response.status();
+    let final_url = response.get_url().to_string();
+    let is_html = response.content_type().contains("html");
+    let body = response
+        .into_string()
+        .map_err(|error| PyOSError::new_err(format!("Failed to read {}: {}", url, error)))?;
+
+    let mut document = if is_html {
+        parse_html(&body)
+    } else {
+        Document {
+            page_content: body,
+            metadata: HashMap::new(),
+        }
+    };
+    document
+        .metadata
+        .insert("source".to_string(), url.to_string());
+    document.metadata.insert("url".to_string(), final_url);
+    document
+        .metadata
+        .insert("status".to_string(), status.to_string());
+    document
+        .metadata
+        .insert("fetched_at".to_string(), fetched_at.to_string());
+    Ok(document)
+}
+
+/// Fetches a web page and returns its readable text as a Document.
+///
+/// HTML pages are run through the same extraction as `Document.from_html`, so the
+/// page's `title`, `description` and `canonical_url` end up in the metadata. The
+/// document also gets the requested `source` URL, the final `url` after redirects, the
+/// HTTP `status` and the `fetched_at` time in seconds since the Unix epoch.
+///
+/// # Arguments
+///
+/// * `url` - The URL to fetch.
+/// * `timeout` - How many seconds to wait for the server before giving up.
+#[pyfunction]
+#[pyo3(signature = (url, timeout = 30.0))]
+pub fn load_url(py: Python, url: &str, timeout: f64) -> PyResult<Document> {
+    let agent = agent(timeout)?;
+    py.allow_threads(|| fetch_url(&agent, url))
+}
+
+/// An awaitable version of `load_url`, with the same arguments.
+///
+/// The page is fetched on a background thread while the event loop keeps running.
+#[pyfunction]
+#[pyo3(signature = (url, timeout = 30.0))]
+pub fn load_url_async<'py>(py: Python<'py>, url: String, timeout: f64) -> PyResult<&'py PyAny> {
+    let agent = agent(timeout)?;
+    spawn_awaitable(
+        py,
+        Work::Blocking,
+        move || fetch_url(&agent, &url),
+        |py, document| Ok(document.into_py(py)),
+    )
+}
+
+/// Fetches many web pages concurrently, like `load_url`.
+///
+/// # Arguments
+///
+/// * `urls` - The URLs to fetch.
+/// * `timeout` - How many seconds to wait for each server before giving up.
+/// * `max_workers` - How many pages are fetched at the same time.
+///
+/// # Returns
+///
+/// The Documents in the same order as `urls`.
+#[pyfunction]
+#[pyo3(signature = (urls, timeout = 30.0, max_workers = 16))]
+pub fn load_urls(
+    py: Python,
+    urls: Vec<String>,
+    timeout: f64,
+    max_workers: usize,
+) -> PyResult<Vec<Document>> {
+    let agent = agent(timeout)?;
+    let pool = fetch_pool(max_workers)?;
+    py.allow_threads(|| {
+        pool.install(|| urls.par_iter().map(|url| fetch_url(&agent, url)).collect())
+    })
+}
+
+/// An awaitable version of `load_urls`, with the same arguments.
+///
+/// The pages are fetched on background threads while the event loop keeps running.
+/// Awaiting the result gives the Documents in the same order as `urls`.
+#[pyfunction]
+#[pyo3(signature = (urls, timeout = 30.0, max_workers = 16))]
+pub fn load_urls_async<'py>(
+    py: Python<'py>,
+    urls: Vec<String>,
+    timeout: f64,
+    max_workers: usize,
+) -> PyResult<&'py PyAny> {
+    let agent = agent(timeout)?;
+    let pool = fetch_pool(max_workers)?;
+    spawn_awaitable(
+        py,
+        Work::Blocking,
+        move || pool.install(|| urls.par_iter().map(|url| fetch_url(&agent, url)).collect()),
+        |py, documents: Vec<Document>| Ok(documents.into_py(py)),
+    )
+}
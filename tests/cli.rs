@@ -0,0 +1,93 @@
+//! Runs the `rs-document` binary on small inputs.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Runs `rs-document` with `args`, writing `input` to its stdin.
+fn run(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rs-document"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("rs-document starts");
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(input.as_bytes())
+        .expect("input is written");
+    child.wait_with_output().expect("rs-document runs")
+}
+
+#[test]
+fn splits_stdin_into_json_lines() {
+    let output = run(
+        &["split", "--splitter", "characters", "--chunk-size", "5"],
+        "hello world",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let chunks: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let contents: Vec<&str> = chunks
+        .iter()
+        .map(|chunk| chunk["page_content"].as_str().unwrap())
+        .collect();
+    assert_eq!(contents, ["hello", " worl", "d"]);
+    assert_eq!(chunks[0]["metadata"]["source"], "-");
+}
+
+#[test]
+fn rejects_chunk_sizes_the_characters_splitter_cannot_count() {
+    let output = run(
+        &[
+            "split",
+            "--splitter",
+            "characters",
+            "--chunk-size",
+            "4294967296",
+        ],
+        "hello world",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("--chunk-size must be at most 4294967295"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn chunk_size_counts_characters() {
+    let input = "Grüße aus Köln, schöne Straße. ".repeat(20);
+    for splitter in ["recursive", "markdown"] {
+        let output = run(
+            &[
+                "split",
+                "--clean",
+                "none",
+                "--splitter",
+                splitter,
+                "--chunk-size",
+                "60",
+            ],
+            &input,
+        );
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let contents: Vec<String> = stdout
+            .lines()
+            .map(|line| {
+                let chunk: serde_json::Value = serde_json::from_str(line).unwrap();
+                chunk["page_content"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert!(contents.iter().all(|chunk| chunk.chars().count() <= 60));
+        assert!(contents.iter().any(|chunk| chunk.len() > 60));
+    }
+}